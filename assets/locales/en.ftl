@@ -0,0 +1,269 @@
+# English, the built-in fallback for every other language.
+# Each message is `key = text`; { $name } fills in an argument and
+# { $n -> [one] ... *[other] ... } picks a form by number.
+
+## Battle log and journal
+
+log-battle-start = >Battle Start
+log-player-defeated = You were defeated...
+log-enemy-defeated = Enemy defeated!
+log-enemy-blocked = The enemy's guard held, it took only { $amount } damage.
+log-player-blocked = Your guard held, you took only { $amount } damage.
+log-player-guard = You raised your guard. Focus { $amount }/{ $max }.
+log-player-attack-miss = Your attack missed!
+log-player-attack-dodged = The enemy dodged your attack!
+log-player-attack-crit = Critical hit! Enemy was attacked for { $amount } damage!
+log-player-attack-graze = Your attack grazed the enemy for { $amount } damage.
+log-player-attack = Enemy was attacked for { $amount } damage!
+log-player-magic-miss = Your magic had no effect!
+log-player-magic = Enemy was attacked with magic for { $amount } damage!
+log-player-spell-miss = Your { $element } spell had no effect!
+log-player-spell-weak = Enemy was struck with { $element } for { $amount } damage! It's super effective!
+log-player-spell-resisted = Enemy was struck with { $element } for { $amount } damage. It's not very effective...
+log-player-spell = Enemy was struck with { $element } for { $amount } damage!
+log-player-heal = Player healed for { $amount } hp!
+log-player-run-blocked = There's no running from this fight!
+log-player-run = You ran away!
+log-player-wait = You waited for an opening.
+log-enemy-guard = Enemy raised its guard!
+log-enemy-attack-miss = Enemy's attack missed!
+log-enemy-attack-dodged = You dodged the enemy's attack!
+log-enemy-attack-crit = Critical hit! Enemy attacked you for { $amount } damage!
+log-enemy-attack-graze = Enemy's attack grazed you for { $amount } damage.
+log-enemy-attack = Enemy attacked you for { $amount } damage!
+log-enemy-magic-miss = Enemy's psychic force had no effect!
+log-enemy-magic = Enemy attacked you with a psychic force for { $amount } damage!
+log-enemy-heal = Enemy healed for { $amount } hp!
+log-enemy-run = Enemy fled!
+log-add-miss = You dodged a flyder's bite!
+log-add-crit = A flyder bit deep for { $amount } damage!
+log-add = A flyder bit you for { $amount } damage!
+log-enemy-wait = Enemy waited.
+log-scroll-hint = (PgUp/PgDn to scroll)
+
+journal-title = Battle Journal
+journal-help = W/S or Scroll: scroll   J: close
+journal-battle = -- Battle { $battle } --
+journal-empty = No battles fought yet.
+
+## Spells
+
+element-fire = fire
+element-ice = ice
+element-lightning = lightning
+element-holy = holy
+element-shadow = shadow
+spell-selected = Spell: { $spell } (E to change)
+spell-plain = plain magic
+
+## Battle menu
+
+battle-prompt = What will you do?
+battle-prompt-attack = Choose your attack:
+menu-fight = Fight >
+menu-attack = Attack
+menu-magic = Magic
+menu-cast = Cast { $element }
+menu-heal = Heal
+menu-run = Run
+menu-wait = Wait
+menu-guard = Guard
+menu-back = < Back
+tooltip-fight = Attack, magic and the spells you know
+tooltip-back = Back to the main menu
+tooltip-damage = { $low } damage
+tooltip-damage-range = { $low }-{ $high } damage
+tooltip-hit = { $damage }, { $chance }% to hit{ $affinity ->
+        [weak] , it's weak to this!
+        [resisted] , it resists this
+       *[neutral] {""}
+    }
+tooltip-heal = Restores { $amount } HP
+tooltip-full-hp = Already at full HP
+tooltip-run = Escape from the battle
+tooltip-run-boss = The boss blocks the way out
+tooltip-guard = Halve the next hit and build focus for an attack
+tooltip-wait = Pass, but get to act again sooner
+action-text-attack = Attacked!
+action-text-magic = Magic Attacked!
+action-text-spell = Cast { $element }!
+action-text-heal = Healed!
+action-text-run = You ran away!
+action-text-wait = You wait...
+action-text-guard = You raise your guard!
+
+## Over the fighters
+
+hit-miss = MISS
+hit-dodge = DODGE
+hit-crit = { $amount }! CRITICAL
+hit-graze = { $amount } graze
+hit-blocked = { $amount } blocked
+hit-super-effective = { $amount } super effective!
+hit-resisted = { $amount } resisted
+
+## End of a battle
+
+victory-title = Victory!
+victory-skill-points = +{ $points } { $points ->
+        [one] skill point
+       *[other] skill points
+    }
+victory-continue = Press Enter to continue
+defeat-title = You were defeated
+defeat-slain-by = Slain by { $enemy }
+defeat-last-moments = Final moments:
+defeat-respawn = Respawn at the last checkpoint
+defeat-penalty = { $points ->
+        [0] No penalty
+        [one] Costs 1 skill point
+       *[other] Costs { $points } skill points
+    }
+defeat-restart = Restart with a new dungeon
+defeat-title-screen = Return to title
+enemy-stray = a stray
+enemy-hunter = a hunter
+enemy-lurker = a lurker
+enemy-flyder = a flyder
+
+## The boss
+
+boss-name = The Warden
+boss-label = { $name } - { $phase }
+boss-phase-warden = Warden
+boss-phase-enraged = Enraged
+boss-phase-desperate = Desperate
+boss-dialogue-warden = The Warden of the Labyrinth blocks the way out!
+boss-dialogue-enraged = The Warden roars and lashes out faster!
+boss-dialogue-desperate = Cornered, the Warden calls flyders to its side!
+
+## Skill tree
+
+skill-tree-exit = Press 'Q' to exit
+skill-tree-help =
+    Press 'F, G, H, or K' to upgrade strength, magic, agility, or health
+    up to a base total of 7
+    Click on a node to unlock bonus stats
+
+## Map
+
+map-help = WASD/Arrows: pan   Scroll or +/-: zoom   Click: place/remove marker   M: close map
+map-start = Start
+map-boss = Boss
+map-exit = Exit
+map-maze = Maze
+map-shrine = Shrine
+
+## Controls
+
+controls-title = Controls
+controls-help = W/S: select   A/D: switch screen   Enter: rebind   R: reset all   F1/Esc: close
+controls-press-key = press a key (Esc to cancel)
+controls-conflict = { $key } is already bound to { $action } here
+context-overworld = Overworld
+context-battle = Battle
+context-menu = Menus
+action-up = Up
+action-down = Down
+action-left = Left
+action-right = Right
+action-confirm = Confirm
+action-back = Back
+action-skill-tree = Skill tree
+action-map = Map
+action-journal = Journal
+action-controls = Controls
+action-pause = Pause
+action-cycle-zoom = Camera zoom
+action-interact = Interact
+action-quick-save = Quick save
+action-quick-load = Quick load
+action-replay = Replay last battle
+action-spend-strength = Spend point: strength
+action-spend-magic = Spend point: magic
+action-spend-agility = Spend point: agility
+action-spend-health = Spend point: health
+action-attack = Attack
+action-magic = Magic
+action-heal = Heal
+action-run = Run
+action-wait = Wait
+action-guard = Guard
+action-cycle-spell = Change spell
+action-log-up = Scroll log up
+action-log-down = Scroll log down
+action-zoom-in = Zoom in
+action-zoom-out = Zoom out
+action-reset-bindings = Reset to defaults
+
+## Pause menu
+
+pause-title = Paused
+pause-resume = Resume
+pause-settings = Settings
+pause-save = Save
+pause-load = Load
+pause-quit-title = Quit to Title
+pause-quit-desktop = Quit to Desktop
+pause-battle-note = Saving and loading wait until the battle is over
+pause-saved = Game saved
+pause-save-failed = Could not save the game
+
+## Settings
+
+settings-title = Settings
+settings-help = Up/Down: select   Left/Right or click: change   Esc: back
+settings-language = Language: < { $language } >
+settings-window = Window: < { $mode } >
+settings-resolution = Resolution: < { $width }x{ $height } >
+settings-vsync = VSync: < { $vsync ->
+        [on] On
+       *[off] Off
+    } >
+settings-master-volume = Master volume: < { $percent }% >
+settings-music-volume = Music volume: < { $percent }% >
+settings-sfx-volume = Sound volume: < { $percent }% >
+settings-text-speed = Battle text speed: < { $speed } >
+settings-ui-scale = UI scale: < { $percent }% >
+settings-text-scale = Text size: < { $percent }% >
+settings-colorblind = Colorblind-safe colors: < { $on ->
+        [on] On
+       *[off] Off
+    } >
+settings-high-contrast-log = High-contrast battle log: < { $on ->
+        [on] On
+       *[off] Off
+    } >
+settings-reduced-motion = Reduced motion: < { $on ->
+        [on] On
+       *[off] Off
+    } >
+settings-enemy-movement = Enemy movement: < { $movement } >
+settings-death-penalty = Death penalty: < { $penalty } >
+settings-shrine-autosave = Autosave at shrines: < { $on ->
+        [on] On
+       *[off] Off
+    } >
+settings-shrine-spacing = Shrines: < { $rooms ->
+        [0] None
+       *[other] Every { $rooms } rooms
+    } >
+settings-controls = Key bindings
+settings-back = Back
+window-windowed = Windowed
+window-borderless = Borderless
+window-fullscreen = Fullscreen
+speed-slow = Slow
+speed-normal = Normal
+speed-fast = Fast
+movement-normal = Normal
+movement-slow = Slow
+movement-still = Still
+penalty-none = None
+penalty-light = 1 skill point
+penalty-harsh = Half your skill points
+
+## Shrines
+
+shrine-prompt = Press { $key } to rest at the shrine
+shrine-rested = The shrine's light restores you. You will return here if you fall.
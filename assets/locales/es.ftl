@@ -0,0 +1,267 @@
+# Español. Las claves son las mismas que en en.ftl; lo que falte aquí se muestra en inglés.
+
+## Registro de combate y diario
+
+log-battle-start = >Comienza el combate
+log-player-defeated = Has sido derrotado...
+log-enemy-defeated = ¡Enemigo derrotado!
+log-enemy-blocked = La guardia del enemigo aguantó, solo recibió { $amount } de daño.
+log-player-blocked = Tu guardia aguantó, solo recibiste { $amount } de daño.
+log-player-guard = Te pones en guardia. Concentración { $amount }/{ $max }.
+log-player-attack-miss = ¡Tu ataque falló!
+log-player-attack-dodged = ¡El enemigo esquivó tu ataque!
+log-player-attack-crit = ¡Golpe crítico! ¡El enemigo recibió { $amount } de daño!
+log-player-attack-graze = Tu ataque rozó al enemigo e hizo { $amount } de daño.
+log-player-attack = ¡El enemigo recibió { $amount } de daño!
+log-player-magic-miss = ¡Tu magia no surtió efecto!
+log-player-magic = ¡Tu magia hizo { $amount } de daño al enemigo!
+log-player-spell-miss = ¡Tu hechizo de { $element } no surtió efecto!
+log-player-spell-weak = ¡Tu hechizo de { $element } hizo { $amount } de daño al enemigo! ¡Es muy eficaz!
+log-player-spell-resisted = Tu hechizo de { $element } hizo { $amount } de daño al enemigo. No es muy eficaz...
+log-player-spell = ¡Tu hechizo de { $element } hizo { $amount } de daño al enemigo!
+log-player-heal = ¡Recuperas { $amount } PV!
+log-player-run-blocked = ¡De este combate no se puede huir!
+log-player-run = ¡Has huido!
+log-player-wait = Esperas una oportunidad.
+log-enemy-guard = ¡El enemigo se pone en guardia!
+log-enemy-attack-miss = ¡El ataque del enemigo falló!
+log-enemy-attack-dodged = ¡Esquivaste el ataque del enemigo!
+log-enemy-attack-crit = ¡Golpe crítico! ¡El enemigo te hizo { $amount } de daño!
+log-enemy-attack-graze = El ataque del enemigo te rozó e hizo { $amount } de daño.
+log-enemy-attack = ¡El enemigo te hizo { $amount } de daño!
+log-enemy-magic-miss = ¡La fuerza psíquica del enemigo no surtió efecto!
+log-enemy-magic = ¡La fuerza psíquica del enemigo te hizo { $amount } de daño!
+log-enemy-heal = ¡El enemigo recupera { $amount } PV!
+log-enemy-run = ¡El enemigo huyó!
+log-add-miss = ¡Esquivaste el mordisco de un flyder!
+log-add-crit = ¡Un flyder te mordió a fondo e hizo { $amount } de daño!
+log-add = ¡Un flyder te mordió e hizo { $amount } de daño!
+log-enemy-wait = El enemigo espera.
+log-scroll-hint = (RePág/AvPág para desplazar)
+
+journal-title = Diario de combate
+journal-help = W/S o rueda: desplazar   J: cerrar
+journal-battle = -- Combate { $battle } --
+journal-empty = Todavía no has librado ningún combate.
+
+## Hechizos
+
+element-fire = fuego
+element-ice = hielo
+element-lightning = rayo
+element-holy = luz
+element-shadow = sombra
+spell-selected = Hechizo: { $spell } (E para cambiar)
+spell-plain = magia simple
+
+## Menú de combate
+
+battle-prompt = ¿Qué vas a hacer?
+battle-prompt-attack = Elige tu ataque:
+menu-fight = Luchar >
+menu-attack = Atacar
+menu-magic = Magia
+menu-cast = Lanzar { $element }
+menu-heal = Curar
+menu-run = Huir
+menu-wait = Esperar
+menu-guard = Guardia
+menu-back = < Volver
+tooltip-fight = Ataque, magia y los hechizos que conoces
+tooltip-back = Volver al menú principal
+tooltip-damage = { $low } de daño
+tooltip-damage-range = { $low }-{ $high } de daño
+tooltip-hit = { $damage }, { $chance }% de acierto{ $affinity ->
+        [weak] , ¡es débil a esto!
+        [resisted] , lo resiste
+       *[neutral] {""}
+    }
+tooltip-heal = Recupera { $amount } PV
+tooltip-full-hp = Ya tienes los PV al máximo
+tooltip-run = Escapar del combate
+tooltip-run-boss = El jefe bloquea la salida
+tooltip-guard = Reduce a la mitad el próximo golpe y acumula concentración para atacar
+tooltip-wait = Pasar el turno, pero volver a actuar antes
+action-text-attack = ¡Atacas!
+action-text-magic = ¡Ataque mágico!
+action-text-spell = ¡Lanzas { $element }!
+action-text-heal = ¡Te curas!
+action-text-run = ¡Has huido!
+action-text-wait = Esperas...
+action-text-guard = ¡Te pones en guardia!
+
+## Sobre los combatientes
+
+hit-miss = FALLO
+hit-dodge = ESQUIVA
+hit-crit = ¡{ $amount }! CRÍTICO
+hit-graze = { $amount } roce
+hit-blocked = { $amount } bloqueado
+hit-super-effective = ¡{ $amount } muy eficaz!
+hit-resisted = { $amount } resistido
+
+## Fin del combate
+
+victory-title = ¡Victoria!
+victory-skill-points = +{ $points } { $points ->
+        [one] punto de habilidad
+       *[other] puntos de habilidad
+    }
+victory-continue = Pulsa Intro para continuar
+defeat-title = Has sido derrotado
+defeat-slain-by = Abatido por { $enemy }
+defeat-last-moments = Últimos momentos:
+defeat-respawn = Reaparecer en el último punto de control
+defeat-penalty = { $points ->
+        [0] Sin penalización
+        [one] Cuesta 1 punto de habilidad
+       *[other] Cuesta { $points } puntos de habilidad
+    }
+defeat-restart = Empezar de nuevo con otra mazmorra
+defeat-title-screen = Volver al título
+enemy-stray = un errante
+enemy-hunter = un cazador
+enemy-lurker = un acechador
+enemy-flyder = un flyder
+
+## El jefe
+
+boss-name = El Guardián
+boss-label = { $name } - { $phase }
+boss-phase-warden = Guardián
+boss-phase-enraged = Furioso
+boss-phase-desperate = Desesperado
+boss-dialogue-warden = ¡El Guardián del Laberinto bloquea la salida!
+boss-dialogue-enraged = ¡El Guardián ruge y ataca más rápido!
+boss-dialogue-desperate = ¡Acorralado, el Guardián llama a los flyders en su ayuda!
+
+## Árbol de habilidades
+
+skill-tree-exit = Pulsa 'Q' para salir
+skill-tree-help =
+    Pulsa 'F, G, H o K' para mejorar fuerza, magia, agilidad o salud
+    hasta un total base de 7
+    Haz clic en un nodo para desbloquear estadísticas extra
+
+## Mapa
+
+map-help = WASD/Flechas: mover   Rueda o +/-: zoom   Clic: poner/quitar marca   M: cerrar mapa
+map-start = Inicio
+map-boss = Jefe
+map-exit = Salida
+map-maze = Laberinto
+map-shrine = Santuario
+
+## Controles
+
+controls-title = Controles
+controls-help = W/S: elegir   A/D: cambiar pantalla   Intro: reasignar   R: restablecer todo   F1/Esc: cerrar
+controls-press-key = pulsa una tecla (Esc para cancelar)
+controls-conflict = { $key } ya está asignada a { $action } aquí
+context-overworld = Exploración
+context-battle = Combate
+context-menu = Menús
+action-up = Arriba
+action-down = Abajo
+action-left = Izquierda
+action-right = Derecha
+action-confirm = Confirmar
+action-back = Volver
+action-skill-tree = Árbol de habilidades
+action-map = Mapa
+action-journal = Diario
+action-controls = Controles
+action-pause = Pausa
+action-cycle-zoom = Zoom de cámara
+action-interact = Interactuar
+action-quick-save = Guardado rápido
+action-quick-load = Carga rápida
+action-replay = Repetir último combate
+action-spend-strength = Gastar punto: fuerza
+action-spend-magic = Gastar punto: magia
+action-spend-agility = Gastar punto: agilidad
+action-spend-health = Gastar punto: salud
+action-attack = Atacar
+action-magic = Magia
+action-heal = Curar
+action-run = Huir
+action-wait = Esperar
+action-guard = Guardia
+action-cycle-spell = Cambiar hechizo
+action-log-up = Subir registro
+action-log-down = Bajar registro
+action-zoom-in = Acercar
+action-zoom-out = Alejar
+action-reset-bindings = Restablecer valores
+
+## Menú de pausa
+
+pause-title = Pausa
+pause-resume = Continuar
+pause-settings = Opciones
+pause-save = Guardar
+pause-load = Cargar
+pause-quit-title = Salir al título
+pause-quit-desktop = Salir al escritorio
+pause-battle-note = Guardar y cargar esperan a que termine el combate
+pause-saved = Partida guardada
+pause-save-failed = No se pudo guardar la partida
+
+## Opciones
+
+settings-title = Opciones
+settings-help = Arriba/Abajo: elegir   Izquierda/Derecha o clic: cambiar   Esc: volver
+settings-language = Idioma: < { $language } >
+settings-window = Ventana: < { $mode } >
+settings-resolution = Resolución: < { $width }x{ $height } >
+settings-vsync = VSync: < { $vsync ->
+        [on] Sí
+       *[off] No
+    } >
+settings-master-volume = Volumen general: < { $percent }% >
+settings-music-volume = Volumen de la música: < { $percent }% >
+settings-sfx-volume = Volumen de los efectos: < { $percent }% >
+settings-text-speed = Velocidad del texto de combate: < { $speed } >
+settings-ui-scale = Escala de la interfaz: < { $percent }% >
+settings-text-scale = Tamaño del texto: < { $percent }% >
+settings-colorblind = Colores para daltónicos: < { $on ->
+        [on] Sí
+       *[off] No
+    } >
+settings-high-contrast-log = Registro de combate en alto contraste: < { $on ->
+        [on] Sí
+       *[off] No
+    } >
+settings-reduced-motion = Movimiento reducido: < { $on ->
+        [on] Sí
+       *[off] No
+    } >
+settings-enemy-movement = Movimiento de los enemigos: < { $movement } >
+settings-death-penalty = Penalización por muerte: < { $penalty } >
+settings-shrine-autosave = Guardar en los santuarios: < { $on ->
+        [on] Sí
+       *[off] No
+    } >
+settings-shrine-spacing = Santuarios: < { $rooms ->
+        [0] Ninguno
+       *[other] Cada { $rooms } salas
+    } >
+settings-controls = Asignación de teclas
+settings-back = Volver
+window-windowed = Ventana
+window-borderless = Sin bordes
+window-fullscreen = Pantalla completa
+speed-slow = Lenta
+speed-normal = Normal
+speed-fast = Rápida
+movement-normal = Normal
+movement-slow = Lento
+movement-still = Quietos
+penalty-none = Ninguna
+penalty-light = 1 punto de habilidad
+penalty-harsh = La mitad de tus puntos de habilidad
+
+## Santuarios
+
+shrine-prompt = Pulsa { $key } para descansar en el santuario
+shrine-rested = La luz del santuario te restaura. Volverás aquí si caes.
@@ -0,0 +1,218 @@
+// Headless battle-balance simulator: pits player builds against every enemy type using the
+// game's own combat code and prints win rate, turns to kill and hp remaining.
+//
+//   cargo run -p balance_sim -- [--battles N] [--seed S] [--brain NAME] [--csv]
+//
+// --brain makes every enemy type fight with one brain (attack, random, heuristic, adaptive or mcts)
+// so strategies can be compared against the same builds.
+use std::env;
+
+use rand::prelude::*;
+
+use main::brain::{brain_for, brain_named, EnemyBrain};
+use main::battle::{elemental_attack, magic_attack, physical_strike, player_turn, enemy_turn, PlayerAction, TurnResult};
+use main::battle_log::BattleLog;
+use main::enemy::EnemyStats;
+use main::player::{BonusStats, PlayerStats};
+use main::turn_order::{player_acts_next, start_turn_order};
+use main::skill_tree::{apply_node, node_cost};
+use main::utility::UtilityConfig;
+
+const ENEMY_TYPES: [(u32, &str); 3] = [(1, "basic"), (2, "advanced"), (3, "boss")];
+const MAX_TURNS: u32 = 100;         // a battle still going after this many player turns counts as a loss
+const HEAL_BELOW: f32 = 0.35;       // the simulated player heals under this fraction of max hp
+const SAMPLE_ROLLS: u32 = 200;      // rolls used to estimate which attack does more damage
+
+struct Build {
+    name: &'static str,
+    strength: u32,
+    magic: u32,
+    agility: u32,
+    health: u32,
+    nodes: &'static [u32],  // unlocked skill tree nodes, listed in an order the tree allows
+}
+
+// the 12 starting ability points spread on top of the minimum of 1 in each score
+const BUILDS: [Build; 6] = [
+    Build { name: "fresh", strength: 1, magic: 1, agility: 1, health: 1, nodes: &[] },
+    Build { name: "balanced", strength: 4, magic: 4, agility: 4, health: 4, nodes: &[] },
+    Build { name: "fighter", strength: 7, magic: 1, agility: 2, health: 6, nodes: &[0, 1] },
+    Build { name: "mage", strength: 1, magic: 7, agility: 2, health: 6, nodes: &[0, 1, 2] },
+    Build { name: "fighter+", strength: 7, magic: 1, agility: 2, health: 6, nodes: &[0, 1, 2, 3, 4, 5, 9, 10, 11] },
+    Build { name: "mage+", strength: 1, magic: 7, agility: 2, health: 6, nodes: &[0, 1, 2, 6, 7, 8, 15, 16, 17] },
+];
+
+impl Build {
+    fn player_stats(&self) -> PlayerStats {
+        let mut player_stats = PlayerStats::new();
+        let mut bonus_stats = BonusStats::new();
+        player_stats.strength = self.strength;
+        player_stats.magic = self.magic;
+        player_stats.agility = self.agility;
+        player_stats.health = self.health;
+        for node in self.nodes.iter() {
+            apply_node(*node, &mut player_stats, &mut bonus_stats);
+        }
+        player_stats.update_stats(&bonus_stats);
+        // every simulated battle starts at full health
+        player_stats.hp = player_stats.max_hp;
+        player_stats
+    }
+
+    fn skill_points(&self) -> u32 {
+        self.nodes.iter().map(|node| node_cost(*node)).sum()
+    }
+}
+
+#[derive(Default)]
+struct Results {
+    battles: u32,
+    wins: u32,
+    turns: u32,     // summed over won battles
+    hp_left: u32,   // summed over won battles
+}
+
+impl Results {
+    fn win_rate(&self) -> f32 {
+        100. * self.wins as f32 / self.battles.max(1) as f32
+    }
+
+    fn avg_turns(&self) -> f32 {
+        self.turns as f32 / self.wins.max(1) as f32
+    }
+
+    fn avg_hp_left(&self) -> f32 {
+        self.hp_left as f32 / self.wins.max(1) as f32
+    }
+}
+
+// the strongest of attack, magic and the spells the build knows against this enemy, or heal when low
+fn choose_action(player_stats: &PlayerStats, enemy_stats: &EnemyStats, rng: &mut impl Rng) -> PlayerAction {
+    if (player_stats.hp as f32) < HEAL_BELOW * player_stats.max_hp as f32 {
+        return PlayerAction::Heal;
+    }
+    // estimate from a separate rng so the battle's own rolls are not consumed
+    let mut sample_rng = StdRng::seed_from_u64(rng.gen());
+    let mut best = (PlayerAction::Attack, 0);
+    let mut candidates = vec![PlayerAction::Attack, PlayerAction::Magic];
+    candidates.extend(player_stats.spells.iter().map(|element| PlayerAction::Spell(*element)));
+    for action in candidates {
+        let mut total = 0;
+        for _ in 0..SAMPLE_ROLLS {
+            total += match action {
+                PlayerAction::Attack => physical_strike(5, player_stats.atk, enemy_stats.physdef, player_stats.spd, enemy_stats.speed, &mut sample_rng).0,
+                PlayerAction::Spell(element) => elemental_attack(element, player_stats.matk, enemy_stats, &mut sample_rng).0,
+                _ => magic_attack(5, player_stats.matk, enemy_stats.mgkdef, &mut sample_rng),
+            };
+        }
+        if total > best.1 {
+            best = (action, total);
+        }
+    }
+    best.0
+}
+
+// plays one battle to the end, returns whether the player won, the turns taken and the hp left
+fn simulate_battle(build: &PlayerStats, etype: u32, brain: &mut dyn EnemyBrain, rng: &mut StdRng) -> (bool, u32, u32) {
+    let mut player_stats = build.clone();
+    let mut enemy_stats = EnemyStats::new(etype);
+    let mut battle_log = BattleLog::default();
+    battle_log.start_battle();
+    start_turn_order(&mut player_stats, &mut enemy_stats);
+
+    for turn in 1..=MAX_TURNS {
+        let action = choose_action(&player_stats, &enemy_stats, rng);
+        match player_turn(action, &mut player_stats, &mut enemy_stats, rng, &mut battle_log) {
+            TurnResult::Victory => return (true, turn, player_stats.hp),
+            TurnResult::Fled => return (false, turn, player_stats.hp),
+            TurnResult::Continue => {
+                while player_stats.hp > 0 && !player_acts_next(&player_stats, &enemy_stats) {
+                    enemy_turn(&mut player_stats, &mut enemy_stats, brain, rng, &mut battle_log);
+                }
+            }
+        }
+        if player_stats.hp == 0 {
+            return (false, turn, 0);
+        }
+    }
+    (false, MAX_TURNS, player_stats.hp)
+}
+
+fn main() {
+    let mut battles: u32 = 1000;
+    let mut seed: u64 = 0;
+    let mut csv = false;
+    let mut brain_name: Option<String> = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--battles" => battles = args.next().and_then(|n| n.parse().ok()).unwrap_or(battles),
+            "--seed" => seed = args.next().and_then(|n| n.parse().ok()).unwrap_or(seed),
+            "--brain" => brain_name = args.next(),
+            "--csv" => csv = true,
+            _ => {
+                eprintln!("usage: balance_sim [--battles N] [--seed S] [--brain NAME] [--csv]");
+                return;
+            }
+        }
+    }
+
+    let config = UtilityConfig::load();
+    if let Some(name) = &brain_name {
+        if brain_named(name, &config).is_none() {
+            eprintln!("unknown brain '{}', expected attack, random, heuristic, adaptive or mcts", name);
+            return;
+        }
+    }
+
+    if csv {
+        println!("build,str,mag,agi,hp,skill_points,enemy,brain,battles,win_rate,avg_turns,avg_hp_left");
+    } else {
+        println!(
+            "{:<10} {:>3} {:>3} {:>3} {:>3} {:>3}  {:<9} {:<10} {:>8} {:>9} {:>8}",
+            "build", "str", "mag", "agi", "hp", "sp", "enemy", "brain", "win %", "turns", "hp left"
+        );
+    }
+
+    for (i, build) in BUILDS.iter().enumerate() {
+        let player_stats = build.player_stats();
+        for (j, (etype, enemy_name)) in ENEMY_TYPES.iter().enumerate() {
+            // each matchup gets its own stream so adding a build does not change the others
+            let mut rng = StdRng::seed_from_u64(seed + (i * ENEMY_TYPES.len() + j) as u64);
+            let mut results = Results::default();
+            let mut brain_used = "";
+            for _ in 0..battles {
+                // a fresh brain per battle, the same way every enemy spawns with its own
+                let mut brain = match &brain_name {
+                    Some(name) => brain_named(name, &config).unwrap_or_else(|| brain_for(*etype, &config)),
+                    None => brain_for(*etype, &config),
+                };
+                brain_used = brain.name();
+                let (won, turns, hp_left) = simulate_battle(&player_stats, *etype, brain.as_mut(), &mut rng);
+                results.battles += 1;
+                if won {
+                    results.wins += 1;
+                    results.turns += turns;
+                    results.hp_left += hp_left;
+                }
+            }
+
+            if csv {
+                println!(
+                    "{},{},{},{},{},{},{},{},{},{:.1},{:.2},{:.2}",
+                    build.name, build.strength, build.magic, build.agility, build.health,
+                    build.skill_points(), enemy_name, brain_used, results.battles,
+                    results.win_rate(), results.avg_turns(), results.avg_hp_left()
+                );
+            } else {
+                println!(
+                    "{:<10} {:>3} {:>3} {:>3} {:>3} {:>3}  {:<9} {:<10} {:>7.1}% {:>9.2} {:>8.2}",
+                    build.name, build.strength, build.magic, build.agility, build.health,
+                    build.skill_points(), enemy_name, brain_used,
+                    results.win_rate(), results.avg_turns(), results.avg_hp_left()
+                );
+            }
+        }
+    }
+}
@@ -1,99 +1,224 @@
-use bevy::prelude::*;
 use rand::prelude::*;
-use crate::GameState;
 
-use crate::player::Player;
-use crate::enemy::Enemy;
+use crate::battle_log::{BattleLog, Actor, BattleAction, Outcome};
+use crate::brain::EnemyAction;
+use crate::element::{Element, affinity};
 
-use crate::player::PlayerStats;
-use crate::enemy::EnemyStats;
+const START_VALUE: f32 = 5.;        // what an untried action is expected to deal, so each gets tried
+const LEARNING_RATE: f32 = 0.5;     // how far one exchange moves an action's value
+const VALUE_WEIGHT: f32 = 0.5;      // learned value against the stat heuristic in the decision
+const STYLE_RATE: f32 = 0.5;        // how quickly the enemy notices the player switching styles
+const STYLE_WEIGHT: f32 = 4.;
+const ELEMENT_WEIGHT: f32 = 4.;     // how much the player's favourite element being a weakness pushes toward healing
 
+// What an advanced enemy has learned from the battle log so far.
+// values are indexed by EnemyAction: physical, magic, heal
+#[derive(Clone, Debug)]
+pub struct EnemyMemory {
+    pub values: [f32; 3],
+    // -1 when the player keeps attacking, 1 when they keep using magic
+    pub player_style: f32,
+    // spells of each element the player has cast this battle, indexed like Element::ALL
+    pub elements: [u32; 5],
+    battle: u32,    // battle the log entries below were read from
+    seen: usize,    // entries of that battle already learned from
+}
 
-pub fn choose_attack(
-    player_stat_query: &mut Query<&mut PlayerStats, With<Player>>,
-    enemy_stat_query: &mut Query<&mut EnemyStats, With<Enemy>>,
-)
--> u32
-{
-    let enemy_stats = match enemy_stat_query.get_single_mut() {
-        Ok(stats) => stats,
-        Err(_) => return 0,
-    };
-    let player_stats = match player_stat_query.get_single_mut() {
-        Ok(stats) => stats,
-        Err(_) => return 0,
-    };
-    match enemy_stat_query.single().etype {
-        1 => rand_attack(),
-        2 => ai_attack(player_stat_query, enemy_stat_query),
-        _ => 0,
+impl Default for EnemyMemory {
+    fn default() -> Self {
+        Self {
+            values: [START_VALUE; 3],
+            player_style: 0.,
+            elements: [0; 5],
+            battle: 0,
+            seen: 0,
+        }
     }
 }
 
-fn rand_attack()
--> u32
+impl EnemyMemory {
+    // reads every exchange logged since the last call
+    pub fn learn(&mut self, battle_log: &BattleLog) {
+        if battle_log.battles != self.battle {
+            self.battle = battle_log.battles;
+            self.seen = 0;
+        }
+        let entries = battle_log.current();
+        for entry in entries[self.seen.min(entries.len())..].iter() {
+            match (entry.actor, entry.action) {
+                (Actor::Player, BattleAction::Attack) => self.observe_player(-1.),
+                (Actor::Player, BattleAction::Magic) => self.observe_player(1.),
+                (Actor::Player, BattleAction::Spell(element)) => {
+                    self.observe_player(1.);
+                    self.elements[element as usize] += 1;
+                }
+                (Actor::Enemy, BattleAction::Attack) => self.observe_result(EnemyAction::Attack, entry.amount, entry.outcome),
+                (Actor::Enemy, BattleAction::Magic) => self.observe_result(EnemyAction::Magic, entry.amount, entry.outcome),
+                (Actor::Enemy, BattleAction::Heal) => self.observe_result(EnemyAction::Heal, entry.amount, entry.outcome),
+                _ => {}
+            }
+        }
+        self.seen = entries.len();
+    }
+
+    // the element the player has cast most this battle
+    pub fn preferred_element(&self) -> Option<Element> {
+        let (i, count) = self.elements.iter().enumerate().max_by_key(|(i, count)| (**count, std::cmp::Reverse(*i)))?;
+        if *count == 0 {
+            None
+        } else {
+            Some(Element::ALL[i])
+        }
+    }
+
+    fn observe_player(&mut self, style: f32) {
+        self.player_style += STYLE_RATE * (style - self.player_style);
+    }
+
+    // a miss (the magic contest lost) counts as dealing nothing
+    fn observe_result(&mut self, action: EnemyAction, amount: u32, outcome: Outcome) {
+        let reward = if outcome == Outcome::Miss || outcome == Outcome::Dodged { 0. } else { amount as f32 };
+        let value = &mut self.values[action as usize];
+        *value += LEARNING_RATE * (reward - *value);
+    }
+}
+
+
+pub fn rand_attack(rng: &mut impl Rng)
+-> EnemyAction
 {
-    let attack = rand::thread_rng().gen_range(0..100)%3;
-    return attack;
-        //possibly add if statement for healing
+    EnemyAction::ALL[rng.gen_range(0..EnemyAction::ALL.len())]
 }
 
-fn ai_attack(
-    player_stat_query: &mut Query<&mut PlayerStats, With<Player>>,
-    enemy_stat_query: &mut Query<&mut EnemyStats, With<Enemy>>,
+// the utility scores plus what the enemy has learned: damage each action has been dealing,
+// and answering the player's style (magic users have low def, attackers low mdef)
+pub fn adaptive_attack(
+    mut scores: [f32; 5],
+    memory: &EnemyMemory,
+    etype: u32,
 )
--> u32
+-> EnemyAction
 {
+    for (score, value) in scores.iter_mut().zip(memory.values.iter()) {
+        *score += VALUE_WEIGHT * value;
+    }
+    scores[0] += STYLE_WEIGHT * memory.player_style;
+    scores[1] -= STYLE_WEIGHT * memory.player_style;
+    // an enemy weak to the player's favourite spell heals sooner, one that resists it holds off
+    if let Some(element) = memory.preferred_element() {
+        if scores[2].is_finite() {
+            scores[2] += ELEMENT_WEIGHT * (affinity(etype, element) - 1.);
+        }
+    }
 
-    let enemy_stats = match enemy_stat_query.get_single_mut() {
-        Ok(stats) => stats,
-        Err(_) => return 0,
-    };
-    let player_stats = match player_stat_query.get_single_mut() {
-        Ok(stats) => stats,
-        Err(_) => return 0,
-    };
-
-    let mut physAttackOp = 0;
-    let mut magAttackOp = 0;
-    let mut healOp = 0;
-
-    let mut temp: i32 = (enemy_stats.physatk as i32) - (enemy_stats.mgkatk as i32);
-    if (temp > 0) {
-        physAttackOp += (temp/2);
-    } else if (temp<0) {
-        magAttackOp += (temp*-1)/2;
+    best_action(scores)
+}
+
+// ties go to attacking, then magic, like the original heuristic, then heal, guard and wait
+pub fn best_action(scores: [f32; 5]) -> EnemyAction {
+    let mut best = 0;
+    for (i, score) in scores.iter().enumerate() {
+        if *score > scores[best] {
+            best = i;
+        }
     }
+    EnemyAction::ALL[best]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::PlayerStats;
+    use crate::enemy::EnemyStats;
+    use crate::brain::BattleSnapshot;
+    use crate::utility::UtilityConfig;
 
-    temp = (player_stats.mdef as i32) - (player_stats.def as i32);
-    if (temp > 0) {
-        physAttackOp += (temp/2);
-    } else if (temp < 0) {
-        magAttackOp += (temp*-1)/2;
+    fn decide(memory: &mut EnemyMemory, battle_log: &BattleLog, player: &PlayerStats, enemy: &EnemyStats) -> EnemyAction {
+        memory.learn(battle_log);
+        let snapshot = BattleSnapshot { player_stats: player, enemy_stats: enemy, battle_log };
+        adaptive_attack(UtilityConfig::default().score(&snapshot), memory, enemy.etype)
     }
 
-    let mut temp_hp = (enemy_stats.hp as f32)/(enemy_stats.max_hp as f32);
-    if (temp_hp >= 0.9){
-        healOp = -999;
-    } else if (temp_hp> 0.6) {
-        healOp = healOp+1;
-    } else if (temp_hp> 0.3) {
-        healOp = healOp+2;
-    } else {
-        healOp = healOp+6;
+    #[test]
+    fn magic_misses_push_the_enemy_to_physical() {
+        let player = PlayerStats::new();
+        let mut enemy = EnemyStats::new(2);
+        enemy.mgkatk = 6;
+        let mut memory = EnemyMemory::default();
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+
+        // the stats alone favour magic
+        assert_eq!(decide(&mut memory, &battle_log, &player, &enemy), EnemyAction::Magic);
+
+        for _ in 0..3 {
+            battle_log.push(Actor::Player, BattleAction::Wait, 0, Outcome::Passed);
+            battle_log.push(Actor::Enemy, BattleAction::Magic, 0, Outcome::Miss);
+        }
+        assert_eq!(decide(&mut memory, &battle_log, &player, &enemy), EnemyAction::Attack);
+        assert!(memory.values[1] < memory.values[0]);
     }
 
-    temp_hp = (player_stats.hp as f32)/(player_stats.max_hp as f32);
-    if (temp_hp < 0.2){
-        physAttackOp += 10;
-        magAttackOp += 10;
+    #[test]
+    fn enemy_switches_when_the_player_switches() {
+        let player = PlayerStats::new();
+        let enemy = EnemyStats::new(2);
+        let mut memory = EnemyMemory::default();
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+
+        // against an attacker the enemy answers with magic
+        for _ in 0..3 {
+            battle_log.push(Actor::Player, BattleAction::Attack, 8, Outcome::Hit);
+        }
+        assert_eq!(decide(&mut memory, &battle_log, &player, &enemy), EnemyAction::Magic);
+
+        // once the player turns to magic it goes after their lower def
+        for _ in 0..2 {
+            battle_log.push(Actor::Player, BattleAction::Magic, 8, Outcome::Hit);
+        }
+        assert_eq!(decide(&mut memory, &battle_log, &player, &enemy), EnemyAction::Attack);
     }
 
-    if (physAttackOp >= magAttackOp && physAttackOp >= healOp) {
-        return 0;
-    } else if (magAttackOp >= healOp) {
-        return 1;
-    } else {
-        return 2;
+    #[test]
+    fn entries_are_learned_once_and_memory_resets_per_battle() {
+        let mut memory = EnemyMemory::default();
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+        battle_log.push(Actor::Enemy, BattleAction::Attack, 9, Outcome::Hit);
+
+        memory.learn(&battle_log);
+        let value = memory.values[0];
+        memory.learn(&battle_log);
+        assert_eq!(memory.values[0], value);
+
+        // a new battle starts reading from its own first entry
+        battle_log.start_battle();
+        battle_log.push(Actor::Player, BattleAction::Magic, 5, Outcome::Hit);
+        memory.learn(&battle_log);
+        assert_eq!(memory.values[0], value);
+        assert!(memory.player_style > 0.);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn enemy_heals_sooner_when_the_player_favours_its_weakness() {
+        let player = PlayerStats::new();
+        let mut enemy = EnemyStats::new(2);
+        enemy.hp = enemy.max_hp * 85 / 100;
+
+        let mut plain = EnemyMemory::default();
+        let mut plain_log = BattleLog::default();
+        plain_log.start_battle();
+        let mut lightning = EnemyMemory::default();
+        let mut lightning_log = BattleLog::default();
+        lightning_log.start_battle();
+        for _ in 0..3 {
+            plain_log.push(Actor::Player, BattleAction::Magic, 5, Outcome::Hit);
+            lightning_log.push(Actor::Player, BattleAction::Spell(Element::Lightning), 10, Outcome::SuperEffective);
+        }
+
+        assert_eq!(decide(&mut plain, &plain_log, &player, &enemy), EnemyAction::Attack);
+        assert_eq!(decide(&mut lightning, &lightning_log, &player, &enemy), EnemyAction::Heal);
+        assert_eq!(lightning.preferred_element(), Some(Element::Lightning));
+    }
+}
@@ -0,0 +1,471 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use crate::GameState;
+use crate::player::{Player, Velocity};
+use crate::enemy::{Enemy, EnemyStats, find_closest_enemy};
+use crate::boss::is_boss;
+use crate::skill_tree::SkillTreeUINode;
+use crate::battle_log::Outcome;
+use crate::settings::Settings;
+use crate::events::{CombatEvent, EndGameEvent};
+
+const CROSSFADE_TIME: f32 = 1.5;    // seconds for one track to fade out and the next to fade in
+const FOOTSTEP_DISTANCE: f32 = 48.; // pixels walked between footsteps
+const ASSET_DIR: &str = "assets";
+
+// a looping music track, one per mood of the game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MusicTrack {
+    Exploration,
+    Battle,
+    Boss,
+    Defeat,
+    Credits,
+}
+
+impl MusicTrack {
+    pub fn path(&self) -> &'static str {
+        match self {
+            MusicTrack::Exploration => "audio/music/exploration.ogg",
+            MusicTrack::Battle => "audio/music/battle.ogg",
+            MusicTrack::Boss => "audio/music/boss.ogg",
+            MusicTrack::Defeat => "audio/music/defeat.ogg",
+            MusicTrack::Credits => "audio/music/credits.ogg",
+        }
+    }
+}
+
+// a one-shot sound effect
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sfx {
+    Footstep,
+    Hit,
+    Miss,
+    Heal,
+    SkillUnlock,
+    Door,
+}
+
+impl Sfx {
+    pub fn path(&self) -> &'static str {
+        match self {
+            Sfx::Footstep => "audio/sfx/footstep.ogg",
+            Sfx::Hit => "audio/sfx/hit.ogg",
+            Sfx::Miss => "audio/sfx/miss.ogg",
+            Sfx::Heal => "audio/sfx/heal.ogg",
+            Sfx::SkillUnlock => "audio/sfx/skill_unlock.ogg",
+            Sfx::Door => "audio/sfx/door.ogg",
+        }
+    }
+}
+
+// what the cue logic asks of whichever backend is plugged in; gains already include the volume buses
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AudioCommand {
+    PlayMusic(MusicTrack),
+    SetMusicGain(MusicTrack, f32),
+    StopMusic(MusicTrack),
+    PlaySfx(Sfx, f32),
+}
+
+// commands waiting for the backend, drained every frame
+#[derive(Resource, Default)]
+pub struct AudioQueue(pub Vec<AudioCommand>);
+
+// the music track for each state; None keeps whatever is playing, e.g. behind the menus
+pub fn music_for(state: &GameState, boss: bool) -> Option<MusicTrack> {
+    match state {
+        GameState::InGame | GameState::Welcome => Some(MusicTrack::Exploration),
+        GameState::BattleMode if boss => Some(MusicTrack::Boss),
+        GameState::BattleMode => Some(MusicTrack::Battle),
+        GameState::DefeatScreen => Some(MusicTrack::Defeat),
+        GameState::EndCredits => Some(MusicTrack::Credits),
+        GameState::SkillTreeMenu | GameState::MapView | GameState::Journal | GameState::Controls => None,
+    }
+}
+
+// the sound a battle log entry makes, if any
+pub fn combat_sfx(outcome: Outcome) -> Option<Sfx> {
+    match outcome {
+        Outcome::Hit | Outcome::Crit | Outcome::Graze | Outcome::SuperEffective | Outcome::Resisted | Outcome::Blocked => Some(Sfx::Hit),
+        Outcome::Miss | Outcome::Dodged => Some(Sfx::Miss),
+        Outcome::Healed => Some(Sfx::Heal),
+        Outcome::Started | Outcome::Fled | Outcome::Passed | Outcome::Defeated => None,
+    }
+}
+
+// the music bus: the track playing, the ones still fading out, and the gains last sent for each
+#[derive(Resource, Default)]
+pub struct MusicMixer {
+    current: Option<MusicTrack>,
+    fades: Vec<(MusicTrack, f32)>,  // every audible track and its fade level, 0..1
+    sent: Vec<(MusicTrack, f32)>,
+}
+
+impl MusicMixer {
+    pub fn current(&self) -> Option<MusicTrack> {
+        self.current
+    }
+
+    // starts `track` at silence and lets the old one fade out; nothing happens if it is already playing
+    pub fn switch_to(&mut self, track: MusicTrack) -> Vec<AudioCommand> {
+        if self.current == Some(track) {
+            return vec![];
+        }
+        self.current = Some(track);
+        // coming back to a track that was still fading out picks it up where it was
+        if self.fades.iter().any(|(fading, _)| *fading == track) {
+            return vec![];
+        }
+        self.fades.push((track, 0.));
+        vec![AudioCommand::PlayMusic(track)]
+    }
+
+    // moves every fade along by `dt` seconds; `bus` is master times music volume
+    pub fn tick(&mut self, dt: f32, bus: f32) -> Vec<AudioCommand> {
+        let step = dt / CROSSFADE_TIME;
+        let mut commands = vec![];
+        for (track, level) in self.fades.iter_mut() {
+            *level = if Some(*track) == self.current { (*level + step).min(1.) } else { (*level - step).max(0.) };
+        }
+        for (track, level) in self.fades.iter() {
+            let gain = level * bus;
+            match self.sent.iter_mut().find(|(sent, _)| sent == track) {
+                Some((_, sent_gain)) if (*sent_gain - gain).abs() < 0.001 => {}
+                Some((_, sent_gain)) => {
+                    *sent_gain = gain;
+                    commands.push(AudioCommand::SetMusicGain(*track, gain));
+                }
+                None => {
+                    self.sent.push((*track, gain));
+                    commands.push(AudioCommand::SetMusicGain(*track, gain));
+                }
+            }
+        }
+        // faded all the way out
+        let current = self.current;
+        for (track, _) in self.fades.iter().filter(|(track, level)| Some(*track) != current && *level <= 0.) {
+            commands.push(AudioCommand::StopMusic(*track));
+        }
+        self.fades.retain(|(track, level)| Some(*track) == current || *level > 0.);
+        self.sent.retain(|(track, _)| self.fades.iter().any(|(fading, _)| fading == track));
+        commands
+    }
+}
+
+// turns gameplay into AudioCommands; pair with a backend plugin to hear them
+pub struct SoundCuesPlugin;
+
+impl Plugin for SoundCuesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioQueue>();
+        app.init_resource::<MusicMixer>();
+        // chained so the queue fills up in the same order every frame
+        app.add_systems(Update, (pick_music, fade_music, combat_cues, footstep_cues, skill_unlock_cues, door_cues).chain().in_set(AudioCues));
+    }
+}
+
+// every cue system, so backends can drain the queue after them
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AudioCues;
+
+// cues played through bevy_audio
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(SoundCuesPlugin);
+        app.add_systems(Update, (play_commands, sync_music_volume).chain().after(AudioCues));
+    }
+}
+
+// swallows the cues, keeping them for whoever wants to look; for tests and machines without an audio device
+pub struct NullAudioPlugin;
+
+#[derive(Resource, Default)]
+pub struct PlayedCues(pub Vec<AudioCommand>);
+
+impl Plugin for NullAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(SoundCuesPlugin);
+        app.init_resource::<PlayedCues>();
+        app.add_systems(Update, record_commands.after(AudioCues));
+    }
+}
+
+fn pick_music(
+    state: Res<State<GameState>>,
+    mut mixer: ResMut<MusicMixer>,
+    mut queue: ResMut<AudioQueue>,
+    enemy_stat_query: Query<&EnemyStats, With<Enemy>>,
+    commands: Commands,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let boss = *state.get() == GameState::BattleMode
+        && !player_query.is_empty()
+        && find_closest_enemy(&commands, &enemy_query, &player_query)
+            .and_then(|enemy| enemy_stat_query.get(enemy).ok())
+            .is_some_and(is_boss);
+    if let Some(track) = music_for(state.get(), boss) {
+        let commands = mixer.switch_to(track);
+        queue.0.extend(commands);
+    }
+}
+
+// fades run on real time so the music keeps going while the game is paused
+fn fade_music(
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    mut mixer: ResMut<MusicMixer>,
+    mut queue: ResMut<AudioQueue>,
+) {
+    let commands = mixer.tick(time.delta_seconds(), settings.master_volume * settings.music_volume);
+    queue.0.extend(commands);
+}
+
+fn sfx_gain(settings: &Settings) -> f32 {
+    settings.master_volume * settings.sfx_volume
+}
+
+fn combat_cues(
+    mut combat_events: EventReader<CombatEvent>,
+    settings: Res<Settings>,
+    mut queue: ResMut<AudioQueue>,
+) {
+    for CombatEvent(entry) in combat_events.read() {
+        if let Some(sfx) = combat_sfx(entry.outcome) {
+            queue.0.push(AudioCommand::PlaySfx(sfx, sfx_gain(&settings)));
+        }
+    }
+}
+
+fn footstep_cues(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    settings: Res<Settings>,
+    player: Query<&Velocity, With<Player>>,
+    mut walked: Local<f32>,
+    mut queue: ResMut<AudioQueue>,
+) {
+    let Ok(velocity) = player.get_single() else {
+        return;
+    };
+    if *state.get() != GameState::InGame {
+        return;
+    }
+    *walked += velocity.velocity.length() * time.delta_seconds();
+    if *walked >= FOOTSTEP_DISTANCE {
+        *walked = 0.;
+        queue.0.push(AudioCommand::PlaySfx(Sfx::Footstep, sfx_gain(&settings)));
+    }
+}
+
+// only nodes unlocked on the skill tree screen chime, not ones restored by a load
+fn skill_unlock_cues(
+    state: Res<State<GameState>>,
+    settings: Res<Settings>,
+    nodes: Query<&SkillTreeUINode>,
+    mut unlocked: Local<HashSet<u32>>,
+    mut queue: ResMut<AudioQueue>,
+) {
+    let now: HashSet<u32> = nodes.iter().filter(|node| node.unlocked).map(|node| node.index).collect();
+    if *state.get() == GameState::SkillTreeMenu && now.difference(&unlocked).next().is_some() {
+        queue.0.push(AudioCommand::PlaySfx(Sfx::SkillUnlock, sfx_gain(&settings)));
+    }
+    *unlocked = now;
+}
+
+fn door_cues(
+    mut end_events: EventReader<EndGameEvent>,
+    settings: Res<Settings>,
+    mut queue: ResMut<AudioQueue>,
+) {
+    if end_events.read().count() > 0 {
+        queue.0.push(AudioCommand::PlaySfx(Sfx::Door, sfx_gain(&settings)));
+    }
+}
+
+fn record_commands(mut queue: ResMut<AudioQueue>, mut played: ResMut<PlayedCues>) {
+    played.0.append(&mut queue.0);
+}
+
+// a playing music track and the gain it should be at; its AudioSink only shows up once the file has loaded
+#[derive(Component)]
+struct MusicPlayer {
+    track: MusicTrack,
+    gain: f32,
+}
+
+// whether the file for a cue is there to load; looked up once per path, and a missing one is warned about once
+fn file_exists(path: &'static str, checked: &mut HashMap<&'static str, bool>) -> bool {
+    *checked.entry(path).or_insert_with(|| {
+        let exists = Path::new(ASSET_DIR).join(path).exists();
+        if !exists {
+            warn!("Missing sound {}/{}, skipping it", ASSET_DIR, path);
+        }
+        exists
+    })
+}
+
+// cues whose file is missing are skipped, so the game stays quiet instead of logging a load error every time
+fn play_commands(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut queue: ResMut<AudioQueue>,
+    mut players: Query<(Entity, &mut MusicPlayer)>,
+    mut checked: Local<HashMap<&'static str, bool>>,
+) {
+    for command in queue.0.drain(..) {
+        match command {
+            AudioCommand::PlayMusic(track) if !file_exists(track.path(), &mut checked) => {}
+            AudioCommand::PlaySfx(sfx, _) if !file_exists(sfx.path(), &mut checked) => {}
+            AudioCommand::PlayMusic(track) => {
+                commands.spawn((
+                    MusicPlayer { track, gain: 0. },
+                    AudioBundle {
+                        source: asset_server.load(track.path()),
+                        settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.)),
+                    },
+                ));
+            }
+            AudioCommand::SetMusicGain(track, gain) => {
+                for (_, mut player) in players.iter_mut().filter(|(_, player)| player.track == track) {
+                    player.gain = gain;
+                }
+            }
+            AudioCommand::StopMusic(track) => {
+                for (entity, _) in players.iter().filter(|(_, player)| player.track == track) {
+                    commands.entity(entity).despawn();
+                }
+            }
+            AudioCommand::PlaySfx(sfx, gain) => {
+                commands.spawn(AudioBundle {
+                    source: asset_server.load(sfx.path()),
+                    settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(gain)),
+                });
+            }
+        }
+    }
+}
+
+// a sink shows up a frame after the player is spawned, so a fresh one needs its gain too
+type MusicVolumeChanged = Or<(Changed<MusicPlayer>, Added<AudioSink>)>;
+
+fn sync_music_volume(players: Query<(&MusicPlayer, &AudioSink), MusicVolumeChanged>) {
+    for (player, sink) in players.iter() {
+        sink.set_volume(player.gain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    use crate::battle_log::{Actor, BattleAction, LogEntry};
+    use super::*;
+
+    #[test]
+    fn missing_files_are_looked_up_once() {
+        let mut checked = HashMap::new();
+        assert!(file_exists("defeat.png", &mut checked));
+        assert!(!file_exists("audio/sfx/not_a_sound.ogg", &mut checked));
+        assert_eq!(checked.get("audio/sfx/not_a_sound.ogg"), Some(&false));
+        assert_eq!(checked.len(), 2);
+        assert!(!file_exists("audio/sfx/not_a_sound.ogg", &mut checked));
+        assert_eq!(checked.len(), 2);
+    }
+
+    #[test]
+    fn mixer_crossfades_between_tracks() {
+        let mut mixer = MusicMixer::default();
+        assert_eq!(mixer.switch_to(MusicTrack::Exploration), vec![AudioCommand::PlayMusic(MusicTrack::Exploration)]);
+        mixer.tick(CROSSFADE_TIME, 1.);
+
+        assert_eq!(mixer.switch_to(MusicTrack::Battle), vec![AudioCommand::PlayMusic(MusicTrack::Battle)]);
+        let halfway = mixer.tick(CROSSFADE_TIME / 2., 1.);
+        assert!(halfway.contains(&AudioCommand::SetMusicGain(MusicTrack::Exploration, 0.5)));
+        assert!(halfway.contains(&AudioCommand::SetMusicGain(MusicTrack::Battle, 0.5)));
+
+        let done = mixer.tick(CROSSFADE_TIME / 2., 1.);
+        assert!(done.contains(&AudioCommand::StopMusic(MusicTrack::Exploration)));
+        assert!(done.contains(&AudioCommand::SetMusicGain(MusicTrack::Battle, 1.)));
+        assert_eq!(mixer.current(), Some(MusicTrack::Battle));
+    }
+
+    #[test]
+    fn same_track_is_not_restarted() {
+        let mut mixer = MusicMixer::default();
+        mixer.switch_to(MusicTrack::Exploration);
+        mixer.tick(CROSSFADE_TIME, 1.);
+        assert!(mixer.switch_to(MusicTrack::Exploration).is_empty());
+        // nothing moved, so nothing is sent again
+        assert!(mixer.tick(0.1, 1.).is_empty());
+    }
+
+    #[test]
+    fn music_gain_follows_the_volume_buses() {
+        let mut mixer = MusicMixer::default();
+        mixer.switch_to(MusicTrack::Exploration);
+        mixer.tick(CROSSFADE_TIME, 1.);
+        assert_eq!(mixer.tick(0., 0.25), vec![AudioCommand::SetMusicGain(MusicTrack::Exploration, 0.25)]);
+    }
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+        app.init_state::<GameState>();
+        app.insert_resource(Settings { master_volume: 0.5, sfx_volume: 0.5, ..default() });
+        app.add_event::<CombatEvent>();
+        app.add_event::<EndGameEvent>();
+        app.add_plugins(NullAudioPlugin);
+        app.update();
+        app
+    }
+
+    fn played(app: &mut App) -> Vec<AudioCommand> {
+        std::mem::take(&mut app.world_mut().resource_mut::<PlayedCues>().0)
+    }
+
+    #[test]
+    fn state_changes_switch_the_music() {
+        let mut app = test_app();
+        assert!(played(&mut app).contains(&AudioCommand::PlayMusic(MusicTrack::Exploration)));
+
+        // menus keep the track that was playing
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::SkillTreeMenu);
+        app.update();
+        assert!(!played(&mut app).iter().any(|command| matches!(command, AudioCommand::PlayMusic(_))));
+
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::BattleMode);
+        app.update();
+        assert!(played(&mut app).contains(&AudioCommand::PlayMusic(MusicTrack::Battle)));
+    }
+
+    #[test]
+    fn combat_events_play_sfx_on_the_sfx_bus() {
+        let mut app = test_app();
+        played(&mut app);
+        for outcome in [Outcome::Crit, Outcome::Dodged, Outcome::Healed, Outcome::Passed] {
+            let entry = LogEntry { battle: 1, actor: Actor::Player, action: BattleAction::Attack, amount: 3, outcome };
+            app.world_mut().send_event(CombatEvent(entry));
+        }
+        app.world_mut().send_event(EndGameEvent);
+        app.update();
+
+        let sfx: Vec<AudioCommand> = played(&mut app).into_iter().filter(|command| matches!(command, AudioCommand::PlaySfx(..))).collect();
+        assert_eq!(sfx, vec![
+            AudioCommand::PlaySfx(Sfx::Hit, 0.25),
+            AudioCommand::PlaySfx(Sfx::Miss, 0.25),
+            AudioCommand::PlaySfx(Sfx::Heal, 0.25),
+            AudioCommand::PlaySfx(Sfx::Door, 0.25),
+        ]);
+    }
+}
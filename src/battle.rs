@@ -1,21 +1,79 @@
-use std::borrow::BorrowMut;
-
 use bevy::prelude::*;
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 use crate::GameState;
 use crate::BattleState;
 
 use crate::player::PlayerStats;
 use crate::enemy::EnemyStats;
-use crate::turn_order::TurnOrder;
-use crate::text_box::BattleDialogue;
+use crate::turn_order::{TurnCost, spend_turn, player_acts_next, start_turn_order};
+use crate::battle_log::{BattleLog, Actor, BattleAction, Outcome};
+use crate::replay::{BattleRecorder, BattleReplay};
 
 use crate::player::Player;
 use crate::enemy::Enemy;
 use crate::enemy::find_closest_enemy;
-use crate::enemy::despawn_closest_enemy;
 
-use crate::attack::choose_attack;
+use crate::boss::{is_boss, advance_phase, actions_per_turn, moveset, add_attacks};
+use crate::element::{Element, SelectedSpell, affinity};
+use crate::brain::{Brain, BattleSnapshot, EnemyAction, EnemyBrain};
+use crate::battle_flow::{BattleFlow, RESOLVE_TIME};
+use crate::controls::{Action, ActionState};
+use crate::events::{CameraShakeEvent, PlayerActionEvent};
+
+// what the player picked from the battle menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerAction {
+    Attack,
+    Magic,
+    Spell(Element),     // magic of one element, only once it is learned
+    Heal,
+    Run,
+    Guard,      // halve the next hit and build focus for a later attack
+    Wait,       // give up the turn, but only half a turn's worth of time
+}
+
+// what happens after the player's action was resolved
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TurnResult {
+    Continue,   // the battle goes on, the turn order decides who acts next
+    Victory,
+    Fled,
+}
+
+// how a physical blow landed, decided by the attacker's and defender's speed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strike {
+    Hit,
+    Crit,
+    Graze,      // barely connected, half damage
+    Dodge,
+}
+
+impl Strike {
+    pub fn outcome(self) -> Outcome {
+        match self {
+            Strike::Hit => Outcome::Hit,
+            Strike::Crit => Outcome::Crit,
+            Strike::Graze => Outcome::Graze,
+            Strike::Dodge => Outcome::Dodged,
+        }
+    }
+}
+
+const BASE_ACCURACY: i32 = 90;      // chance in 100 to connect when both sides are equally quick
+const ACCURACY_PER_SPEED: i32 = 3;  // per point of speed the attacker has over the defender
+const GRAZE_BAND: i32 = 8;          // rolls this close to a dodge only graze
+const BASE_CRIT: u32 = 5;
+const CRIT_PER_SPEED: u32 = 2;
+const MAX_CRIT: u32 = 50;
+const GUARD_PERCENT: u32 = 50;      // share of a hit that gets through a raised guard
+pub const MAX_FOCUS: u32 = 3;
+const FOCUS_BONUS: f32 = 0.25;      // extra damage per point of focus spent
+
+// every combat roll goes through this so a battle can be replayed from its seed
+#[derive(Resource)]
+pub struct BattleRng(pub StdRng);
 
 pub struct BattlePlugin;
 
@@ -23,151 +81,187 @@ pub struct BattlePlugin;
 
 impl Plugin for BattlePlugin{
     fn build(&self, app: &mut App){
-        app.add_systems(Update, battle_input.run_if(in_state(GameState::BattleMode)));
-        app.add_systems(Update, enemy_attack.run_if(in_state(BattleState::EnemyTurn)));
+        app.insert_resource(BattleRng(StdRng::seed_from_u64(random())));
+        app.add_systems(OnEnter(GameState::BattleMode), reset_turn_order);
+        app.add_systems(Update, read_battle_keys.run_if(in_state(BattleState::ChooseAction)));
+        app.add_systems(Update, battle_input.after(read_battle_keys).run_if(in_state(BattleState::ChooseAction)));
+        app.add_systems(Update, enemy_attack.run_if(in_state(BattleState::ResolveEnemy)));
     }
 }
 
+// turns the battle actions (number keys by default) into a move, unless a recorded battle is driving the menu
+fn read_battle_keys(
+    actions: Res<ActionState>,
+    replay: Res<BattleReplay>,
+    selected_spell: Res<SelectedSpell>,
+    mut action_events: EventWriter<PlayerActionEvent>,
+) {
+    if replay.active() {
+        return;
+    }
+    let action = if actions.just_pressed(Action::Attack) {
+        PlayerAction::Attack
+    } else if actions.just_pressed(Action::Magic) {
+        match selected_spell.0 {
+            Some(element) => PlayerAction::Spell(element),
+            None => PlayerAction::Magic,
+        }
+    } else if actions.just_pressed(Action::Heal) {
+        PlayerAction::Heal
+    } else if actions.just_pressed(Action::Run) {
+        PlayerAction::Run
+    } else if actions.just_pressed(Action::Wait) {
+        PlayerAction::Wait
+    } else if actions.just_pressed(Action::Guard) {
+        PlayerAction::Guard
+    } else {
+        /* else do nothing until player selects a valid battle option */
+        return;
+    };
+    action_events.send(PlayerActionEvent(action));
+}
+
 pub fn battle_input(
     /* for input */
-    state: Res<State<GameState>>,
-    mut next_state: ResMut<NextState<GameState>>,
     mut next_turn_state: ResMut<NextState<BattleState>>,
-    input: Res<ButtonInput<KeyCode>>,
+    mut flow: ResMut<BattleFlow>,
+    mut action_events: EventReader<PlayerActionEvent>,
 
     mut player_stat_query: Query<&mut PlayerStats, With<Player>>,
     mut enemy_stat_query: Query<&mut EnemyStats, With<Enemy>>,
-    mut battle_dialogue_query: Query<&mut BattleDialogue>,
+    mut battle_log: ResMut<BattleLog>,
+    mut battle_rng: ResMut<BattleRng>,
+    mut recorder: ResMut<BattleRecorder>,
+    replay: Res<BattleReplay>,
 
     commands: Commands,
     enemy_query: Query<(Entity, &Transform), With<Enemy>>,
     player_query: Query<&Transform, With<Player>>,
-    //use TurnOrder to ensure it is player's turn
+    mut shake_event_writer: EventWriter<CameraShakeEvent>,
 ) {
-    // The turn system will be integrated here: Before checking for player input, 
-    // we will check if it’s the player's turn to act.
-    // TurnOrder will hold the order of turns (whether it's the player’s or enemy's turn).
-
-    // Check if it's the player's turn using TurnOrder
-    // If it's the player's turn, they can perform actions (attacks, healing, etc.)
-    // Example of TurnOrder check (pseudo-code):
-    // if turn_order.current_turn == player_entity {
-    //     // Allow player to act
-    // }
-        if let Ok(mut player_stats) = player_stat_query.get_single_mut() {
-            if (player_stats.hp<=0){
-                next_state.set(GameState::DefeatScreen);
-                insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(""));
-                insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(""));
-                insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(">Battle Start"));
-            } 
+    // this only runs in BattleState::ChooseAction, which the turn order hands over to
+    // once the player has the lowest next_action_tick
+        // only one action per turn, anything else pressed this frame is dropped
+        let Some(PlayerActionEvent(action)) = action_events.read().last().copied() else {
+            return;
+        };
+        let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query) else {
+            return;
+        };
+        let Ok(mut enemy_stats) = enemy_stat_query.get_mut(closest_enemy) else {
+            return;
+        };
+        let Ok(mut player_stats) = player_stat_query.get_single_mut() else {
+            return;
+        };
+
+        // a greyed out option can't be picked with its key either; replays play back whatever was recorded
+        if !replay.active() && !action_available(action, &player_stats, &enemy_stats) {
+            return;
+        }
+
+        let enemy_hp = enemy_stats.hp;
+        let result = player_turn(action, &mut player_stats, &mut enemy_stats, &mut battle_rng.0, &mut battle_log);
+        recorder.record(action, &player_stats, &enemy_stats);
+        if enemy_stats.hp < enemy_hp {
+            shake_event_writer.send(CameraShakeEvent { trauma: 0.3 });
         }
 
-        if input.just_pressed(KeyCode::Digit1) { //later on we can just if the different attacks there are and query player stats.
-            if let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query){
-                if let Ok(mut enemy_stats) = enemy_stat_query.get_mut(closest_enemy) {
-                    if let Ok(mut player_stats) = player_stat_query.get_single_mut() {
-       
-                        // Simple attack that deals 10 damage to the enemy
-                        let attack_dmg = physical_attack(5, player_stats.atk, enemy_stats.physdef);//why is attack using eenemy magic attack?
-                        enemy_stats.hp = enemy_stats.hp.saturating_sub(attack_dmg);
-                        
-                        insert_battledialogue(battle_dialogue_query.borrow_mut(), format!("Enemy was attacked for {attack_dmg} damage!"));
-                        info!("Enemy was attacked with sword for {} damage! Enemy HP is now: {}", attack_dmg, enemy_stats.hp);
-    
-                        if enemy_stats.hp <= 0 {
-                            info!("Enemy defeated!");
-                            player_stats.skill_points += 1;
-                            //player_stats.ability_points += 1;
-                            despawn_closest_enemy(commands, enemy_query, player_query);  // Despawn the enemy if defeated
-                            next_state.set(GameState::InGame);
-                            insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(""));
-                            insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(""));
-                            insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(">Battle Start"));
-    
-                        } else {
-                            next_turn_state.set(BattleState::EnemyTurn);
-                            //enemy_attack(player_stat_query, enemy_stat_query);
-                        }
-                    }
+        let next = match result {
+            // after a wait the player can still be next in line
+            TurnResult::Continue if player_acts_next(&player_stats, &enemy_stats) => BattleState::ChooseAction,
+            TurnResult::Continue => BattleState::ResolveEnemy,
+            TurnResult::Victory => BattleState::Victory,
+            TurnResult::Fled => BattleState::Fled,
+        };
+        flow.then(RESOLVE_TIME, next);
+        next_turn_state.set(BattleState::ResolvePlayer);
+    }
+
+// resolves one player action against the enemy; shared by the battle scene, replays and the balance tools
+pub fn player_turn(
+    action: PlayerAction,
+    player_stats: &mut PlayerStats,
+    enemy_stats: &mut EnemyStats,
+    rng: &mut impl Rng,
+    battle_log: &mut BattleLog,
+) -> TurnResult {
+    // a guard only lasts until its owner's next turn
+    player_stats.guarding = false;
+    match action {
+        PlayerAction::Attack | PlayerAction::Magic | PlayerAction::Spell(_) => {
+            let (attack_dmg, battle_action, outcome) = match action {
+                PlayerAction::Attack => {
+                    let (damage, strike) = physical_strike(5, player_stats.atk, enemy_stats.physdef, player_stats.spd, enemy_stats.speed, rng);
+                    (damage, BattleAction::Attack, strike.outcome())
                 }
+                PlayerAction::Spell(element) => {
+                    let (damage, outcome) = elemental_attack(element, player_stats.matk, enemy_stats, rng);
+                    (damage, BattleAction::Spell(element), outcome)
+                }
+                _ => {
+                    let damage = magic_attack(5, player_stats.matk, enemy_stats.mgkdef, rng);
+                    (damage, BattleAction::Magic, if damage > 0 { Outcome::Hit } else { Outcome::Miss })
+                }
+            };
+            let attack_dmg = focused(attack_dmg, &mut player_stats.focus);
+            let (attack_dmg, outcome) = guarded(attack_dmg, outcome, &mut enemy_stats.guarding);
+            enemy_stats.hp = enemy_stats.hp.saturating_sub(attack_dmg);
+
+            battle_log.push(Actor::Player, battle_action, attack_dmg, outcome);
+            info!("Enemy HP is now: {}", enemy_stats.hp);
+
+            if enemy_stats.hp == 0 {
+                battle_log.push(Actor::Enemy, BattleAction::Defeat, 0, Outcome::Defeated);
+                return TurnResult::Victory;
             }
         }
-        else if input.just_pressed(KeyCode::Digit2) {
-            //info!("magic attacked! but it had no effect...");
-            if let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query) {
-                if let Ok(mut enemy_stats) = enemy_stat_query.get_mut(closest_enemy) {
-                    if let Ok(mut player_stats) = player_stat_query.get_single_mut() {
-    
-                        // Simple attack that deals 10 damage to the enemy
-                        let attack_dmg = magic_attack(5, player_stats.matk, enemy_stats.mgkdef);
-                        enemy_stats.hp = enemy_stats.hp.saturating_sub(attack_dmg);
-                        
-                        insert_battledialogue(battle_dialogue_query.borrow_mut(), format!("Enemy was attacked with magic for {attack_dmg} damage!"));
-                        info!("Enemy was attacked with magic for {} damage! Enemy HP is now: {}", attack_dmg, enemy_stats.hp);
-
-                        if enemy_stats.hp <= 0 {
-                            info!("Enemy defeated!");
-                            player_stats.skill_points += 1;
-                            //player_stats.ability_points += 1;
-                            despawn_closest_enemy(commands, enemy_query, player_query);  // Despawn the enemy if defeated
-                            next_state.set(GameState::InGame);
-                            insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(""));
-                            insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(""));
-                            insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(">Battle Start"));
-
-                        } else {
-                            next_turn_state.set(BattleState::EnemyTurn);
-                            //enemy_attack(player_stat_query, enemy_stat_query);
-                        }
-                    }
-                }
-            }   
+        PlayerAction::Heal => {
+            let current_hp = player_stats.hp;
+            let max_hp = player_stats.max_hp;
+            let heal_amt = heal(4, player_stats.magic); // get the heal amount (just a flat 5 hp for now)
+            player_stats.hp = current_hp + heal_amt.clamp(0, max_hp - current_hp);
+            battle_log.push(Actor::Player, BattleAction::Heal, heal_amt, Outcome::Healed);
+            info!("Player hp is now: {}", player_stats.hp);
         }
-        else if input.just_pressed(KeyCode::Digit3) {
-            //battle_heal(player_stat_query);
-            if let Ok(mut player_stats) = player_stat_query.get_single_mut() {
-                let current_hp = player_stats.hp;
-                let max_hp = player_stats.max_hp;
-                let heal_amt = heal(4, player_stats.magic); // get the heal amount (just a flat 5 hp for now)
-                player_stats.hp = current_hp + heal_amt.clamp(0, max_hp - current_hp);
-                insert_battledialogue(battle_dialogue_query.borrow_mut(), format!("Player healed for {heal_amt} hp!"));
-                info!("Player healed! Player hp is now: {}", player_stats.hp);
-            }
-            //info!("press 5 to end turn");
-            // later: change turn state here
-            next_turn_state.set(BattleState::EnemyTurn);
-            
-            //enemy_attack(player_stat_query, enemy_stat_query);
-
+        // the boss blocks the way out, so there is nowhere to run to
+        PlayerAction::Run if is_boss(enemy_stats) => {
+            battle_log.push(Actor::Player, BattleAction::Run, 0, Outcome::Miss);
         }
-        else if input.just_pressed(KeyCode::Digit4) {
-            info!("ran away");
-            /* change game state to over world */
-            match state.get() {
-                GameState::Welcome => next_state.set(GameState::InGame),
-                GameState::BattleMode => next_state.set(GameState::InGame),
-                GameState::InGame => next_state.set(GameState::InGame),
-                GameState::SkillTreeMenu => next_state.set(GameState::SkillTreeMenu), // no op?
-                GameState::EndCredits => next_state.set(GameState::EndCredits),
-                GameState::DefeatScreen => next_state.set(GameState::DefeatScreen),
-            }
-            despawn_closest_enemy(commands, enemy_query, player_query);
-            insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(""));
-            insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(""));
-            insert_battledialogue(battle_dialogue_query.borrow_mut(), format!(">Battle Start"));
-        /* else do nothing until player selects a valid battle option */
-        } else if input.just_pressed(KeyCode::Digit5){
-            next_turn_state.set(BattleState::EnemyTurn);
-            //enemy_attack(player_stat_query, enemy_stat_query);
+        PlayerAction::Run => {
+            battle_log.push(Actor::Player, BattleAction::Run, 0, Outcome::Fled);
+            return TurnResult::Fled;
+        }
+        PlayerAction::Guard => {
+            let focus = raise_guard(&mut player_stats.guarding, &mut player_stats.focus);
+            battle_log.push(Actor::Player, BattleAction::Guard, focus, Outcome::Passed);
         }
-    // After the player's input is processed, the next step would be to move the turn order forward
-    // turn_order.next_turn();
+        PlayerAction::Wait => {
+            battle_log.push(Actor::Player, BattleAction::Wait, 0, Outcome::Passed);
+        }
+    }
+    let cost = if action == PlayerAction::Wait { TurnCost::Half } else { TurnCost::Full };
+    spend_turn(&mut player_stats.next_action_tick, player_stats.spd, cost);
+    TurnResult::Continue
+}
 
+// both sides start a battle on tick 0 with their guard down
+fn reset_turn_order(
+    commands: Commands,
+    mut player_stat_query: Query<&mut PlayerStats, With<Player>>,
+    mut enemy_stat_query: Query<&mut EnemyStats, With<Enemy>>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query) else {
+        return;
+    };
+    if let (Ok(mut player_stats), Ok(mut enemy_stats)) = (player_stat_query.get_single_mut(), enemy_stat_query.get_mut(closest_enemy)) {
+        start_turn_order(&mut player_stats, &mut enemy_stats);
     }
-        
+}
 
-//moved function to enemy_attack to make it easier for insert_battledialogue to be called
+//moved function to enemy_attack to make it easier to write to the battle log
 fn battle_heal(
     mut player_stat_query: Query<&mut PlayerStats, With<Player>>,
 ) {
@@ -176,7 +270,6 @@ fn battle_heal(
         let max_hp = player_stats.max_hp;
         let heal_amt = heal(4, player_stats.magic); // get the heal amount (just a flat 5 hp for now)
         player_stats.hp = current_hp + heal_amt.clamp(0, max_hp - current_hp);
-        //insert_battledialogue(battle_dialogue_query, format!("Player healed for {heal_amt} hp"));
         //info!("Player healed! Player hp is now: {}", player_stats.hp);d
     }
 
@@ -184,54 +277,162 @@ fn battle_heal(
 
 pub fn enemy_attack(
     mut player_stat_query: Query<&mut PlayerStats, With<Player>>,
-    mut enemy_stat_query: Query<&mut EnemyStats, With<Enemy>>,
-    mut battle_dialogue_query: Query<&mut BattleDialogue>,
-    mut next_turn_state: ResMut<NextState<BattleState>>,
+    mut enemy_stat_query: Query<(&mut EnemyStats, &mut Brain), With<Enemy>>,
+    mut battle_log: ResMut<BattleLog>,
+    mut battle_rng: ResMut<BattleRng>,
+    mut recorder: ResMut<BattleRecorder>,
+    mut flow: ResMut<BattleFlow>,
+    mut shake_event_writer: EventWriter<CameraShakeEvent>,
 
     commands: Commands,
     enemy_query: Query<(Entity, &Transform), With<Enemy>>,
     player_query: Query<&Transform, With<Player>>,
 ) {
-    //check if it is enemy's turn with TurnOrder
-    // let rand: usize = random();
-    // let attack = rand %3; 
-    let attack = choose_attack(&mut player_stat_query, &mut enemy_stat_query);
-    //info!("attack value: {}", attack);
+    // the enemy moves once per ResolveEnemy, then waits for it to play out
+    if flow.enemy_acted {
+        return;
+    }
     let mut enemy_damage = 0;
+    let mut next = BattleState::ChooseAction;
     if let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query) {
         if let Ok(mut player_stats) = player_stat_query.get_single_mut() {
-            if let Ok(mut enemy_stats) = enemy_stat_query.get_mut(closest_enemy) {
-                if (attack == 0){
-                    enemy_damage = physical_attack(5, enemy_stats.physatk, player_stats.def);
-                    player_stats.hp = player_stats.hp.saturating_sub(enemy_damage);
-
-                    insert_battledialogue(battle_dialogue_query.borrow_mut(), format!("Enemy attacked you for {enemy_damage} damage!"));
-                    info!("Enemy hit you for {} damage! Player HP is now: {}",enemy_damage, player_stats.hp);
-                } else if (attack == 1){
-                    enemy_damage = magic_attack(5, enemy_stats.mgkatk, player_stats.mdef);
-                    player_stats.hp = player_stats.hp.saturating_sub(enemy_damage);
-
-                    insert_battledialogue(battle_dialogue_query.borrow_mut(), format!("Enemy attacked you with a psychic force for {enemy_damage} damage!"));
-                    info!("Enemy hit you with a psychic force for {} damage! Player HP is now: {}",enemy_damage, player_stats.hp);
-                } else if (attack == 2){
-                    //enemy_heal(enemy_stat_query);
-                    let current_hp = enemy_stats.hp;
-                    let max_hp = enemy_stats.max_hp;
-                    let heal_amt = heal(4, enemy_stats.mgkatk); // get the heal amount (just a flat 5 hp for now)
-                    enemy_stats.hp = current_hp + heal_amt.clamp(0, max_hp - current_hp);
-                        
-                    insert_battledialogue(battle_dialogue_query.borrow_mut(), format!("Enemy healed for {heal_amt} hp!"));
-                    info!("Enemy healed! Enemy hp is now: {}", enemy_stats.hp);
-                    player_stats.hp = player_stats.hp.saturating_sub(enemy_damage);
-                    
+            if let Ok((mut enemy_stats, mut brain)) = enemy_stat_query.get_mut(closest_enemy) {
+                enemy_damage = enemy_turn(&mut player_stats, &mut enemy_stats, brain.0.as_mut(), &mut battle_rng.0, &mut battle_log);
+                recorder.update_hp(&player_stats, &enemy_stats);
+                // defeat is settled as soon as the blow lands, and a faster or waiting enemy may go again
+                next = if player_stats.hp == 0 {
+                    BattleState::Defeat
+                } else if player_acts_next(&player_stats, &enemy_stats) {
+                    BattleState::ChooseAction
+                } else {
+                    BattleState::ResolveEnemy
+                };
+            }
+        }
+    }
+    // getting hit shakes harder than landing a hit
+    if enemy_damage > 0 {
+        shake_event_writer.send(CameraShakeEvent { trauma: 0.6 });
+    }
+    flow.then(RESOLVE_TIME, next);
+    flow.enemy_acted = true;
+}
+
+// the enemy picks and resolves its moves, returns the damage dealt to the player
+pub fn enemy_turn(
+    player_stats: &mut PlayerStats,
+    enemy_stats: &mut EnemyStats,
+    brain: &mut dyn EnemyBrain,
+    rng: &mut impl Rng,
+    battle_log: &mut BattleLog,
+) -> u32 {
+    // a boss that was knocked past a threshold changes phase before it acts
+    advance_phase(enemy_stats, battle_log);
+    enemy_stats.guarding = false;
+    let mut damage = 0;
+    let mut cost = TurnCost::Full;
+    for i in 0..actions_per_turn(enemy_stats) {
+        if player_stats.hp == 0 {
+            break;
+        }
+        let snapshot = BattleSnapshot { player_stats, enemy_stats, battle_log };
+        let mut attack = brain.choose_action(&snapshot, rng);
+        // only moves from the boss's current phase are allowed
+        let moves = moveset(enemy_stats);
+        if !moves.contains(&attack) {
+            attack = moves[0];
+        }
+        let (amount, outcome) = resolve_enemy_action(attack, player_stats, enemy_stats, rng);
+        match attack {
+            EnemyAction::Attack | EnemyAction::Magic => {
+                let battle_action = if attack == EnemyAction::Attack { BattleAction::Attack } else { BattleAction::Magic };
+                battle_log.push(Actor::Enemy, battle_action, amount, outcome);
+                info!("Player HP is now: {}", player_stats.hp);
+                damage += amount;
+            }
+            EnemyAction::Heal => {
+                battle_log.push(Actor::Enemy, BattleAction::Heal, amount, outcome);
+                info!("Enemy hp is now: {}", enemy_stats.hp);
+            }
+            EnemyAction::Guard => {
+                battle_log.push(Actor::Enemy, BattleAction::Guard, amount, outcome);
+            }
+            // waiting ends the turn, and only costs half of one if nothing else was done
+            EnemyAction::Wait => {
+                battle_log.push(Actor::Enemy, BattleAction::Wait, amount, outcome);
+                if i == 0 {
+                    cost = TurnCost::Half;
                 }
-            }       
+                break;
+            }
         }
     }
-    next_turn_state.set(BattleState::PlayerTurn);
+    spend_turn(&mut enemy_stats.next_action_tick, enemy_stats.speed, cost);
+    damage + add_attacks(player_stats, enemy_stats, rng, battle_log)
+}
+
+// applies the enemy's move without logging it, returns the damage dealt or hp healed and how it went
+pub fn resolve_enemy_action(
+    attack: EnemyAction,
+    player_stats: &mut PlayerStats,
+    enemy_stats: &mut EnemyStats,
+    rng: &mut impl Rng,
+) -> (u32, Outcome) {
+    match attack {
+        EnemyAction::Attack | EnemyAction::Magic => {
+            let (enemy_damage, outcome) = if attack == EnemyAction::Attack {
+                let (damage, strike) = physical_strike(5, enemy_stats.physatk, player_stats.def, enemy_stats.speed, player_stats.spd, rng);
+                (damage, strike.outcome())
+            } else {
+                let damage = magic_attack(5, enemy_stats.mgkatk, player_stats.mdef, rng);
+                (damage, if damage > 0 { Outcome::Hit } else { Outcome::Miss })
+            };
+            let enemy_damage = focused(enemy_damage, &mut enemy_stats.focus);
+            let (enemy_damage, outcome) = guarded(enemy_damage, outcome, &mut player_stats.guarding);
+            player_stats.hp = player_stats.hp.saturating_sub(enemy_damage);
+            (enemy_damage, outcome)
+        }
+        EnemyAction::Heal => {
+            let current_hp = enemy_stats.hp;
+            let max_hp = enemy_stats.max_hp;
+            let heal_amt = heal(4, enemy_stats.mgkatk); // get the heal amount (just a flat 5 hp for now)
+            enemy_stats.hp = current_hp + heal_amt.clamp(0, max_hp - current_hp);
+            (heal_amt, Outcome::Healed)
+        }
+        EnemyAction::Guard => (raise_guard(&mut enemy_stats.guarding, &mut enemy_stats.focus), Outcome::Passed),
+        EnemyAction::Wait => (0, Outcome::Passed),
+    }
+}
+
+// raises the guard and builds a point of focus, returns the focus now held
+pub fn raise_guard(guarding: &mut bool, focus: &mut u32) -> u32 {
+    *guarding = true;
+    *focus = (*focus + 1).min(MAX_FOCUS);
+    *focus
 }
 
-//moved function to enemy_attack to make it easier for insert_battledialogue to be called
+// focus built by guarding is all spent on the next attack
+pub fn focused(damage: u32, focus: &mut u32) -> u32 {
+    let damage = focus_bonus(damage, *focus);
+    *focus = 0;
+    damage
+}
+
+// damage with the focus added on
+pub fn focus_bonus(damage: u32, focus: u32) -> u32 {
+    ((damage as f32) * (1. + FOCUS_BONUS * focus as f32)) as u32
+}
+
+// a raised guard softens the next hit that lands and then drops
+pub fn guarded(damage: u32, outcome: Outcome, guarding: &mut bool) -> (u32, Outcome) {
+    if !*guarding || damage == 0 {
+        return (damage, outcome);
+    }
+    *guarding = false;
+    (damage * GUARD_PERCENT / 100, Outcome::Blocked)
+}
+
+//moved function to enemy_attack to make it easier to write to the battle log
 fn enemy_heal(
     commands: Commands,
     enemy_query: Query<(Entity, &Transform), With<Enemy>>,
@@ -244,16 +445,21 @@ fn enemy_heal(
             let max_hp = enemy_stats.max_hp;
             let heal_amt = heal(4, enemy_stats.mgkatk); // get the heal amount (just a flat 5 hp for now)
             enemy_stats.hp = current_hp + heal_amt.clamp(0, max_hp - current_hp);
-            
+
             info!("Enemy healed! Enemy hp is now: {}", enemy_stats.hp);
 
         }
     }
 }
 
-fn physical_attack(base_damage: u32,physical_attack: u32, physical_defense: u32) -> u32{
-    let mut final_dmg: u32 = 0;
-    let num = rand::thread_rng().gen_range(75..125);
+pub fn physical_attack(base_damage: u32,physical_attack: u32, physical_defense: u32, rng: &mut impl Rng) -> u32{
+    let num = rng.gen_range(75..125);
+    physical_damage(base_damage, physical_attack, physical_defense, num)
+}
+
+// a physical blow at `num` percent of its base strength
+fn physical_damage(base_damage: u32,physical_attack: u32, physical_defense: u32, num: u32) -> u32{
+    let mut final_dmg: u32;
     //attack
     final_dmg = ((base_damage as f64)*(((num as f64)/100.0)*(1.0+(physical_attack as f64)/10.0))) as u32;
     //defend
@@ -262,12 +468,50 @@ fn physical_attack(base_damage: u32,physical_attack: u32, physical_defense: u32)
     return final_dmg;
 }
 
-fn magic_attack(base_damage: u32,magic_attack: u32, magic_defense: u32) -> u32{
+// chance in 100 that a blow connects at all, grazes included
+pub fn accuracy(attacker_speed: u32, defender_speed: u32) -> i32 {
+    let edge = attacker_speed as i32 - defender_speed as i32;
+    (BASE_ACCURACY + ACCURACY_PER_SPEED * edge).clamp(50, 99)
+}
+
+pub fn crit_chance(speed: u32) -> u32 {
+    (BASE_CRIT + CRIT_PER_SPEED * speed).min(MAX_CRIT)
+}
+
+pub fn crit_multiplier(speed: u32) -> f64 {
+    (1.5 + 0.05 * speed as f64).min(2.5)
+}
+
+// a physical attack where speed decides whether it lands, grazes or crits
+pub fn physical_strike(
+    base_damage: u32,
+    physical_attack_stat: u32,
+    physical_defense: u32,
+    attacker_speed: u32,
+    defender_speed: u32,
+    rng: &mut impl Rng,
+) -> (u32, Strike) {
+    let roll = rng.gen_range(0..100);
+    let accuracy = accuracy(attacker_speed, defender_speed);
+    if roll >= accuracy {
+        return (0, Strike::Dodge);
+    }
+    let damage = physical_attack(base_damage, physical_attack_stat, physical_defense, rng);
+    if roll >= accuracy - GRAZE_BAND {
+        return (damage / 2, Strike::Graze);
+    }
+    if rng.gen_range(0..100) < crit_chance(attacker_speed) {
+        return (((damage as f64) * crit_multiplier(attacker_speed)) as u32, Strike::Crit);
+    }
+    (damage, Strike::Hit)
+}
+
+pub fn magic_attack(base_damage: u32,magic_attack: u32, magic_defense: u32, rng: &mut impl Rng) -> u32{
     let mut final_dmg: u32 = 0;
 
     //defend
-    let num = rand::thread_rng().gen_range(0..100);
-    let magic_contest =((((magic_attack as f64)-(magic_defense as f64)+10.0))*5.0+25.0) as u32; 
+    let num = rng.gen_range(0..100);
+    let magic_contest = magic_chance(magic_attack, magic_defense);
 
     if(num<magic_contest){
         final_dmg = ((base_damage as f64)*(1.0+(magic_attack as f64)/10.0)) as u32;
@@ -276,19 +520,278 @@ fn magic_attack(base_damage: u32,magic_attack: u32, magic_defense: u32) -> u32{
     return final_dmg;
 }
 
-fn heal(base_heal: u32,magic_attack: u32) -> u32{
+// chance in 100 that magic gets past the defender's magic defense
+fn magic_chance(magic_attack: u32, magic_defense: u32) -> u32 {
+    ((((magic_attack as f64)-(magic_defense as f64)+10.0))*5.0+25.0) as u32
+}
+
+// what an attack would do before any rolls: lowest and highest damage and the chance in 100 to land
+pub fn damage_preview(action: PlayerAction, player_stats: &PlayerStats, enemy_stats: &EnemyStats) -> Option<(u32, u32, u32)> {
+    let (low, high, chance) = match action {
+        PlayerAction::Attack => {
+            let low = physical_damage(5, player_stats.atk, enemy_stats.physdef, 75) / 2;   // a graze
+            let high = physical_damage(5, player_stats.atk, enemy_stats.physdef, 124);
+            let high = ((high as f64) * crit_multiplier(player_stats.spd)) as u32;
+            (low, high, accuracy(player_stats.spd, enemy_stats.speed) as u32)
+        }
+        PlayerAction::Magic | PlayerAction::Spell(_) => {
+            let damage = ((5.0)*(1.0+(player_stats.matk as f64)/10.0)) as u32;
+            let damage = match action {
+                PlayerAction::Spell(element) => ((damage as f32) * affinity(enemy_stats.etype, element)) as u32,
+                _ => damage,
+            };
+            (damage, damage, magic_chance(player_stats.matk, enemy_stats.mgkdef).min(100))
+        }
+        _ => return None,
+    };
+    // counts the focus built up by guarding without spending it, and the enemy's raised guard
+    let hit = |damage: u32| {
+        let damage = focus_bonus(damage, player_stats.focus);
+        if enemy_stats.guarding { damage * GUARD_PERCENT / 100 } else { damage }
+    };
+    Some((hit(low), hit(high), chance))
+}
+
+// whether the battle menu lets the player pick an action right now
+pub fn action_available(action: PlayerAction, player_stats: &PlayerStats, enemy_stats: &EnemyStats) -> bool {
+    match action {
+        PlayerAction::Heal => player_stats.hp < player_stats.max_hp,
+        PlayerAction::Run => !is_boss(enemy_stats),
+        _ => true,
+    }
+}
+
+// a spell against the enemy archetype's weakness or resistance to its element
+pub fn elemental_attack(element: Element, magic_attack_stat: u32, enemy_stats: &EnemyStats, rng: &mut impl Rng) -> (u32, Outcome) {
+    let damage = magic_attack(5, magic_attack_stat, enemy_stats.mgkdef, rng);
+    let multiplier = affinity(enemy_stats.etype, element);
+    let damage = ((damage as f32) * multiplier) as u32;
+    let outcome = if damage == 0 {
+        Outcome::Miss
+    } else if multiplier > 1. {
+        Outcome::SuperEffective
+    } else if multiplier < 1. {
+        Outcome::Resisted
+    } else {
+        Outcome::Hit
+    };
+    (damage, outcome)
+}
+
+pub fn heal(base_heal: u32,magic_attack: u32) -> u32{
     let final_heal: u32 = ((base_heal as f64)*(1.0+((magic_attack as f64)/10.0)))as u32;
 
     return final_heal;
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::turn_order::action_delay;
+    use crate::boss::BOSS_ETYPE;
+    use crate::brain::AttackBrain;
+
+    #[test]
+    fn accuracy_follows_the_speed_edge() {
+        assert_eq!(accuracy(4, 4), BASE_ACCURACY);
+        assert_eq!(accuracy(6, 4), BASE_ACCURACY + 2 * ACCURACY_PER_SPEED);
+        assert_eq!(accuracy(2, 4), BASE_ACCURACY - 2 * ACCURACY_PER_SPEED);
+        // never a sure thing either way
+        assert_eq!(accuracy(40, 0), 99);
+        assert_eq!(accuracy(0, 40), 50);
+    }
+
+    #[test]
+    fn crit_chance_and_multiplier_are_capped() {
+        assert_eq!(crit_chance(0), BASE_CRIT);
+        assert_eq!(crit_chance(10), BASE_CRIT + 10 * CRIT_PER_SPEED);
+        assert_eq!(crit_chance(100), MAX_CRIT);
+        assert_eq!(crit_multiplier(0), 1.5);
+        assert_eq!(crit_multiplier(10), 2.0);
+        assert_eq!(crit_multiplier(100), 2.5);
+    }
+
+    #[test]
+    fn physical_strike_damage_matches_its_outcome() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let (low, high) = (physical_damage(5, 2, 1, 75), physical_damage(5, 2, 1, 124));
+        let crit = |damage: u32| (damage as f64 * crit_multiplier(3)) as u32;
+        let mut seen = Vec::new();
+        for _ in 0..2000 {
+            let (damage, strike) = physical_strike(5, 2, 1, 3, 3, &mut rng);
+            match strike {
+                Strike::Dodge => assert_eq!(damage, 0),
+                Strike::Graze => assert!((low / 2..=high / 2).contains(&damage)),
+                Strike::Hit => assert!((low..=high).contains(&damage)),
+                Strike::Crit => assert!((crit(low)..=crit(high)).contains(&damage)),
+            }
+            if !seen.contains(&strike) {
+                seen.push(strike);
+            }
+        }
+        assert_eq!(seen.len(), 4);
+    }
+
+    #[test]
+    fn faster_attackers_dodge_less() {
+        let dodges = |attacker_speed, defender_speed| {
+            let mut rng = StdRng::seed_from_u64(5);
+            (0..2000)
+                .filter(|_| physical_strike(5, 2, 1, attacker_speed, defender_speed, &mut rng).1 == Strike::Dodge)
+                .count()
+        };
+        assert!(dodges(10, 0) < dodges(0, 0));
+        assert!(dodges(0, 0) < dodges(0, 10));
+    }
+
+    #[test]
+    fn the_preview_counts_focus_and_the_enemy_guard() {
+        let mut player = PlayerStats::new();
+        let mut enemy = EnemyStats::new(1);
+        let (low, high, chance) = damage_preview(PlayerAction::Attack, &player, &enemy).unwrap();
+        assert!(low <= high);
+        assert_eq!(chance, accuracy(player.spd, enemy.speed) as u32);
+
+        player.focus = MAX_FOCUS;
+        let focused = damage_preview(PlayerAction::Attack, &player, &enemy).unwrap();
+        assert_eq!(focused, (focus_bonus(low, MAX_FOCUS), focus_bonus(high, MAX_FOCUS), chance));
+        // previewing doesn't spend the focus
+        assert_eq!(player.focus, MAX_FOCUS);
+
+        enemy.guarding = true;
+        let guarded = damage_preview(PlayerAction::Attack, &player, &enemy).unwrap();
+        assert_eq!(guarded, (focused.0 * GUARD_PERCENT / 100, focused.1 * GUARD_PERCENT / 100, chance));
+    }
+
+    #[test]
+    fn spells_preview_their_affinity() {
+        let player = PlayerStats::new();
+        let enemy = EnemyStats::new(1);
+        let (magic, _, _) = damage_preview(PlayerAction::Magic, &player, &enemy).unwrap();
+        let (fire, _, _) = damage_preview(PlayerAction::Spell(Element::Fire), &player, &enemy).unwrap();
+        assert_eq!(fire, ((magic as f32) * affinity(1, Element::Fire)) as u32);
+        assert_eq!(damage_preview(PlayerAction::Guard, &player, &enemy), None);
+    }
+
+    #[test]
+    fn healing_needs_lost_hp_and_bosses_cant_be_fled() {
+        let mut player = PlayerStats::new();
+        let enemy = EnemyStats::new(1);
+        let boss = EnemyStats::new(BOSS_ETYPE);
+        assert!(!action_available(PlayerAction::Heal, &player, &enemy));
+        player.hp -= 1;
+        assert!(action_available(PlayerAction::Heal, &player, &enemy));
+        assert!(action_available(PlayerAction::Run, &player, &enemy));
+        assert!(!action_available(PlayerAction::Run, &player, &boss));
+        assert!(action_available(PlayerAction::Attack, &player, &boss));
+    }
+
+    // the same magic roll with and without the archetype's affinity applied
+    fn spell_against(element: Element, etype: u32) -> ((u32, Outcome), u32) {
+        let enemy = EnemyStats::new(etype);
+        let spell = elemental_attack(element, 4, &enemy, &mut StdRng::seed_from_u64(9));
+        let plain = magic_attack(5, 4, enemy.mgkdef, &mut StdRng::seed_from_u64(9));
+        (spell, plain)
+    }
+
+    #[test]
+    fn elemental_attack_scales_by_affinity() {
+        let ((damage, outcome), plain) = spell_against(Element::Lightning, 2);
+        assert!(plain > 0);
+        assert_eq!((damage, outcome), (plain * 2, Outcome::SuperEffective));
+
+        let ((damage, outcome), plain) = spell_against(Element::Shadow, 2);
+        assert_eq!((damage, outcome), (plain / 2, Outcome::Resisted));
 
-fn insert_battledialogue(
-        mut battle_dialogue_query: &mut Query<&mut BattleDialogue>,
-        text: String
-){
-    
-    for(mut battle_dialogue) in battle_dialogue_query{
-        battle_dialogue.change(text.clone());
+        let ((damage, outcome), plain) = spell_against(Element::Fire, 2);
+        assert_eq!((damage, outcome), (plain, Outcome::Hit));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn elemental_attack_misses_with_the_magic_roll() {
+        let mut enemy = EnemyStats::new(2);
+        enemy.mgkdef = 40;  // the magic contest can't be won
+        assert_eq!(elemental_attack(Element::Lightning, 0, &enemy, &mut StdRng::seed_from_u64(9)), (0, Outcome::Miss));
+    }
+
+    #[test]
+    fn guard_halves_the_next_hit_only() {
+        let mut guarding = true;
+        assert_eq!(guarded(9, Outcome::Hit, &mut guarding), (9 * GUARD_PERCENT / 100, Outcome::Blocked));
+        assert!(!guarding);
+        assert_eq!(guarded(9, Outcome::Hit, &mut guarding), (9, Outcome::Hit));
+
+        // a miss doesn't use the guard up
+        let mut guarding = true;
+        assert_eq!(guarded(0, Outcome::Miss, &mut guarding), (0, Outcome::Miss));
+        assert!(guarding);
+    }
+
+    #[test]
+    fn focus_builds_to_the_cap_and_is_spent_at_once() {
+        let (mut guarding, mut focus) = (false, 0);
+        for expected in 1..=MAX_FOCUS {
+            assert_eq!(raise_guard(&mut guarding, &mut focus), expected);
+        }
+        assert_eq!(raise_guard(&mut guarding, &mut focus), MAX_FOCUS);
+        assert!(guarding);
+
+        assert_eq!(focused(8, &mut focus), (8. * (1. + FOCUS_BONUS * MAX_FOCUS as f32)) as u32);
+        assert_eq!(focus, 0);
+        assert_eq!(focused(8, &mut focus), 8);
+    }
+
+    #[test]
+    fn waiting_comes_back_around_sooner() {
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut waiting = PlayerStats::new();
+        let mut guarding = PlayerStats::new();
+        let mut enemy = EnemyStats::new(1);
+        player_turn(PlayerAction::Wait, &mut waiting, &mut enemy, &mut rng, &mut battle_log);
+        player_turn(PlayerAction::Guard, &mut guarding, &mut enemy, &mut rng, &mut battle_log);
+
+        assert_eq!(waiting.next_action_tick, action_delay(waiting.spd, TurnCost::Half));
+        assert!(waiting.next_action_tick < guarding.next_action_tick);
+        assert_eq!(enemy.hp, enemy.max_hp);
+        let last = battle_log.entries.last().unwrap();
+        assert_eq!((last.action, last.outcome), (BattleAction::Guard, Outcome::Passed));
+    }
+
+    #[test]
+    fn moves_outside_the_boss_phase_fall_back_to_its_first() {
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut player = PlayerStats::new();
+        player.max_hp = 100;
+        player.hp = 100;
+        let mut boss = EnemyStats::new(BOSS_ETYPE);
+        boss.hp = boss.max_hp / 5;
+        // the desperate phase has no physical attack, so it casts magic instead
+        enemy_turn(&mut player, &mut boss, &mut AttackBrain, &mut rng, &mut battle_log);
+        assert_eq!(boss.phase, 2);
+        let moves: Vec<BattleAction> = battle_log.current().iter()
+            .filter(|entry| entry.actor == Actor::Enemy && entry.action != BattleAction::Phase)
+            .map(|entry| entry.action)
+            .collect();
+        assert_eq!(moves, vec![BattleAction::Magic, BattleAction::Add, BattleAction::Add]);
+    }
+
+    #[test]
+    fn a_beaten_enemy_is_logged_as_defeated() {
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut player = PlayerStats::new();
+        let mut enemy = EnemyStats::new(1);
+        enemy.hp = 1;
+        let result = (0..20)
+            .map(|_| player_turn(PlayerAction::Attack, &mut player, &mut enemy, &mut rng, &mut battle_log))
+            .find(|result| *result == TurnResult::Victory);
+        assert_eq!(result, Some(TurnResult::Victory));
+        let last = battle_log.entries.last().unwrap();
+        assert_eq!((last.actor, last.action, last.outcome), (Actor::Enemy, BattleAction::Defeat, Outcome::Defeated));
+    }
+}
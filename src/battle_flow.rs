@@ -0,0 +1,359 @@
+use bevy::prelude::*;
+
+use crate::{GameState, BattleState};
+use crate::player::{Player, PlayerStats};
+use crate::enemy::{Enemy, find_closest_enemy, despawn_closest_enemy};
+use crate::battle_log::{BattleLog, Actor, BattleAction, Outcome};
+use crate::replay::BattleReplay;
+use crate::events::EnemyDefeatedEvent;
+use crate::controls::{Action, ActionState};
+use crate::settings::Settings;
+use crate::locale::Localized;
+
+const INTRO_TIME: f32 = 1.;         // seconds before the first action can be picked
+pub const RESOLVE_TIME: f32 = 0.6;  // pause after each action so its animation can play out
+const DEFEAT_TIME: f32 = 1.5;
+const FLEE_TIME: f32 = 0.8;
+const SKILL_POINT_REWARD: u32 = 1;
+
+// where the battle goes once the phase in progress has played out
+#[derive(Resource)]
+pub struct BattleFlow {
+    pub timer: Timer,
+    pub next: BattleState,
+    pub enemy_acted: bool,  // the enemy already moved in this ResolveEnemy
+}
+
+impl Default for BattleFlow {
+    fn default() -> Self {
+        BattleFlow {
+            timer: Timer::from_seconds(INTRO_TIME, TimerMode::Once),
+            next: BattleState::ChooseAction,
+            enemy_acted: false,
+        }
+    }
+}
+
+impl BattleFlow {
+    // waits `seconds` and then moves on to `next`
+    pub fn then(&mut self, seconds: f32, next: BattleState) {
+        self.timer = Timer::from_seconds(seconds, TimerMode::Once);
+        self.next = next;
+    }
+}
+
+#[derive(Component)]    // everything on the rewards screen
+struct VictoryScreen;
+
+pub struct BattleFlowPlugin;
+
+impl Plugin for BattleFlowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BattleFlow>();
+        app.add_systems(OnEnter(BattleState::Intro), start_intro);
+        app.add_systems(OnEnter(BattleState::Victory), claim_victory);
+        app.add_systems(OnExit(BattleState::Victory), despawn_victory_screen);
+        app.add_systems(OnEnter(BattleState::Defeat), start_defeat);
+        app.add_systems(OnEnter(BattleState::Fled), start_flee);
+        app.add_systems(OnExit(GameState::BattleMode), reset_flow);
+        app.add_systems(Update, play_out_phase.run_if(in_state(GameState::BattleMode)));
+        app.add_systems(Update, leave_battle.after(play_out_phase).run_if(in_state(GameState::BattleMode)));
+        app.add_systems(Update, leave_victory.run_if(in_state(BattleState::Victory)));
+    }
+}
+
+fn start_intro(mut flow: ResMut<BattleFlow>) {
+    flow.then(INTRO_TIME, BattleState::ChooseAction);
+}
+
+// nothing carries over into the next battle
+fn reset_flow(mut flow: ResMut<BattleFlow>) {
+    *flow = BattleFlow::default();
+}
+
+// waits out the timed phases, then moves the battle along
+fn play_out_phase(
+    time: Res<Time>,
+    state: Res<State<BattleState>>,
+    mut next_state: ResMut<NextState<BattleState>>,
+    mut flow: ResMut<BattleFlow>,
+    settings: Res<Settings>,
+) {
+    match state.get() {
+        BattleState::Intro | BattleState::ResolvePlayer | BattleState::Defeat | BattleState::Fled => {}
+        // nothing to wait on until the enemy has made its move
+        BattleState::ResolveEnemy => {
+            if !flow.enemy_acted {
+                return;
+            }
+        }
+        BattleState::ChooseAction | BattleState::Victory => return,
+    }
+    // the battle text speed setting makes phases play out faster or slower
+    flow.timer.tick(time.delta().mul_f32(settings.text_speed.factor()));
+    if !flow.timer.finished() {
+        return;
+    }
+    match state.get() {
+        // leave_battle takes it from here
+        BattleState::Defeat | BattleState::Fled => {}
+        _ => {
+            flow.enemy_acted = false;
+            next_state.set(flow.next.clone());
+        }
+    }
+}
+
+// ends the battle once a defeat or an escape has played out
+fn leave_battle(
+    state: Res<State<BattleState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    flow: Res<BattleFlow>,
+    replay: Res<BattleReplay>,
+
+    commands: Commands,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if !flow.timer.finished() {
+        return;
+    }
+    match state.get() {
+        // a replayed defeat just ends the replay instead of killing the real player
+        BattleState::Defeat => {
+            if replay.active() {
+                next_game_state.set(GameState::InGame);
+            } else {
+                next_game_state.set(GameState::DefeatScreen);
+            }
+        }
+        BattleState::Fled => {
+            despawn_closest_enemy(commands, enemy_query, player_query);
+            next_game_state.set(GameState::InGame);
+        }
+        _ => {}
+    }
+}
+
+// hands out the rewards and shows them until the player moves on
+fn claim_victory(
+    mut commands: Commands,
+    mut player_stat_query: Query<&mut PlayerStats, With<Player>>,
+    replay: Res<BattleReplay>,
+    enemy_room_query: Query<&Enemy>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut defeated_event_writer: EventWriter<EnemyDefeatedEvent>,
+) {
+    if let Ok(mut player_stats) = player_stat_query.get_single_mut() {
+        player_stats.skill_points += SKILL_POINT_REWARD;
+    }
+    if !replay.active() {
+        if let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query) {
+            if let Ok(enemy) = enemy_room_query.get(closest_enemy) {
+                defeated_event_writer.send(EnemyDefeatedEvent { room: enemy.room });
+            }
+        }
+    }
+
+    commands.spawn((
+        VictoryScreen,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.),
+                padding: UiRect::all(Val::Px(30.)),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+            ..default()
+        })
+        .with_children(|panel| {
+            panel.spawn((
+                Localized::new("victory-title"),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 48.,
+                        color: Color::srgb(1., 0.85, 0.4),
+                        ..default()
+                    },
+                ),
+            ));
+            panel.spawn((
+                Localized::new("victory-skill-points").with("points", SKILL_POINT_REWARD as i64),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 30.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+            panel.spawn((
+                Localized::new("victory-continue"),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 22.,
+                        color: Color::srgb(0.7, 0.7, 0.7),
+                        ..default()
+                    },
+                ),
+            ));
+        });
+    });
+}
+
+fn leave_victory(
+    actions: Res<ActionState>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+
+    commands: Commands,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if actions.just_pressed(Action::Confirm) {
+        despawn_closest_enemy(commands, enemy_query, player_query);  // Despawn the enemy if defeated
+        next_game_state.set(GameState::InGame);
+    }
+}
+
+fn despawn_victory_screen(mut commands: Commands, query: Query<Entity, With<VictoryScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// the defeat is logged right away, the defeat screen follows once it has sunk in
+fn start_defeat(mut flow: ResMut<BattleFlow>, mut battle_log: ResMut<BattleLog>) {
+    battle_log.push(Actor::Player, BattleAction::Defeat, 0, Outcome::Defeated);
+    flow.timer = Timer::from_seconds(DEFEAT_TIME, TimerMode::Once);
+}
+
+fn start_flee(mut flow: ResMut<BattleFlow>) {
+    flow.timer = Timer::from_seconds(FLEE_TIME, TimerMode::Once);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    // the battle flow on its own, with time stepping a tenth of a second per update
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+        app.init_state::<GameState>();
+        app.add_sub_state::<BattleState>();
+        app.init_resource::<Settings>();
+        app.init_resource::<BattleLog>();
+        app.init_resource::<BattleReplay>();
+        app.init_resource::<ActionState>();
+        app.add_event::<EnemyDefeatedEvent>();
+        app.add_plugins(BattleFlowPlugin);
+        app.world_mut().spawn((Player, PlayerStats::new(), Transform::default()));
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::BattleMode);
+        app.update();
+        app
+    }
+
+    fn battle_state(app: &App) -> BattleState {
+        app.world().resource::<State<BattleState>>().get().clone()
+    }
+
+    fn game_state(app: &App) -> GameState {
+        app.world().resource::<State<GameState>>().get().clone()
+    }
+
+    fn set_battle_state(app: &mut App, state: BattleState) {
+        app.world_mut().resource_mut::<NextState<BattleState>>().set(state);
+        app.update();
+    }
+
+    // long enough for the timer to run out, plus a frame for the state change to apply
+    fn wait(app: &mut App, seconds: f32) {
+        for _ in 0..(seconds * 10.).ceil() as u32 + 2 {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn intro_hands_over_to_the_player() {
+        let mut app = test_app();
+        assert_eq!(battle_state(&app), BattleState::Intro);
+        wait(&mut app, INTRO_TIME);
+        assert_eq!(battle_state(&app), BattleState::ChooseAction);
+    }
+
+    #[test]
+    fn enemy_phase_waits_for_the_enemy_to_act() {
+        let mut app = test_app();
+        app.world_mut().resource_mut::<BattleFlow>().then(RESOLVE_TIME, BattleState::ChooseAction);
+        set_battle_state(&mut app, BattleState::ResolveEnemy);
+        wait(&mut app, RESOLVE_TIME);
+        assert_eq!(battle_state(&app), BattleState::ResolveEnemy);
+
+        app.world_mut().resource_mut::<BattleFlow>().enemy_acted = true;
+        wait(&mut app, RESOLVE_TIME);
+        assert_eq!(battle_state(&app), BattleState::ChooseAction);
+        assert!(!app.world().resource::<BattleFlow>().enemy_acted);
+    }
+
+    #[test]
+    fn defeat_is_logged_for_the_player_then_shows_the_defeat_screen() {
+        let mut app = test_app();
+        set_battle_state(&mut app, BattleState::Defeat);
+        let last = app.world().resource::<BattleLog>().entries.last().cloned().unwrap();
+        assert_eq!((last.actor, last.action, last.outcome), (Actor::Player, BattleAction::Defeat, Outcome::Defeated));
+        assert_eq!(game_state(&app), GameState::BattleMode);
+
+        wait(&mut app, DEFEAT_TIME);
+        assert_eq!(game_state(&app), GameState::DefeatScreen);
+    }
+
+    #[test]
+    fn fleeing_goes_back_to_exploring() {
+        let mut app = test_app();
+        set_battle_state(&mut app, BattleState::Fled);
+        wait(&mut app, FLEE_TIME);
+        assert_eq!(game_state(&app), GameState::InGame);
+        // the next battle starts from the intro again
+        assert_eq!(app.world().resource::<BattleFlow>().next, BattleState::ChooseAction);
+    }
+
+    #[test]
+    fn victory_pays_out_and_waits_for_confirm() {
+        let mut app = test_app();
+        set_battle_state(&mut app, BattleState::Victory);
+        let player_stats = app.world_mut().query::<&PlayerStats>().single(app.world());
+        assert_eq!(player_stats.skill_points, SKILL_POINT_REWARD);
+        wait(&mut app, 1.);
+        assert_eq!(battle_state(&app), BattleState::Victory);
+
+        app.world_mut().resource_mut::<ActionState>().press(Action::Confirm, true);
+        app.update();
+        app.update();
+        assert_eq!(game_state(&app), GameState::InGame);
+    }
+}
@@ -0,0 +1,203 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+use crate::GameState;
+use crate::boss::phase_dialogue;
+use crate::element::Element;
+use crate::events::CombatEvent;
+use crate::controls::{Action, ActionState};
+use crate::battle::{battle_input, enemy_attack, MAX_FOCUS};
+use crate::locale::Locale;
+
+pub const LOG_LINES: usize = 4;     // battle log lines visible in the battle panel at once
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Actor {
+    Player,
+    Enemy,
+    System,     // battle start and other narration
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BattleAction {
+    Start,
+    Attack,
+    Magic,
+    Spell(Element),
+    Heal,
+    Run,
+    Guard,      // amount is the focus held afterwards
+    Wait,
+    Phase,      // a boss moved to a new phase, amount is the phase number
+    Add,        // one of a boss's summoned adds attacked
+    Defeat,     // the actor was beaten, always logged with Outcome::Defeated
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Started,
+    Hit,
+    Crit,
+    Graze,
+    Miss,
+    Dodged,     // the target sidestepped a physical attack
+    SuperEffective,     // a spell hit the target's weakness
+    Resisted,
+    Blocked,    // a raised guard softened the hit
+    Healed,
+    Fled,
+    Passed,
+    Defeated,   // the actor was defeated
+}
+
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub battle: u32,    // which battle of the run this happened in, starting at 1
+    pub actor: Actor,
+    pub action: BattleAction,
+    pub amount: u32,    // damage dealt or hp healed
+    pub outcome: Outcome,
+}
+
+impl LogEntry {
+    // the line shown in the battle panel and the journal
+    pub fn describe(&self, locale: &Locale) -> String {
+        let key = match (self.actor, self.action, self.outcome) {
+            (_, _, Outcome::Started) => "log-battle-start",
+            (Actor::Player, _, Outcome::Defeated) => "log-player-defeated",
+            (_, _, Outcome::Defeated) => "log-enemy-defeated",
+            (Actor::Player, _, Outcome::Blocked) => "log-enemy-blocked",
+            (_, _, Outcome::Blocked) => "log-player-blocked",
+            (Actor::Player, BattleAction::Guard, _) => "log-player-guard",
+            (Actor::Player, BattleAction::Attack, Outcome::Miss) => "log-player-attack-miss",
+            (Actor::Player, BattleAction::Attack, Outcome::Dodged) => "log-player-attack-dodged",
+            (Actor::Player, BattleAction::Attack, Outcome::Crit) => "log-player-attack-crit",
+            (Actor::Player, BattleAction::Attack, Outcome::Graze) => "log-player-attack-graze",
+            (Actor::Player, BattleAction::Attack, _) => "log-player-attack",
+            (Actor::Player, BattleAction::Magic, Outcome::Miss) => "log-player-magic-miss",
+            (Actor::Player, BattleAction::Magic, _) => "log-player-magic",
+            (Actor::Player, BattleAction::Spell(_), Outcome::Miss) => "log-player-spell-miss",
+            (Actor::Player, BattleAction::Spell(_), Outcome::SuperEffective) => "log-player-spell-weak",
+            (Actor::Player, BattleAction::Spell(_), Outcome::Resisted) => "log-player-spell-resisted",
+            (Actor::Player, BattleAction::Spell(_), _) => "log-player-spell",
+            (Actor::Player, BattleAction::Heal, _) => "log-player-heal",
+            (Actor::Player, BattleAction::Run, Outcome::Miss) => "log-player-run-blocked",
+            (Actor::Player, BattleAction::Run, _) => "log-player-run",
+            (Actor::Player, _, _) => "log-player-wait",
+            (_, BattleAction::Guard, _) => "log-enemy-guard",
+            (_, BattleAction::Attack, Outcome::Miss) => "log-enemy-attack-miss",
+            (_, BattleAction::Attack, Outcome::Dodged) => "log-enemy-attack-dodged",
+            (_, BattleAction::Attack, Outcome::Crit) => "log-enemy-attack-crit",
+            (_, BattleAction::Attack, Outcome::Graze) => "log-enemy-attack-graze",
+            (_, BattleAction::Attack, _) => "log-enemy-attack",
+            (_, BattleAction::Magic, Outcome::Miss) => "log-enemy-magic-miss",
+            (_, BattleAction::Magic, _) => "log-enemy-magic",
+            (_, BattleAction::Heal, _) => "log-enemy-heal",
+            (_, BattleAction::Run, _) => "log-enemy-run",
+            (_, BattleAction::Phase, _) => phase_dialogue(self.amount),
+            (_, BattleAction::Add, Outcome::Miss | Outcome::Dodged) => "log-add-miss",
+            (_, BattleAction::Add, Outcome::Crit) => "log-add-crit",
+            (_, BattleAction::Add, _) => "log-add",
+            (_, _, _) => "log-enemy-wait",
+        };
+        let element = match self.action {
+            BattleAction::Spell(element) => locale.text(element.key()),
+            _ => String::new(),
+        };
+        locale.format(key, &[("amount", self.amount.into()), ("element", element.into()), ("max", MAX_FOCUS.into())])
+    }
+}
+
+// Every entry of every battle this run, shown in the battle panel and kept for the journal
+#[derive(Resource, Default)]
+pub struct BattleLog {
+    pub entries: Vec<LogEntry>,
+    pub battles: u32,
+    current_start: usize,   // index of the first entry of the current battle
+    pub scroll: usize,      // lines scrolled up from the newest entry in the battle panel
+}
+
+impl BattleLog {
+    pub fn start_battle(&mut self) {
+        self.battles += 1;
+        self.current_start = self.entries.len();
+        self.scroll = 0;
+        self.push(Actor::System, BattleAction::Start, 0, Outcome::Started);
+    }
+
+    pub fn push(&mut self, actor: Actor, action: BattleAction, amount: u32, outcome: Outcome) {
+        let entry = LogEntry {
+            battle: self.battles,
+            actor,
+            action,
+            amount,
+            outcome,
+        };
+        info!("{:?}", entry);
+        self.entries.push(entry);
+        // jump back to the newest line when something happens
+        self.scroll = 0;
+    }
+
+    // entries of the battle in progress (or the last one fought)
+    pub fn current(&self) -> &[LogEntry] {
+        &self.entries[self.current_start..]
+    }
+}
+
+pub struct BattleLogPlugin;
+
+impl Plugin for BattleLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BattleLog>();
+        app.add_systems(OnEnter(GameState::BattleMode), start_battle_log);
+        app.add_systems(Update, scroll_battle_log.run_if(in_state(GameState::BattleMode)));
+        app.add_systems(Update, announce_log_entries.after(battle_input).after(enemy_attack));
+    }
+}
+
+pub fn start_battle_log(mut battle_log: ResMut<BattleLog>) {
+    battle_log.start_battle();
+}
+
+// turns every entry logged since last frame into a CombatEvent for the fight scene
+fn announce_log_entries(
+    battle_log: Res<BattleLog>,
+    mut seen: Local<usize>,
+    mut combat_events: EventWriter<CombatEvent>,
+) {
+    if *seen > battle_log.entries.len() {
+        *seen = 0;
+    }
+    for entry in battle_log.entries[*seen..].iter() {
+        combat_events.send(CombatEvent(entry.clone()));
+    }
+    *seen = battle_log.entries.len();
+}
+
+// mouse wheel or the log actions (PageUp/PageDown by default) scroll back through the current battle
+fn scroll_battle_log(
+    actions: Res<ActionState>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut battle_log: ResMut<BattleLog>,
+) {
+    let mut scroll = battle_log.scroll as i32;
+    for event in scroll_events.read() {
+        if event.y > 0. {
+            scroll += 1;
+        } else if event.y < 0. {
+            scroll -= 1;
+        }
+    }
+    if actions.just_pressed(Action::LogUp) {
+        scroll += 1;
+    }
+    if actions.just_pressed(Action::LogDown) {
+        scroll -= 1;
+    }
+    let max_scroll = battle_log.current().len().saturating_sub(LOG_LINES) as i32;
+    let scroll = scroll.clamp(0, max_scroll) as usize;
+    if scroll != battle_log.scroll {
+        battle_log.scroll = scroll;
+    }
+}
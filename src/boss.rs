@@ -0,0 +1,519 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::GameState;
+use crate::player::{Player, PlayerStats};
+use crate::enemy::{Enemy, EnemyStats, find_closest_enemy};
+use crate::dungeon::{Door, DungeonLayout, ClearedRooms, RoomKind, LevelBounds};
+use crate::battle_log::{BattleLog, Actor, BattleAction, Outcome, start_battle_log};
+use crate::battle::{physical_strike, guarded};
+use crate::brain::EnemyAction;
+use crate::locale::Locale;
+use crate::settings::Settings;
+
+pub const BOSS_ETYPE: u32 = 3;
+pub const BOSS_NAME: &str = "boss-name";     // message key
+
+const ADD_DAMAGE: u32 = 2;          // base damage of each summoned add's bite
+const ADD_SPEED: u32 = 4;           // flyders are quick, so they are hard to dodge
+const INTRO_TIME: f32 = 2.5;        // seconds the boss's name stays on screen
+const SEALED_TINT: Color = Color::srgb(0.35, 0.2, 0.2);
+
+// A stage of the boss fight, entered once the boss's hp drops to `below` of its max; name and dialogue are message keys
+pub struct BossPhase {
+    pub name: &'static str,
+    pub below: f32,
+    pub dialogue: &'static str,
+    pub moves: &'static [EnemyAction],
+    pub actions_per_turn: u32,
+    // added to the boss's stats when the phase starts
+    pub physatk: u32,
+    pub mgkatk: u32,
+    pub speed: u32,
+    pub adds: u32,      // flyders summoned to fight alongside it
+}
+
+pub const BOSS_PHASES: [BossPhase; 3] = [
+    BossPhase {
+        name: "boss-phase-warden",
+        below: 1.,
+        dialogue: "boss-dialogue-warden",
+        moves: &[EnemyAction::Attack, EnemyAction::Magic, EnemyAction::Guard],
+        actions_per_turn: 1,
+        physatk: 0,
+        mgkatk: 0,
+        speed: 0,
+        adds: 0,
+    },
+    BossPhase {
+        name: "boss-phase-enraged",
+        below: 0.6,
+        dialogue: "boss-dialogue-enraged",
+        moves: &[EnemyAction::Attack, EnemyAction::Magic],
+        actions_per_turn: 2,
+        physatk: 3,
+        mgkatk: 0,
+        speed: 3,
+        adds: 0,
+    },
+    BossPhase {
+        name: "boss-phase-desperate",
+        below: 0.3,
+        dialogue: "boss-dialogue-desperate",
+        moves: &[EnemyAction::Magic, EnemyAction::Heal],
+        actions_per_turn: 1,
+        physatk: 0,
+        mgkatk: 2,
+        speed: 0,
+        adds: 2,
+    },
+];
+
+pub fn is_boss(enemy_stats: &EnemyStats) -> bool {
+    enemy_stats.etype == BOSS_ETYPE
+}
+
+fn phase(enemy_stats: &EnemyStats) -> &'static BossPhase {
+    &BOSS_PHASES[(enemy_stats.phase as usize).min(BOSS_PHASES.len() - 1)]
+}
+
+// the line logged when a phase starts, phase 0 is the intro
+pub fn phase_dialogue(phase: u32) -> &'static str {
+    BOSS_PHASES.get(phase as usize).map(|p| p.dialogue).unwrap_or("")
+}
+
+// moves the enemy may pick from this turn
+pub fn moveset(enemy_stats: &EnemyStats) -> &'static [EnemyAction] {
+    if is_boss(enemy_stats) {
+        phase(enemy_stats).moves
+    } else {
+        &EnemyAction::ALL
+    }
+}
+
+pub fn actions_per_turn(enemy_stats: &EnemyStats) -> u32 {
+    if is_boss(enemy_stats) {
+        phase(enemy_stats).actions_per_turn
+    } else {
+        1
+    }
+}
+
+// enters every phase whose threshold the boss's hp has dropped to, logging each one
+pub fn advance_phase(enemy_stats: &mut EnemyStats, battle_log: &mut BattleLog) {
+    if !is_boss(enemy_stats) {
+        return;
+    }
+    let hp = enemy_stats.hp as f32 / enemy_stats.max_hp.max(1) as f32;
+    while let Some(next) = BOSS_PHASES.get(enemy_stats.phase as usize + 1) {
+        if hp > next.below {
+            break;
+        }
+        enemy_stats.phase += 1;
+        enemy_stats.physatk += next.physatk;
+        enemy_stats.mgkatk += next.mgkatk;
+        enemy_stats.speed += next.speed;
+        enemy_stats.adds += next.adds;
+        battle_log.push(Actor::Enemy, BattleAction::Phase, enemy_stats.phase, Outcome::Passed);
+    }
+}
+
+// each summoned add bites the player once per enemy turn, returns the total damage
+pub fn add_attacks(
+    player_stats: &mut PlayerStats,
+    enemy_stats: &EnemyStats,
+    rng: &mut impl Rng,
+    battle_log: &mut BattleLog,
+) -> u32 {
+    let mut total = 0;
+    for _ in 0..enemy_stats.adds {
+        if player_stats.hp == 0 {
+            break;
+        }
+        let (damage, strike) = physical_strike(ADD_DAMAGE, 1, player_stats.def, ADD_SPEED, player_stats.spd, rng);
+        let (damage, outcome) = guarded(damage, strike.outcome(), &mut player_stats.guarding);
+        player_stats.hp = player_stats.hp.saturating_sub(damage);
+        battle_log.push(Actor::Enemy, BattleAction::Add, damage, outcome);
+        total += damage;
+    }
+    total
+}
+
+#[derive(Component)]    // root of the boss name and health bar at the top of the battle screen
+struct BossUI;
+
+#[derive(Component)]
+struct BossNameText;
+
+#[derive(Component)]
+struct BossHealthFill;
+
+#[derive(Component)]    // big name card shown as the fight starts
+struct BossIntro(Timer);
+
+#[derive(Component)]    // battle sprite of one summoned add
+struct AddSprite;
+
+// the exit door stays shut while the boss room is uncleared
+#[derive(Component)]
+pub struct Sealed;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_boss_ui);
+        app.add_systems(OnEnter(GameState::BattleMode), begin_boss_battle.after(start_battle_log));
+        app.add_systems(OnExit(GameState::BattleMode), end_boss_battle);
+        app.add_systems(Update, (update_boss_ui, update_add_sprites).run_if(in_state(GameState::BattleMode)));
+        app.add_systems(Update, fade_boss_intro);
+        app.add_systems(Update, color_boss_bar.run_if(resource_changed::<Settings>));
+        app.add_systems(Update, seal_exit);
+    }
+}
+
+fn setup_boss_ui(mut commands: Commands) {
+    commands.spawn((
+        BossUI,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(20.),
+                left: Val::Percent(25.),
+                width: Val::Percent(50.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            BossNameText,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 28.,
+                    color: Color::srgb(1., 0.85, 0.4),
+                    ..default()
+                },
+            ),
+        ));
+        // bar background, the fill and a tick where each later phase starts
+        parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Px(18.),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgb(0.2, 0.05, 0.05)),
+            ..default()
+        })
+        .with_children(|bar| {
+            bar.spawn((
+                BossHealthFill,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::srgb(0.75, 0.1, 0.1)),
+                    ..default()
+                },
+            ));
+            for phase in BOSS_PHASES.iter().skip(1) {
+                bar.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(phase.below * 100.),
+                        width: Val::Px(2.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::WHITE),
+                    ..default()
+                });
+            }
+        });
+    });
+}
+
+fn begin_boss_battle(
+    mut commands: Commands,
+    mut battle_log: ResMut<BattleLog>,
+    enemy_stat_query: Query<&EnemyStats, With<Enemy>>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut boss_ui: Query<&mut Visibility, With<BossUI>>,
+    locale: Res<Locale>,
+) {
+    let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query) else {
+        return;
+    };
+    let Ok(enemy_stats) = enemy_stat_query.get(closest_enemy) else {
+        return;
+    };
+    if !is_boss(enemy_stats) {
+        return;
+    }
+
+    battle_log.push(Actor::Enemy, BattleAction::Phase, 0, Outcome::Passed);
+    for mut visibility in boss_ui.iter_mut() {
+        *visibility = Visibility::Visible;
+    }
+    commands.spawn((
+        BossIntro(Timer::from_seconds(INTRO_TIME, TimerMode::Once)),
+        TextBundle::from_section(
+            locale.text(BOSS_NAME).to_uppercase(),
+            TextStyle {
+                font_size: 96.,
+                color: Color::srgb(1., 0.85, 0.4),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(35.),
+            width: Val::Percent(100.),
+            justify_self: JustifySelf::Center,
+            ..default()
+        })
+        .with_text_justify(JustifyText::Center),
+    ));
+}
+
+fn end_boss_battle(
+    mut commands: Commands,
+    mut boss_ui: Query<&mut Visibility, With<BossUI>>,
+    intros: Query<Entity, With<BossIntro>>,
+    adds: Query<Entity, With<AddSprite>>,
+) {
+    for mut visibility in boss_ui.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    for entity in intros.iter().chain(adds.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_boss_ui(
+    commands: Commands,
+    enemy_stat_query: Query<&EnemyStats, With<Enemy>>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut fill: Query<&mut Style, With<BossHealthFill>>,
+    mut name: Query<&mut Text, With<BossNameText>>,
+    locale: Res<Locale>,
+) {
+    let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query) else {
+        return;
+    };
+    let Ok(enemy_stats) = enemy_stat_query.get(closest_enemy) else {
+        return;
+    };
+    if !is_boss(enemy_stats) {
+        return;
+    }
+    let hp = enemy_stats.hp as f32 / enemy_stats.max_hp.max(1) as f32;
+    for mut style in fill.iter_mut() {
+        style.width = Val::Percent(hp * 100.);
+    }
+    for mut text in name.iter_mut() {
+        let label = if enemy_stats.phase == 0 {
+            locale.text(BOSS_NAME)
+        } else {
+            locale.format("boss-label", &[("name", locale.text(BOSS_NAME).into()), ("phase", locale.text(phase(enemy_stats).name).into())])
+        };
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+    }
+}
+
+fn color_boss_bar(settings: Res<Settings>, mut fill: Query<&mut BackgroundColor, With<BossHealthFill>>) {
+    for mut background in fill.iter_mut() {
+        background.0 = settings.palette().boss_health;
+    }
+}
+
+// keeps one flyder on the battle screen per summoned add
+fn update_add_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    bounds: Res<LevelBounds>,
+    enemy_stat_query: Query<&EnemyStats, With<Enemy>>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+    adds: Query<Entity, With<AddSprite>>,
+) {
+    let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query) else {
+        return;
+    };
+    let Ok(enemy_stats) = enemy_stat_query.get(closest_enemy) else {
+        return;
+    };
+    let Ok(pt) = player_query.get_single() else {
+        return;
+    };
+    let shown = adds.iter().count() as u32;
+    if shown >= enemy_stats.adds {
+        return;
+    }
+    let screen = bounds.screen_center(pt.translation);
+    for i in shown..enemy_stats.adds {
+        commands.spawn((
+            AddSprite,
+            SpriteBundle {
+                texture: asset_server.load("flyder.png"),
+                transform: Transform {
+                    translation: Vec3::new(screen.x + 380. + 40. * i as f32, screen.y + 40. - 140. * i as f32, pt.translation.z + 1.15),
+                    scale: Vec3::splat(0.8),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+}
+
+fn fade_boss_intro(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut intros: Query<(Entity, &mut BossIntro, &mut Text)>,
+) {
+    for (entity, mut intro, mut text) in intros.iter_mut() {
+        intro.0.tick(time.delta());
+        if intro.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = 1. - intro.0.fraction();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
+
+// the exit opens once the boss room is cleared, including after loading a save
+fn seal_exit(
+    mut commands: Commands,
+    layout: Res<DungeonLayout>,
+    cleared: Res<ClearedRooms>,
+    mut doors: Query<(Entity, &mut Sprite, Has<Sealed>), With<Door>>,
+) {
+    let boss_room = layout.rooms.iter().position(|room| room.kind == RoomKind::Boss);
+    let sealed = match boss_room {
+        Some(room) => !cleared.0.contains(&room),
+        None => false,
+    };
+    for (entity, mut sprite, is_sealed) in doors.iter_mut() {
+        if sealed && !is_sealed {
+            commands.entity(entity).insert(Sealed);
+            sprite.color = SEALED_TINT;
+        } else if !sealed && is_sealed {
+            commands.entity(entity).remove::<Sealed>();
+            sprite.color = Color::WHITE;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a battle log with just its start entry
+    fn log() -> BattleLog {
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+        battle_log
+    }
+
+    #[test]
+    fn one_big_hit_can_cross_two_phases() {
+        let mut battle_log = log();
+        let mut boss = EnemyStats::new(BOSS_ETYPE);
+        let fresh = boss.clone();
+        boss.hp = boss.max_hp / 5;
+        advance_phase(&mut boss, &mut battle_log);
+
+        assert_eq!(boss.phase, 2);
+        assert_eq!(boss.physatk, fresh.physatk + 3);
+        assert_eq!(boss.speed, fresh.speed + 3);
+        assert_eq!(boss.mgkatk, fresh.mgkatk + 2);
+        assert_eq!(boss.adds, 2);
+        let phases: Vec<u32> = battle_log.current().iter()
+            .filter(|entry| entry.action == BattleAction::Phase)
+            .map(|entry| entry.amount)
+            .collect();
+        assert_eq!(phases, vec![1, 2]);
+
+        // the bumps are only applied once
+        advance_phase(&mut boss, &mut battle_log);
+        assert_eq!(boss.phase, 2);
+        assert_eq!(boss.physatk, fresh.physatk + 3);
+        assert_eq!(battle_log.current().len(), 3);
+    }
+
+    #[test]
+    fn phases_start_at_their_threshold() {
+        let mut battle_log = log();
+        let mut boss = EnemyStats::new(BOSS_ETYPE);
+        boss.hp = boss.max_hp * 6 / 10 + 1;
+        advance_phase(&mut boss, &mut battle_log);
+        assert_eq!(boss.phase, 0);
+        boss.hp = boss.max_hp * 6 / 10;
+        advance_phase(&mut boss, &mut battle_log);
+        assert_eq!(boss.phase, 1);
+    }
+
+    #[test]
+    fn other_enemies_have_no_phases() {
+        let mut battle_log = log();
+        let mut enemy = EnemyStats::new(2);
+        let fresh = enemy.clone();
+        enemy.hp = 1;
+        advance_phase(&mut enemy, &mut battle_log);
+        assert_eq!((enemy.phase, enemy.physatk, enemy.speed, enemy.adds), (0, fresh.physatk, fresh.speed, 0));
+        assert_eq!(battle_log.current().len(), 1);
+        assert_eq!(actions_per_turn(&enemy), 1);
+        assert_eq!(moveset(&enemy), &EnemyAction::ALL);
+    }
+
+    #[test]
+    fn each_phase_has_its_own_moves_and_pace() {
+        let mut boss = EnemyStats::new(BOSS_ETYPE);
+        assert_eq!(actions_per_turn(&boss), 1);
+        assert!(moveset(&boss).contains(&EnemyAction::Guard));
+        boss.phase = 1;
+        assert_eq!(actions_per_turn(&boss), 2);
+        assert_eq!(moveset(&boss), &[EnemyAction::Attack, EnemyAction::Magic]);
+        boss.phase = 2;
+        assert_eq!(actions_per_turn(&boss), 1);
+        assert_eq!(moveset(&boss), &[EnemyAction::Magic, EnemyAction::Heal]);
+        assert_eq!(phase_dialogue(2), "boss-dialogue-desperate");
+        assert_eq!(phase_dialogue(3), "");
+    }
+
+    #[test]
+    fn adds_bite_in_the_desperate_phase() {
+        let mut battle_log = log();
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut boss = EnemyStats::new(BOSS_ETYPE);
+        let mut player = PlayerStats::new();
+        player.max_hp = 100;
+        player.hp = 100;
+        assert_eq!(add_attacks(&mut player, &boss, &mut rng, &mut battle_log), 0);
+
+        boss.hp = boss.max_hp / 5;
+        advance_phase(&mut boss, &mut battle_log);
+        let total: u32 = (0..5).map(|_| add_attacks(&mut player, &boss, &mut rng, &mut battle_log)).sum();
+        assert!(total > 0);
+        assert_eq!(player.hp, 100 - total);
+        let bites = battle_log.current().iter().filter(|entry| entry.action == BattleAction::Add).count();
+        assert_eq!(bites, 10);
+    }
+}
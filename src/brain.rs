@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::player::PlayerStats;
+use crate::enemy::EnemyStats;
+use crate::battle_log::BattleLog;
+use crate::attack::{rand_attack, adaptive_attack, best_action, EnemyMemory};
+use crate::utility::UtilityConfig;
+use crate::mcts::MctsBrain;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyAction {
+    Attack,
+    Magic,
+    Heal,
+    Guard,
+    Wait,
+}
+
+impl EnemyAction {
+    pub const ALL: [EnemyAction; 5] = [EnemyAction::Attack, EnemyAction::Magic, EnemyAction::Heal, EnemyAction::Guard, EnemyAction::Wait];
+}
+
+// everything a brain may look at when it picks a move
+pub struct BattleSnapshot<'a> {
+    pub player_stats: &'a PlayerStats,
+    pub enemy_stats: &'a EnemyStats,
+    pub battle_log: &'a BattleLog,
+}
+
+// An enemy AI strategy. Brains may keep state between turns but only read the battle.
+pub trait EnemyBrain: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn choose_action(&mut self, snapshot: &BattleSnapshot, rng: &mut dyn RngCore) -> EnemyAction;
+}
+
+#[derive(Component)]
+pub struct Brain(pub Box<dyn EnemyBrain>);
+
+// the brain each enemy type fights with
+pub fn brain_for(etype: u32, config: &UtilityConfig) -> Box<dyn EnemyBrain> {
+    match etype {
+        1 => Box::new(RandomBrain),
+        2 => Box::new(AdaptiveBrain::new(config.clone())),
+        3 => Box::new(MctsBrain),
+        _ => Box::new(HeuristicBrain::new(config.clone())),
+    }
+}
+
+// lets tools like the balance simulator swap brains in by name
+pub fn brain_named(name: &str, config: &UtilityConfig) -> Option<Box<dyn EnemyBrain>> {
+    match name {
+        "attack" => Some(Box::new(AttackBrain)),
+        "random" => Some(Box::new(RandomBrain)),
+        "heuristic" => Some(Box::new(HeuristicBrain::new(config.clone()))),
+        "adaptive" => Some(Box::new(AdaptiveBrain::new(config.clone()))),
+        "mcts" => Some(Box::new(MctsBrain)),
+        _ => None,
+    }
+}
+
+// always attacks, a baseline for the balance simulator
+pub struct AttackBrain;
+
+impl EnemyBrain for AttackBrain {
+    fn name(&self) -> &'static str {
+        "attack"
+    }
+
+    fn choose_action(&mut self, _snapshot: &BattleSnapshot, _rng: &mut dyn RngCore) -> EnemyAction {
+        EnemyAction::Attack
+    }
+}
+
+pub struct RandomBrain;
+
+impl EnemyBrain for RandomBrain {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn choose_action(&mut self, _snapshot: &BattleSnapshot, mut rng: &mut dyn RngCore) -> EnemyAction {
+        rand_attack(&mut rng)
+    }
+}
+
+// picks the move with the best utility score, stateless
+pub struct HeuristicBrain {
+    pub config: UtilityConfig,
+}
+
+impl HeuristicBrain {
+    pub fn new(config: UtilityConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl EnemyBrain for HeuristicBrain {
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+
+    fn choose_action(&mut self, snapshot: &BattleSnapshot, _rng: &mut dyn RngCore) -> EnemyAction {
+        let action = best_action(self.config.score(snapshot));
+        debug!("heuristic brain chose {:?}", action);
+        action
+    }
+}
+
+pub struct AdaptiveBrain {
+    pub config: UtilityConfig,
+    pub memory: EnemyMemory,
+}
+
+impl AdaptiveBrain {
+    pub fn new(config: UtilityConfig) -> Self {
+        Self { config, memory: EnemyMemory::default() }
+    }
+}
+
+impl EnemyBrain for AdaptiveBrain {
+    fn name(&self) -> &'static str {
+        "adaptive"
+    }
+
+    fn choose_action(&mut self, snapshot: &BattleSnapshot, _rng: &mut dyn RngCore) -> EnemyAction {
+        // catch up on the player's move and how the enemy's last one went
+        self.memory.learn(snapshot.battle_log);
+        let action = adaptive_attack(self.config.score(snapshot), &self.memory, snapshot.enemy_stats.etype);
+        debug!("adaptive brain chose {:?} with learned values {:?}", action, self.memory.values);
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boss::{BOSS_ETYPE, BOSS_PHASES};
+
+    const SEED: u64 = 7;
+
+    // every brain gets the same opening position: a fresh player against an etype 2 enemy
+    fn choose(brain: &mut dyn EnemyBrain, player: &PlayerStats, enemy: &EnemyStats) -> EnemyAction {
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+        let snapshot = BattleSnapshot { player_stats: player, enemy_stats: enemy, battle_log: &battle_log };
+        brain.choose_action(&snapshot, &mut StdRng::seed_from_u64(SEED))
+    }
+
+    #[test]
+    fn attack_brain_always_attacks() {
+        let player = PlayerStats::new();
+        let mut enemy = EnemyStats::new(BOSS_ETYPE);
+        assert_eq!(choose(&mut AttackBrain, &player, &enemy), EnemyAction::Attack);
+        enemy.hp = 1;
+        assert_eq!(choose(&mut AttackBrain, &player, &enemy), EnemyAction::Attack);
+    }
+
+    #[test]
+    fn boss_uses_every_move_of_each_phase() {
+        let mut brain = brain_for(BOSS_ETYPE, &UtilityConfig::default());
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+        for (phase, boss_phase) in BOSS_PHASES.iter().enumerate() {
+            let mut enemy = EnemyStats::new(BOSS_ETYPE);
+            enemy.phase = phase as u32;
+            enemy.hp = (boss_phase.below * enemy.max_hp as f32 / 2.) as u32;
+            let mut chosen = Vec::new();
+            // a weak or a healthy player, with or without a magic ward, guarding or not
+            for hp in [1, 10] {
+                for mdef in [0, 20] {
+                    for guarding in [false, true] {
+                        let mut player = PlayerStats::new();
+                        player.hp = hp;
+                        player.mdef = mdef;
+                        player.guarding = guarding;
+                        let snapshot = BattleSnapshot { player_stats: &player, enemy_stats: &enemy, battle_log: &battle_log };
+                        for seed in 0..3 {
+                            chosen.push(brain.choose_action(&snapshot, &mut StdRng::seed_from_u64(seed)));
+                        }
+                    }
+                }
+            }
+            for action in boss_phase.moves {
+                assert!(chosen.contains(action), "{} never chose {:?}", boss_phase.name, action);
+            }
+            assert!(chosen.iter().all(|action| boss_phase.moves.contains(action)));
+        }
+    }
+
+    #[test]
+    fn random_brain_follows_the_seed() {
+        let player = PlayerStats::new();
+        let enemy = EnemyStats::new(1);
+        let expected = rand_attack(&mut StdRng::seed_from_u64(SEED));
+        assert_eq!(choose(&mut RandomBrain, &player, &enemy), expected);
+        assert_eq!(choose(&mut RandomBrain, &player, &enemy), expected);
+    }
+
+    #[test]
+    fn random_brain_can_guard_and_wait() {
+        let player = PlayerStats::new();
+        let enemy = EnemyStats::new(1);
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+        let snapshot = BattleSnapshot { player_stats: &player, enemy_stats: &enemy, battle_log: &battle_log };
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let chosen: Vec<EnemyAction> = (0..50).map(|_| RandomBrain.choose_action(&snapshot, &mut rng)).collect();
+        for action in EnemyAction::ALL {
+            assert!(chosen.contains(&action), "never chose {:?}", action);
+        }
+    }
+
+    #[test]
+    fn heuristic_brain_heals_when_low() {
+        let player = PlayerStats::new();
+        let mut enemy = EnemyStats::new(2);
+        let mut brain = HeuristicBrain::new(UtilityConfig::default());
+        assert_eq!(choose(&mut brain, &player, &enemy), EnemyAction::Attack);
+        enemy.hp = enemy.max_hp / 5;
+        assert_eq!(choose(&mut brain, &player, &enemy), EnemyAction::Heal);
+    }
+
+    #[test]
+    fn adaptive_brain_starts_from_the_heuristic() {
+        let player = PlayerStats::new();
+        let enemy = EnemyStats::new(2);
+        let mut brain = AdaptiveBrain::new(UtilityConfig::default());
+        let mut heuristic = HeuristicBrain::new(UtilityConfig::default());
+        assert_eq!(choose(&mut brain, &player, &enemy), choose(&mut heuristic, &player, &enemy));
+    }
+
+    #[test]
+    fn mcts_brain_is_repeatable_and_goes_for_the_kill() {
+        let mut player = PlayerStats::new();
+        let enemy = EnemyStats::new(2);
+        let first = choose(&mut MctsBrain, &player, &enemy);
+        assert_eq!(choose(&mut MctsBrain, &player, &enemy), first);
+
+        // one hit ends it, so healing or standing still is never the best move
+        player.hp = 1;
+        let action = choose(&mut MctsBrain, &player, &enemy);
+        assert!(matches!(action, EnemyAction::Attack | EnemyAction::Magic), "chose {:?}", action);
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use bevy::transform::TransformSystem;
+use rand::prelude::*;
+
+use crate::GameState;
+use crate::player::{Player, Velocity};
+use crate::dungeon::LevelBounds;
+use crate::events::CameraShakeEvent;
+use crate::controls::{Action, ActionState};
+use crate::settings::Settings;
+use crate::{WIN_W, WIN_H};
+
+const FOLLOW_SPEED: f32 = 6.;       // how quickly the camera catches up, higher is snappier
+const LOOK_AHEAD: f32 = 0.35;       // seconds of player velocity the camera leads by
+const ZOOM_SPEED: f32 = 8.;
+const ZOOM_LEVELS: [f32; 3] = [1.0, 1.5, 0.75];  // projection scales, cycled with Z
+const MAX_SHAKE: f32 = 24.;         // pixels of offset at full trauma
+const SHAKE_DECAY: f32 = 1.5;       // trauma lost per second
+
+#[derive(Component)]
+pub struct GameCamera {
+    focus: Vec2,    // where the camera looks before shake is applied
+    scale: f32,
+    zoom_level: usize,
+    trauma: f32,
+}
+
+impl GameCamera {
+    fn new() -> Self {
+        Self {
+            focus: Vec2::ZERO,
+            scale: ZOOM_LEVELS[0],
+            zoom_level: 0,
+            trauma: 0.,
+        }
+    }
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_camera);
+        app.add_systems(Update, cycle_zoom.run_if(in_state(GameState::InGame)));
+        app.add_systems(Update, add_trauma);
+        // after gameplay has moved the player, before transforms are propagated for rendering
+        app.add_systems(PostUpdate, update_camera.before(TransformSystem::TransformPropagate));
+    }
+}
+
+// the world is laid out for a WIN_W x WIN_H view, so any window size shows at least that much of it
+fn spawn_camera(mut commands: Commands) {
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scaling_mode = ScalingMode::AutoMin { min_width: WIN_W, min_height: WIN_H };
+    commands.spawn((camera, GameCamera::new()));
+}
+
+fn cycle_zoom(
+    actions: Res<ActionState>,
+    mut camera: Query<&mut GameCamera>,
+) {
+    if actions.just_pressed(Action::CycleZoom) {
+        if let Ok(mut camera) = camera.get_single_mut() {
+            camera.zoom_level = (camera.zoom_level + 1) % ZOOM_LEVELS.len();
+        }
+    }
+}
+
+// with reduced motion on the shakes are still read, just never felt
+fn add_trauma(
+    mut shake_events: EventReader<CameraShakeEvent>,
+    settings: Res<Settings>,
+    mut camera: Query<&mut GameCamera>,
+) {
+    for event in shake_events.read() {
+        if settings.reduced_motion {
+            continue;
+        }
+        if let Ok(mut camera) = camera.get_single_mut() {
+            camera.trauma = (camera.trauma + event.trauma).min(1.);
+        }
+    }
+}
+
+// the player, kept apart from the camera's own Transform
+type FollowedPlayer = (With<Player>, Without<GameCamera>);
+
+fn update_camera(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    bounds: Res<LevelBounds>,
+    player: Query<(&Transform, &Velocity), FollowedPlayer>,
+    mut camera: Query<(&mut GameCamera, &mut Transform, &mut OrthographicProjection)>,
+) {
+    let Ok((mut camera, mut ct, mut projection)) = camera.get_single_mut() else {
+        return;
+    };
+    let Ok((pt, pv)) = player.get_single() else {
+        return;
+    };
+    let deltat = time.delta_seconds();
+
+    match state.get() {
+        GameState::InGame => {
+            // ease toward the zoom level and a point ahead of where the player is heading
+            let target_scale = ZOOM_LEVELS[camera.zoom_level];
+            camera.scale += (target_scale - camera.scale) * (1. - (-ZOOM_SPEED * deltat).exp());
+
+            let view = Vec2::new(WIN_W, WIN_H) * camera.scale;
+            let target = bounds.clamp_view(pt.translation + (pv.velocity * LOOK_AHEAD).extend(0.), view);
+            let focus = camera.focus.lerp(target, 1. - (-FOLLOW_SPEED * deltat).exp());
+            camera.focus = bounds.clamp_view(focus.extend(0.), view);
+        }
+        // the welcome screen is drawn around the origin, the player may be anywhere after quitting to it
+        GameState::Welcome => {
+            camera.scale = 1.;
+            camera.focus = Vec2::ZERO;
+        }
+        // the credits bring their own camera
+        GameState::EndCredits => return,
+        // battle, defeat, skill tree, map, journal and controls screens are laid out around screen_center at normal zoom
+        GameState::BattleMode | GameState::DefeatScreen | GameState::SkillTreeMenu | GameState::MapView | GameState::Journal | GameState::Controls => {
+            camera.scale = 1.;
+            camera.focus = bounds.screen_center(pt.translation);
+        }
+    }
+
+    let mut offset = Vec2::ZERO;
+    if camera.trauma > 0. {
+        // squaring the trauma keeps small hits subtle and big ones punchy
+        let strength = MAX_SHAKE * camera.trauma * camera.trauma;
+        let mut rng = thread_rng();
+        offset = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * strength;
+        camera.trauma = (camera.trauma - SHAKE_DECAY * deltat).max(0.);
+    }
+
+    ct.translation.x = camera.focus.x + offset.x;
+    ct.translation.y = camera.focus.y + offset.y;
+    projection.scale = camera.scale;
+}
@@ -0,0 +1,712 @@
+use std::collections::HashSet;
+use std::fs;
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{GameState, PauseState};
+use crate::locale::{Locale, Localized};
+
+const CONFIG_DIR: &str = "config";
+const BINDINGS_FILE: &str = "config/bindings.ron";
+
+// Everything the player can do, named so the keys behind it can be changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    SkillTree,
+    Map,
+    Journal,
+    Controls,
+    Pause,
+    CycleZoom,
+    Interact,   // use whatever the player stands next to, e.g. a shrine
+    QuickSave,
+    QuickLoad,
+    Replay,
+    SpendStrength,
+    SpendMagic,
+    SpendAgility,
+    SpendHealth,
+    Attack,
+    Magic,
+    Heal,
+    Run,
+    Wait,
+    Guard,
+    CycleSpell,
+    LogUp,
+    LogDown,
+    ZoomIn,
+    ZoomOut,
+    ResetBindings,
+}
+
+impl Action {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Action::Up => "action-up",
+            Action::Down => "action-down",
+            Action::Left => "action-left",
+            Action::Right => "action-right",
+            Action::Confirm => "action-confirm",
+            Action::Back => "action-back",
+            Action::SkillTree => "action-skill-tree",
+            Action::Map => "action-map",
+            Action::Journal => "action-journal",
+            Action::Controls => "action-controls",
+            Action::Pause => "action-pause",
+            Action::CycleZoom => "action-cycle-zoom",
+            Action::Interact => "action-interact",
+            Action::QuickSave => "action-quick-save",
+            Action::QuickLoad => "action-quick-load",
+            Action::Replay => "action-replay",
+            Action::SpendStrength => "action-spend-strength",
+            Action::SpendMagic => "action-spend-magic",
+            Action::SpendAgility => "action-spend-agility",
+            Action::SpendHealth => "action-spend-health",
+            Action::Attack => "action-attack",
+            Action::Magic => "action-magic",
+            Action::Heal => "action-heal",
+            Action::Run => "action-run",
+            Action::Wait => "action-wait",
+            Action::Guard => "action-guard",
+            Action::CycleSpell => "action-cycle-spell",
+            Action::LogUp => "action-log-up",
+            Action::LogDown => "action-log-down",
+            Action::ZoomIn => "action-zoom-in",
+            Action::ZoomOut => "action-zoom-out",
+            Action::ResetBindings => "action-reset-bindings",
+        }
+    }
+}
+
+// which set of bindings is live, so the same key can mean different things per screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputContext {
+    Overworld,
+    Battle,
+    Menu,
+}
+
+impl InputContext {
+    pub const ALL: [InputContext; 3] = [InputContext::Overworld, InputContext::Battle, InputContext::Menu];
+
+    pub fn of(state: &GameState) -> Self {
+        match state {
+            GameState::InGame => InputContext::Overworld,
+            GameState::BattleMode => InputContext::Battle,
+            GameState::Welcome
+            | GameState::SkillTreeMenu
+            | GameState::EndCredits
+            | GameState::DefeatScreen
+            | GameState::MapView
+            | GameState::Journal
+            | GameState::Controls => InputContext::Menu,
+        }
+    }
+
+    // the pause menu is navigated like any other menu, whatever it was opened over
+    pub fn current(state: &GameState, pause: &PauseState) -> Self {
+        match pause {
+            PauseState::Running => Self::of(state),
+            PauseState::Paused | PauseState::Settings => InputContext::Menu,
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            InputContext::Overworld => "context-overworld",
+            InputContext::Battle => "context-battle",
+            InputContext::Menu => "context-menu",
+        }
+    }
+}
+
+// one action and the keys that trigger it, keys by their KeyCode name such as "KeyW" or "Digit1"
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Binding {
+    pub action: Action,
+    pub keys: Vec<String>,
+}
+
+impl Binding {
+    fn new(action: Action, keys: &[KeyCode]) -> Self {
+        Binding { action, keys: keys.iter().map(|key| key_name(*key)).collect() }
+    }
+}
+
+// the action map, read from BINDINGS_FILE so players can edit it by hand or on the controls screen
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct Bindings {
+    pub overworld: Vec<Binding>,
+    pub battle: Vec<Binding>,
+    pub menu: Vec<Binding>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            overworld: vec![
+                Binding::new(Action::Up, &[KeyCode::KeyW]),
+                Binding::new(Action::Down, &[KeyCode::KeyS]),
+                Binding::new(Action::Left, &[KeyCode::KeyA]),
+                Binding::new(Action::Right, &[KeyCode::KeyD]),
+                Binding::new(Action::SkillTree, &[KeyCode::KeyQ]),
+                Binding::new(Action::Map, &[KeyCode::KeyM]),
+                Binding::new(Action::Journal, &[KeyCode::KeyJ]),
+                Binding::new(Action::CycleZoom, &[KeyCode::KeyZ]),
+                Binding::new(Action::Interact, &[KeyCode::KeyE]),
+                Binding::new(Action::QuickSave, &[KeyCode::F5]),
+                Binding::new(Action::QuickLoad, &[KeyCode::F9]),
+                Binding::new(Action::Replay, &[KeyCode::F8]),
+                Binding::new(Action::Controls, &[KeyCode::F1]),
+                Binding::new(Action::Pause, &[KeyCode::Escape]),
+            ],
+            battle: vec![
+                Binding::new(Action::Up, &[KeyCode::ArrowUp]),
+                Binding::new(Action::Down, &[KeyCode::ArrowDown]),
+                Binding::new(Action::Attack, &[KeyCode::Digit1]),
+                Binding::new(Action::Magic, &[KeyCode::Digit2]),
+                Binding::new(Action::Heal, &[KeyCode::Digit3]),
+                Binding::new(Action::Run, &[KeyCode::Digit4]),
+                Binding::new(Action::Wait, &[KeyCode::Digit5]),
+                Binding::new(Action::Guard, &[KeyCode::Digit6]),
+                Binding::new(Action::CycleSpell, &[KeyCode::KeyE]),
+                Binding::new(Action::LogUp, &[KeyCode::PageUp]),
+                Binding::new(Action::LogDown, &[KeyCode::PageDown]),
+                Binding::new(Action::Confirm, &[KeyCode::Enter, KeyCode::Space]),
+                Binding::new(Action::Back, &[KeyCode::Backspace]),
+                Binding::new(Action::Pause, &[KeyCode::Escape]),
+            ],
+            menu: vec![
+                Binding::new(Action::Up, &[KeyCode::KeyW, KeyCode::ArrowUp]),
+                Binding::new(Action::Down, &[KeyCode::KeyS, KeyCode::ArrowDown]),
+                Binding::new(Action::Left, &[KeyCode::KeyA, KeyCode::ArrowLeft]),
+                Binding::new(Action::Right, &[KeyCode::KeyD, KeyCode::ArrowRight]),
+                Binding::new(Action::Confirm, &[KeyCode::Space, KeyCode::Enter]),
+                Binding::new(Action::Back, &[KeyCode::Escape]),
+                Binding::new(Action::SkillTree, &[KeyCode::KeyQ]),
+                Binding::new(Action::Map, &[KeyCode::KeyM]),
+                Binding::new(Action::Journal, &[KeyCode::KeyJ]),
+                Binding::new(Action::Controls, &[KeyCode::F1]),
+                Binding::new(Action::SpendStrength, &[KeyCode::KeyF]),
+                Binding::new(Action::SpendMagic, &[KeyCode::KeyG]),
+                Binding::new(Action::SpendAgility, &[KeyCode::KeyH]),
+                Binding::new(Action::SpendHealth, &[KeyCode::KeyK]),
+                Binding::new(Action::ZoomIn, &[KeyCode::Equal, KeyCode::NumpadAdd]),
+                Binding::new(Action::ZoomOut, &[KeyCode::Minus, KeyCode::NumpadSubtract]),
+                Binding::new(Action::ResetBindings, &[KeyCode::KeyR]),
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    // the player's bindings if the file is there and parses; the defaults are written out otherwise
+    pub fn load() -> Self {
+        let text = match fs::read_to_string(BINDINGS_FILE) {
+            Ok(text) => text,
+            Err(_) => {
+                let bindings = Self::default();
+                bindings.save();
+                return bindings;
+            }
+        };
+        match ron::from_str::<Bindings>(&text) {
+            Ok(mut bindings) => {
+                bindings.add_missing_actions();
+                for binding in bindings.overworld.iter().chain(bindings.battle.iter()).chain(bindings.menu.iter()) {
+                    for key in binding.keys.iter().filter(|key| key_from_name(key).is_none()) {
+                        warn!("Unknown key {} bound to {:?} in {}", key, binding.action, BINDINGS_FILE);
+                    }
+                }
+                for context in InputContext::ALL {
+                    for binding in bindings.context(context).iter() {
+                        for key in binding.keys.iter() {
+                            if let Some(other) = bindings.bound_to(context, key, binding.action) {
+                                warn!("{} is bound to both {:?} and {:?} in {}", key, binding.action, other, BINDINGS_FILE);
+                            }
+                        }
+                    }
+                }
+                bindings
+            }
+            Err(e) => {
+                warn!("Could not parse {}: {}, using the default bindings", BINDINGS_FILE, e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let text = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Could not serialize bindings: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = fs::create_dir_all(CONFIG_DIR).and_then(|_| fs::write(BINDINGS_FILE, text)) {
+            warn!("Could not write {}: {}", BINDINGS_FILE, e);
+        }
+    }
+
+    pub fn context(&self, context: InputContext) -> &Vec<Binding> {
+        match context {
+            InputContext::Overworld => &self.overworld,
+            InputContext::Battle => &self.battle,
+            InputContext::Menu => &self.menu,
+        }
+    }
+
+    pub fn context_mut(&mut self, context: InputContext) -> &mut Vec<Binding> {
+        match context {
+            InputContext::Overworld => &mut self.overworld,
+            InputContext::Battle => &mut self.battle,
+            InputContext::Menu => &mut self.menu,
+        }
+    }
+
+    // actions added since the file was written get their default keys
+    fn add_missing_actions(&mut self) {
+        let defaults = Self::default();
+        for context in InputContext::ALL {
+            for default in defaults.context(context).iter() {
+                if !self.context(context).iter().any(|binding| binding.action == default.action) {
+                    self.context_mut(context).push(default.clone());
+                }
+            }
+        }
+    }
+
+    // the other action in this context that `key` already triggers, if any
+    pub fn bound_to(&self, context: InputContext, key: &str, action: Action) -> Option<Action> {
+        self.context(context)
+            .iter()
+            .find(|binding| binding.action != action && binding.keys.iter().any(|bound| bound == key))
+            .map(|binding| binding.action)
+    }
+
+    // puts `key` in place of the binding's first key, keeping its alternates;
+    // refused with the action that already uses the key in this context
+    pub fn rebind(&mut self, context: InputContext, index: usize, key: KeyCode) -> Result<(), Action> {
+        let name = key_name(key);
+        let Some(action) = self.context(context).get(index).map(|binding| binding.action) else {
+            return Ok(());
+        };
+        if let Some(other) = self.bound_to(context, &name, action) {
+            return Err(other);
+        }
+        let binding = &mut self.context_mut(context)[index];
+        binding.keys.retain(|bound| *bound != name);
+        if binding.keys.is_empty() {
+            binding.keys.push(name);
+        } else {
+            binding.keys[0] = name;
+        }
+        Ok(())
+    }
+}
+
+// keys that can be bound; anything else in the bindings file is ignored
+const BINDABLE_KEYS: [KeyCode; 70] = [
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF, KeyCode::KeyG,
+    KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL, KeyCode::KeyM, KeyCode::KeyN,
+    KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU,
+    KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY, KeyCode::KeyZ,
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+    KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::ArrowUp, KeyCode::ArrowDown, KeyCode::ArrowLeft, KeyCode::ArrowRight,
+    KeyCode::Space, KeyCode::Enter, KeyCode::Escape, KeyCode::Tab, KeyCode::Backspace,
+    KeyCode::ShiftLeft, KeyCode::ShiftRight, KeyCode::ControlLeft, KeyCode::ControlRight,
+    KeyCode::PageUp, KeyCode::PageDown, KeyCode::Home, KeyCode::End,
+    KeyCode::Equal, KeyCode::Minus, KeyCode::NumpadAdd, KeyCode::NumpadSubtract, KeyCode::NumpadEnter,
+];
+
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS.iter().copied().find(|key| key_name(*key) == name)
+}
+
+// the actions held and newly pressed this frame, in the current context
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    movement: Vec2,     // analog movement, zero unless a stick is tilted
+    blocked: bool,      // the controls screen is waiting for a key
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn movement(&self) -> Vec2 {
+        self.movement
+    }
+
+    // for input that doesn't come from the keyboard
+    pub fn press(&mut self, action: Action, just_pressed: bool) {
+        if self.blocked {
+            return;
+        }
+        self.pressed.insert(action);
+        if just_pressed {
+            self.just_pressed.insert(action);
+        }
+    }
+
+    pub fn set_movement(&mut self, movement: Vec2) {
+        if !self.blocked {
+            self.movement = movement;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pressed.clear();
+        self.just_pressed.clear();
+        self.movement = Vec2::ZERO;
+        self.blocked = false;
+    }
+}
+
+// keyboard, pad and on-screen menus all feed ActionState in here, before any gameplay reads it
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReadInput;
+
+// run condition, the action-map version of input_just_pressed
+pub fn action_just_pressed(action: Action) -> impl FnMut(Res<ActionState>) -> bool + Clone {
+    move |actions: Res<ActionState>| actions.just_pressed(action)
+}
+
+// where the controls screen is and whether it is waiting for a key
+#[derive(Resource, Default)]
+pub struct RebindScreen {
+    context: usize,     // index into InputContext::ALL
+    cursor: usize,
+    capturing: bool,
+    conflict: Option<(String, Action)>,     // the last key refused and the action that already has it
+}
+
+#[derive(Component)]    // root ui node of the controls screen
+struct ControlsUI;
+
+#[derive(Component)]    // the list of bindings
+struct ControlsText;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Bindings::load());
+        app.init_resource::<ActionState>();
+        app.init_resource::<RebindScreen>();
+        app.add_systems(PreUpdate, read_actions.in_set(ReadInput).after(InputSystem));
+        app.add_systems(Startup, setup_controls_screen);
+        app.add_systems(Update, toggle_controls_screen);
+        app.add_systems(Update, rebind.run_if(in_state(GameState::Controls)));
+        app.add_systems(Update, update_controls_screen.after(rebind).run_if(in_state(GameState::Controls)));
+        app.add_systems(OnEnter(GameState::Controls), show_controls_screen);
+        app.add_systems(OnExit(GameState::Controls), hide_controls_screen);
+    }
+}
+
+// turns the keys held this frame into actions, using the bindings of the current screen
+pub fn read_actions(
+    input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    pause: Res<State<PauseState>>,
+    bindings: Res<Bindings>,
+    screen: Res<RebindScreen>,
+    mut actions: ResMut<ActionState>,
+) {
+    actions.clear();
+    // a key pressed while rebinding only goes to the new binding
+    if screen.capturing {
+        actions.blocked = true;
+        return;
+    }
+    for binding in bindings.context(InputContext::current(state.get(), pause.get())).iter() {
+        for key in binding.keys.iter().filter_map(|key| key_from_name(key)) {
+            if input.pressed(key) {
+                actions.pressed.insert(binding.action);
+            }
+            if input.just_pressed(key) {
+                actions.just_pressed.insert(binding.action);
+            }
+        }
+    }
+}
+
+fn setup_controls_screen(mut commands: Commands) {
+    commands.spawn((
+        ControlsUI,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(40.0)),
+                row_gap: Val::Px(20.0),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Localized::new("controls-title"),
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::srgb(1.0, 1.0, 0.0),
+                    ..default()
+                },
+            ),
+        ));
+        parent.spawn((
+            ControlsText,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 22.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+        ));
+        parent.spawn((
+            Localized::new("controls-help"),
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::srgb(0.6, 0.6, 0.6),
+                    ..default()
+                },
+            ),
+        ));
+    });
+}
+
+// F1 opens the controls from the overworld, same toggle style as the journal
+fn toggle_controls_screen(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    actions: Res<ActionState>,
+) {
+    let back = *state.get() == GameState::Controls && actions.just_pressed(Action::Back);
+    if actions.just_pressed(Action::Controls) || back {
+        match state.get() {
+            GameState::Welcome => next_state.set(GameState::Welcome),
+            GameState::InGame => next_state.set(GameState::Controls),
+            GameState::SkillTreeMenu => next_state.set(GameState::SkillTreeMenu),
+            GameState::BattleMode => next_state.set(GameState::BattleMode),
+            GameState::EndCredits => next_state.set(GameState::EndCredits),
+            GameState::DefeatScreen => next_state.set(GameState::DefeatScreen),
+            GameState::MapView => next_state.set(GameState::MapView),
+            GameState::Journal => next_state.set(GameState::Journal),
+            GameState::Controls => next_state.set(GameState::InGame),
+        }
+    }
+}
+
+fn show_controls_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<ControlsUI>>,
+    mut screen: ResMut<RebindScreen>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(Visibility::Visible);
+    }
+    *screen = RebindScreen::default();
+}
+
+fn hide_controls_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<ControlsUI>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(Visibility::Hidden);
+    }
+}
+
+// moves the cursor, and puts the next key pressed on the selected action
+fn rebind(
+    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
+    mut screen: ResMut<RebindScreen>,
+    mut bindings: ResMut<Bindings>,
+) {
+    let context = InputContext::ALL[screen.context];
+    if screen.capturing {
+        let Some(key) = input.get_just_pressed().copied().find(|key| BINDABLE_KEYS.contains(key)) else {
+            return;
+        };
+        screen.capturing = false;
+        // Escape cancels instead of being bound
+        if key == KeyCode::Escape {
+            return;
+        }
+        let cursor = screen.cursor;
+        match bindings.rebind(context, cursor, key) {
+            Ok(()) => {
+                info!("{} bound in {:?}", key_name(key), context);
+                bindings.save();
+            }
+            Err(other) => screen.conflict = Some((key_name(key), other)),
+        }
+        return;
+    }
+
+    let count = bindings.context(context).len();
+    if actions.just_pressed(Action::Up) {
+        screen.cursor = (screen.cursor + count - 1) % count;
+    }
+    if actions.just_pressed(Action::Down) {
+        screen.cursor = (screen.cursor + 1) % count;
+    }
+    if actions.just_pressed(Action::Left) || actions.just_pressed(Action::Right) {
+        let step = if actions.just_pressed(Action::Left) { InputContext::ALL.len() - 1 } else { 1 };
+        screen.context = (screen.context + step) % InputContext::ALL.len();
+        let count = bindings.context(InputContext::ALL[screen.context]).len();
+        screen.cursor = screen.cursor.min(count - 1);
+    }
+    if actions.just_pressed(Action::Confirm) {
+        screen.capturing = true;
+        screen.conflict = None;
+    }
+    if actions.just_pressed(Action::ResetBindings) {
+        *bindings = Bindings::default();
+        bindings.save();
+    }
+}
+
+fn update_controls_screen(
+    screen: Res<RebindScreen>,
+    bindings: Res<Bindings>,
+    locale: Res<Locale>,
+    mut text: Query<&mut Text, With<ControlsText>>,
+) {
+    if !screen.is_changed() && !bindings.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+    let context = InputContext::ALL[screen.context];
+    let tabs: Vec<String> = InputContext::ALL
+        .iter()
+        .map(|other| if *other == context { format!("[{}]", locale.text(other.key())) } else { locale.text(other.key()) })
+        .collect();
+    let mut lines = vec![tabs.join("   "), String::new()];
+    for (i, binding) in bindings.context(context).iter().enumerate() {
+        let keys = if screen.capturing && i == screen.cursor {
+            locale.text("controls-press-key")
+        } else {
+            binding.keys.join(" / ")
+        };
+        let marker = if i == screen.cursor { ">" } else { " " };
+        lines.push(format!("{} {}: {}", marker, locale.text(binding.action.key()), keys));
+    }
+    if let Some((key, action)) = screen.conflict.as_ref() {
+        lines.push(String::new());
+        lines.push(locale.format("controls-conflict", &[("key", key.clone().into()), ("action", locale.text(action.key()).into())]));
+    }
+    text.sections[0].value = lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_key_does_two_things_by_default() {
+        let bindings = Bindings::default();
+        for context in InputContext::ALL {
+            for binding in bindings.context(context).iter() {
+                for key in binding.keys.iter() {
+                    assert_eq!(bindings.bound_to(context, key, binding.action), None, "{} in {:?}", key, context);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rebinding_keeps_the_alternate_keys() {
+        let mut bindings = Bindings::default();
+        let index = bindings.menu.iter().position(|binding| binding.action == Action::Up).unwrap();
+        assert_eq!(bindings.rebind(InputContext::Menu, index, KeyCode::KeyI), Ok(()));
+        assert_eq!(bindings.menu[index].keys, vec!["KeyI".to_string(), "ArrowUp".to_string()]);
+
+        // picking the alternate again just moves it to the front
+        assert_eq!(bindings.rebind(InputContext::Menu, index, KeyCode::ArrowUp), Ok(()));
+        assert_eq!(bindings.menu[index].keys, vec!["ArrowUp".to_string()]);
+    }
+
+    #[test]
+    fn rebinding_to_a_taken_key_is_refused() {
+        let mut bindings = Bindings::default();
+        let index = bindings.menu.iter().position(|binding| binding.action == Action::SpendHealth).unwrap();
+        assert_eq!(bindings.rebind(InputContext::Menu, index, KeyCode::KeyJ), Err(Action::Journal));
+        assert_eq!(bindings.menu[index].keys, vec!["KeyK".to_string()]);
+    }
+
+    #[test]
+    fn old_files_get_the_new_actions() {
+        let mut bindings = Bindings::default();
+        bindings.menu.retain(|binding| binding.action != Action::ResetBindings);
+        bindings.add_missing_actions();
+        assert!(bindings.menu.iter().any(|binding| binding.action == Action::ResetBindings));
+        assert_eq!(bindings.menu.len(), Bindings::default().menu.len());
+    }
+
+    #[test]
+    fn pressed_actions_last_until_cleared() {
+        let mut actions = ActionState::default();
+        actions.press(Action::Confirm, true);
+        actions.press(Action::Up, false);
+        actions.set_movement(Vec2::X);
+        assert!(actions.pressed(Action::Confirm) && actions.just_pressed(Action::Confirm));
+        assert!(actions.pressed(Action::Up) && !actions.just_pressed(Action::Up));
+        assert_eq!(actions.movement(), Vec2::X);
+
+        actions.clear();
+        assert!(!actions.pressed(Action::Confirm) && !actions.just_pressed(Action::Confirm));
+        assert_eq!(actions.movement(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn a_blocked_state_ignores_input_until_cleared() {
+        let mut actions = ActionState { blocked: true, ..default() };
+        actions.press(Action::Confirm, true);
+        actions.set_movement(Vec2::Y);
+        assert!(!actions.pressed(Action::Confirm));
+        assert_eq!(actions.movement(), Vec2::ZERO);
+
+        actions.clear();
+        actions.press(Action::Confirm, true);
+        assert!(actions.just_pressed(Action::Confirm));
+    }
+}
@@ -2,35 +2,120 @@ use bevy::prelude::*;
 use crate::GameState;
 use crate::player::PlayerStats;
 use crate::player::Player;
-use crate::WIN_W;
-use crate::WIN_H;
-use crate::player::LEVEL_W;
-use crate::player::LEVEL_H;
+use crate::dungeon::LevelBounds;
+use crate::enemy::{Enemy, EnemyStats, find_closest_enemy};
+use crate::battle_log::{Actor, BattleAction, BattleLog, LogEntry, LOG_LINES};
+use crate::controls::{ActionState, ReadInput};
+use crate::menu::pick_button;
+use crate::events::NewRunEvent;
+use crate::locale::{Locale, Localized};
+use crate::settings::Settings;
+
+const BUTTON_COLOR: Color = Color::srgb(1., 0.44, 0.44);
+const FOCUS_COLOR: Color = Color::srgb(0.53, 0.53, 0.53);
 
 pub struct DefeatScreenPlugin;
 
 #[derive(Component)]
 struct DefeatScreenElement;
 
+// where the player comes back after a defeat; the start of the dungeon until a shrine moves it
+#[derive(Component, Default, Clone, Copy)]
+pub struct Checkpoint(pub Vec2);
+
+#[derive(Clone, Copy, PartialEq)]
+enum DefeatOption {
+    Respawn,    // at the checkpoint, paying the death penalty
+    Restart,    // a new run with a new seed, straight to the skill tree
+    Title,
+}
+
+impl DefeatOption {
+    const ALL: [DefeatOption; 3] = [DefeatOption::Respawn, DefeatOption::Restart, DefeatOption::Title];
+
+    fn key(&self) -> &'static str {
+        match self {
+            DefeatOption::Respawn => "defeat-respawn",
+            DefeatOption::Restart => "defeat-restart",
+            DefeatOption::Title => "defeat-title-screen",
+        }
+    }
+}
+
+#[derive(Component)]
+struct DefeatButton {
+    option: DefeatOption,
+    index: usize,
+}
+
+// what the death recap shows, taken from the battle that was lost
+#[derive(Resource, Default)]
+struct DeathRecap {
+    killer: &'static str,   // message key of the enemy's name
+    last_moments: Vec<LogEntry>,
+}
+
+// the button Up/Down or the mouse last moved to
+#[derive(Resource, Default)]
+struct DefeatFocus(usize);
+
+// the option picked this frame, carried out by apply_defeat_option
+#[derive(Resource, Default)]
+struct DefeatPick(Option<DefeatOption>);
+
 impl Plugin for DefeatScreenPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::DefeatScreen), spawn_defeat_screen);
+        app.init_resource::<DeathRecap>();
+        app.init_resource::<DefeatFocus>();
+        app.init_resource::<DefeatPick>();
+        app.add_systems(OnEnter(GameState::DefeatScreen), (record_death, spawn_defeat_screen).chain());
         app.add_systems(OnExit(GameState::DefeatScreen), despawn_defeat_screen);
-        app.add_systems(Update, handle_respawn_button.run_if(in_state(GameState::DefeatScreen)));
+        app.add_systems(PreUpdate, (pick_defeat_option, apply_defeat_option).chain().after(ReadInput).run_if(in_state(GameState::DefeatScreen)));
+        app.add_systems(Update, style_defeat_screen.run_if(in_state(GameState::DefeatScreen)));
+    }
+}
+
+// the enemy still stands next to the player, and the lost battle is the current one in the log
+fn record_death(
+    mut recap: ResMut<DeathRecap>,
+    battle_log: Res<BattleLog>,
+    enemy_stat_query: Query<&EnemyStats, With<Enemy>>,
+    commands: Commands,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let entries = battle_log.current();
+    let enemy_stats = find_closest_enemy(&commands, &enemy_query, &player_query).and_then(|enemy| enemy_stat_query.get(enemy).ok());
+    recap.killer = killer(entries, enemy_stats);
+    recap.last_moments = entries[entries.len().saturating_sub(LOG_LINES)..].to_vec();
+}
+
+// message key of whatever landed the last blow: the enemy fought, or one of a boss's adds
+fn killer(entries: &[LogEntry], enemy_stats: Option<&EnemyStats>) -> &'static str {
+    let last_blow = entries.iter().rev().find(|entry| {
+        entry.actor == Actor::Enemy
+            && entry.amount > 0
+            && matches!(entry.action, BattleAction::Attack | BattleAction::Magic | BattleAction::Add)
+    });
+    match last_blow {
+        Some(entry) if entry.action == BattleAction::Add => "enemy-flyder",
+        _ => enemy_stats.map(|enemy_stats| enemy_stats.name_key()).unwrap_or("enemy-lurker"),
     }
 }
 
 fn spawn_defeat_screen(
     mut commands: Commands, asset_server: Res<AssetServer>,
-    player: Query<&Transform, With<Player>>
+    player: Query<(&Transform, &PlayerStats), With<Player>>,
+    bounds: Res<LevelBounds>,
+    recap: Res<DeathRecap>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
 ) {
+    let (pt, player_stats) = player.single();
+    let screen = bounds.screen_center(pt.translation);
 
-    let pt = player.single();
-    let x_bound = LEVEL_W / 2. - WIN_W / 2.;
-    let y_bound = LEVEL_H / 2. - WIN_H / 2.;
-
-    let x_player = pt.translation.x.clamp(-x_bound, x_bound);   // same logic as camera/player movement
-    let y_player = pt.translation.y.clamp(-y_bound, y_bound);   // same logic as camera/player movement
+    let x_player = screen.x;
+    let y_player = screen.y;
     let z_player = pt.translation.z;
 
     commands.spawn((
@@ -42,63 +127,123 @@ fn spawn_defeat_screen(
         }
     ));
 
-    // Spawn the Respawn button
+    let text = |value: String, font_size: f32, color: Color| TextBundle::from_section(value, TextStyle { font_size, color, ..default() });
+    let penalty = settings.death_penalty.skill_points_lost(player_stats.skill_points);
+
+    // the recap and the ways to carry on
     commands.spawn((
         DefeatScreenElement,
-        ButtonBundle {
+        NodeBundle {
             style: Style {
-                margin: UiRect::all(Val::Auto),
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
-                ..Default::default()
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.),
+                padding: UiRect::all(Val::Px(30.)),
+                ..default()
             },
-            ..Default::default()
-        }))
-        .with_children(|parent| {
-            parent.spawn((
-                DefeatScreenElement,
-                TextBundle {
-                text: Text::from_section(
-                    "Respawn",
-                    TextStyle {
-                        font_size: 30.0,
-                        color: Color::BLACK,
-                        ..Default::default()
+            background_color: BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+            ..default()
+        })
+        .with_children(|panel| {
+            panel.spawn((Localized::new("defeat-title"), text(String::new(), 44., Color::srgb(1., 0.44, 0.44))));
+            panel.spawn((Localized::new("defeat-slain-by").with_name("enemy", recap.killer), text(String::new(), 26., Color::WHITE)));
+            panel.spawn((Localized::new("defeat-last-moments"), text(String::new(), 20., Color::srgb(0.7, 0.7, 0.7))));
+            for entry in recap.last_moments.iter() {
+                panel.spawn(text(entry.describe(&locale), 20., Color::srgb(1., 1., 0.7)));
+            }
+            for (index, option) in DefeatOption::ALL.iter().enumerate() {
+                panel.spawn((
+                    DefeatButton { option: *option, index },
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(420.),
+                            padding: UiRect::axes(Val::Px(12.), Val::Px(6.)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: BackgroundColor(BUTTON_COLOR),
+                        ..default()
                     },
-                ),
-                ..Default::default()
-            }));
+                ))
+                .with_children(|button| {
+                    button.spawn((Localized::new(option.key()), text(String::new(), 28., Color::BLACK)));
+                });
+            }
+            panel.spawn((
+                Localized::new("defeat-penalty").with("points", penalty as i64),
+                text(String::new(), 20., Color::srgb(0.7, 0.7, 0.7)),
+            ));
         });
+    });
+}
+
+// Up/Down or hovering moves the focus, Confirm or a click picks; then the input is used up
+fn pick_defeat_option(
+    mut actions: ResMut<ActionState>,
+    mut focus: ResMut<DefeatFocus>,
+    mut pick: ResMut<DefeatPick>,
+    buttons: Query<(&DefeatButton, Ref<Interaction>)>,
+) {
+    let picked = pick_button(&mut focus.0, DefeatOption::ALL.len(), &actions, buttons.iter().map(|(button, interaction)| (button.index, interaction)));
+    if let Some(button) = picked.and_then(|index| buttons.iter().map(|(button, _)| button).find(|button| button.index == index)) {
+        pick.0 = Some(button.option);
+    }
+    actions.clear();
 }
 
-fn handle_respawn_button(
-    mut interaction_query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+fn apply_defeat_option(
+    mut pick: ResMut<DefeatPick>,
+    mut focus: ResMut<DefeatFocus>,
     mut state: ResMut<NextState<GameState>>,
-    mut player_stat_query: Query<&mut PlayerStats, With<Player>>,
-    mut player: Query<&mut Transform, With<Player>>,
-    mut commands: Commands,
+    mut new_run_events: EventWriter<NewRunEvent>,
+    settings: Res<Settings>,
+    mut player: Query<(&mut Transform, &mut PlayerStats, &Checkpoint), With<Player>>,
 ) {
-    for (interaction, mut color) in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Pressed => {
-                if let Ok(mut player_stats) = player_stat_query.get_single_mut() {
-                    player_stats.hp = player_stats.max_hp;
-                }
-                
-                if let Ok(mut transform) = player.get_single_mut() {
-                    transform.translation.x = 0.0;
-                    transform.translation.y = 0.0;
-                }
-                // Transition back to gameplay
-                state.set(GameState::InGame);
-            }
-            Interaction::Hovered => {
-                *color = BackgroundColor(Color::srgb(136.0, 136.0, 136.0));
-            }
-            Interaction::None => {
-                *color = BackgroundColor(Color::srgb(255.0, 112.0, 112.0));
+    let Some(option) = pick.0.take() else {
+        return;
+    };
+    focus.0 = 0;
+    match option {
+        // enemies and progress stay as they were, only the penalty is paid
+        DefeatOption::Respawn => {
+            if let Ok((mut transform, mut player_stats, checkpoint)) = player.get_single_mut() {
+                player_stats.hp = player_stats.max_hp;
+                player_stats.skill_points -= settings.death_penalty.skill_points_lost(player_stats.skill_points);
+                transform.translation.x = checkpoint.0.x;
+                transform.translation.y = checkpoint.0.y;
             }
+            state.set(GameState::InGame);
+        }
+        DefeatOption::Restart => {
+            new_run_events.send(NewRunEvent);
+            state.set(GameState::SkillTreeMenu);
         }
+        DefeatOption::Title => {
+            new_run_events.send(NewRunEvent);
+            state.set(GameState::Welcome);
+        }
+    }
+}
+
+fn style_defeat_screen(
+    focus: Res<DefeatFocus>,
+    mut buttons: Query<(&DefeatButton, &mut BackgroundColor)>,
+) {
+    for (button, mut background) in buttons.iter_mut() {
+        *background = BackgroundColor(if button.index == focus.0 { FOCUS_COLOR } else { BUTTON_COLOR });
     }
 }
 
@@ -106,4 +251,41 @@ fn despawn_defeat_screen(mut commands: Commands, query: Query<Entity, With<Defea
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle_log::Outcome;
+    use crate::boss::{BOSS_ETYPE, BOSS_NAME};
+
+    fn entry(actor: Actor, action: BattleAction, amount: u32) -> LogEntry {
+        LogEntry { battle: 1, actor, action, amount, outcome: Outcome::Hit }
+    }
+
+    #[test]
+    fn the_enemy_fought_gets_the_kill() {
+        let boss = EnemyStats::new(BOSS_ETYPE);
+        let entries = [entry(Actor::Enemy, BattleAction::Add, 2), entry(Actor::Enemy, BattleAction::Attack, 5)];
+        assert_eq!(killer(&entries, Some(&boss)), BOSS_NAME);
+        assert_eq!(killer(&entries, Some(&EnemyStats::new(1))), "enemy-stray");
+    }
+
+    #[test]
+    fn an_add_can_land_the_last_blow() {
+        let boss = EnemyStats::new(BOSS_ETYPE);
+        // the boss's miss and the player's own turn after the add don't count as blows
+        let entries = [
+            entry(Actor::Enemy, BattleAction::Attack, 5),
+            entry(Actor::Enemy, BattleAction::Add, 3),
+            entry(Actor::Enemy, BattleAction::Magic, 0),
+            entry(Actor::Player, BattleAction::Attack, 4),
+        ];
+        assert_eq!(killer(&entries, Some(&boss)), "enemy-flyder");
+    }
+
+    #[test]
+    fn an_unknown_killer_is_a_lurker() {
+        assert_eq!(killer(&[], None), "enemy-lurker");
+    }
+}
@@ -1,8 +1,13 @@
-use std::thread::spawn;
+use std::collections::HashSet;
 
-use bevy::{gizmos::grid, prelude::*};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
 use rand::prelude::*;
 use crate::enemy::spawn_enemy;
+use crate::shrine::spawn_shrine;
+use crate::events::{EnemyDefeatedEvent, RebuildDungeonEvent};
+use crate::settings::Settings;
+use crate::{WIN_W, WIN_H};
 const TILE_SIZE: u32 = 144;
 const DOOR_SIZE: u32 = 296;
 const GRID_WIDTH: usize = 8; // Width of the grid
@@ -20,6 +25,142 @@ pub struct Wall;
 #[derive(Component)]
 pub struct Door;
 
+// Everything spawned by the dungeon builder, so a rebuild (e.g. loading a save) can clear it
+#[derive(Component)]
+pub struct DungeonElement;
+
+// Seed for every random roll the dungeon builder makes; same seed = same dungeon
+#[derive(Resource)]
+pub struct DungeonSeed(pub u64);
+
+// Indices (into DungeonLayout::rooms) of rooms whose enemy has been defeated
+#[derive(Resource, Default)]
+pub struct ClearedRooms(pub HashSet<usize>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoomKind {
+    Start,
+    Battle,
+    Boss,
+    End,
+}
+
+#[derive(Clone, Debug)]
+pub struct RoomInfo {
+    pub kind: RoomKind,
+    pub origin: IVec2, // bottom left tile of the room
+    pub size: IVec2,
+    pub enemy: u32,    // enemy type placed in the room, 0 = none
+    pub shrine: bool,  // a shrine stands in the corner, out of the enemy's way
+}
+
+impl RoomInfo {
+    // tile the room's enemy is spawned on
+    pub fn enemy_tile(&self) -> IVec2 {
+        self.origin + IVec2::new(2, 2)
+    }
+
+    pub fn shrine_tile(&self) -> IVec2 {
+        self.origin + IVec2::new(4, 4)
+    }
+}
+
+// rooms are built in order from the start to the exit; every `spacing` rooms a battle room gets a shrine, 0 for none
+pub fn shrine_rooms(rooms: &[RoomInfo], spacing: usize) -> Vec<usize> {
+    if spacing == 0 {
+        return vec![];
+    }
+    rooms
+        .iter()
+        .enumerate()
+        .filter(|(index, room)| room.kind == RoomKind::Battle && index.is_multiple_of(spacing))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// Tile-level record of what create_dungeon placed, used by the minimap
+#[derive(Resource, Default)]
+pub struct DungeonLayout {
+    pub rooms: Vec<RoomInfo>,
+    pub floors: HashSet<IVec2>,
+    pub walls: HashSet<IVec2>,
+    pub door: Option<IVec2>,
+}
+
+impl DungeonLayout {
+    // smallest and largest tile coordinates that hold a floor or wall
+    pub fn tile_bounds(&self) -> (IVec2, IVec2) {
+        let mut min = IVec2::splat(i32::MAX);
+        let mut max = IVec2::splat(i32::MIN);
+        for tile in self.floors.iter().chain(self.walls.iter()) {
+            min = min.min(*tile);
+            max = max.max(*tile);
+        }
+        if min.x > max.x {
+            return (IVec2::ZERO, IVec2::ZERO);
+        }
+        (min, max)
+    }
+
+    // world-space rectangle covered by every floor and wall tile
+    pub fn world_bounds(&self) -> LevelBounds {
+        let (min, max) = self.tile_bounds();
+        LevelBounds {
+            min: tile_to_world(min) - Vec2::splat(TILE_SIZE as f32 / 2.),
+            max: tile_to_world(max) + Vec2::splat(TILE_SIZE as f32 / 2.),
+        }
+    }
+}
+
+// Real extent of the generated dungeon in world units, replaces the old LEVEL_W/LEVEL_H constants
+#[derive(Resource, Default, Clone, Copy)]
+pub struct LevelBounds {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl LevelBounds {
+    // center for a view of the given size so it never shows anything outside the level
+    pub fn clamp_view(&self, position: Vec3, view: Vec2) -> Vec2 {
+        let low = self.min + view / 2.;
+        let high = self.max - view / 2.;
+        // a level smaller than the view just gets centered
+        let center = (self.min + self.max) / 2.;
+        Vec2::new(
+            if low.x > high.x { center.x } else { position.x.clamp(low.x, high.x) },
+            if low.y > high.y { center.y } else { position.y.clamp(low.y, high.y) },
+        )
+    }
+
+    // where a window-sized screen around `position` sits; the camera, battle, defeat and skill tree screens all use this
+    pub fn screen_center(&self, position: Vec3) -> Vec2 {
+        self.clamp_view(position, Vec2::new(WIN_W, WIN_H))
+    }
+
+    // keeps a tile-sized body (the player) inside the level
+    pub fn contains(&self, position: Vec3, size: f32) -> bool {
+        position.x >= self.min.x + size / 2.
+            && position.x <= self.max.x - size / 2.
+            && position.y >= self.min.y + size / 2.
+            && position.y <= self.max.y - size / 2.
+    }
+}
+
+// tiles are centered on (i * TILE_SIZE - TILE_SIZE / 2), so tile i covers [(i - 1) * TILE_SIZE, i * TILE_SIZE)
+pub fn world_to_tile(position: Vec3) -> IVec2 {
+    IVec2::new(
+        (position.x / TILE_SIZE as f32).floor() as i32 + 1,
+        (position.y / TILE_SIZE as f32).floor() as i32 + 1,
+    )
+}
+
+pub fn tile_to_world(tile: IVec2) -> Vec2 {
+    Vec2::new(
+        tile.x as f32 * TILE_SIZE as f32 - TILE_SIZE as f32 / 2.0,
+        tile.y as f32 * TILE_SIZE as f32 - TILE_SIZE as f32 / 2.0,
+    )
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Cell {
     Wall,
@@ -53,14 +194,20 @@ impl GridCell {
 }
 
 #[derive(Resource)]
-struct MazeGrid {
+pub struct MazeGrid {
     grid: Vec<Vec<GridCell>>,
+    pub origin: IVec2, // bottom left tile of the spawned maze
+    pub size: IVec2,   // size of the spawned maze in tiles (after blueprint_to_grid and double_grid)
 }
 
 impl MazeGrid {
     fn new(rows: usize, cols: usize) -> Self {
         let grid = create_grid(rows, cols);
-        MazeGrid { grid }
+        MazeGrid { grid, origin: IVec2::ZERO, size: IVec2::ZERO }
+    }
+
+    pub fn center_tile(&self) -> IVec2 {
+        self.origin + self.size / 2
     }
 }
 
@@ -69,18 +216,77 @@ pub struct DungeonPlugin;
 impl Plugin for DungeonPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(MazeGrid::new(GRID_WIDTH, GRID_HEIGHT)) // Add the grid as a resource
-            .add_systems(Startup, create_dungeon);
+            .insert_resource(DungeonSeed(random()))
+            .init_resource::<DungeonLayout>()
+            .init_resource::<ClearedRooms>()
+            .init_resource::<LevelBounds>()
+            .add_systems(Startup, (create_dungeon, spawn_shrines).chain())
+            .add_systems(Update, record_cleared_room)
+            .add_systems(Update, rebuild_dungeon.after(record_cleared_room))
+            .add_systems(Update, spawn_shrines.after(rebuild_dungeon).run_if(on_event::<RebuildDungeonEvent>()));
+    }
+}
+
+// everything building the dungeon reads or writes, shared by the first build and rebuilds
+#[derive(SystemParam)]
+struct DungeonBuilder<'w, 's> {
+    commands: Commands<'w, 's>,
+    asset_server: Res<'w, AssetServer>,
+    texture_atlases: ResMut<'w, Assets<TextureAtlasLayout>>,
+    maze_grid: ResMut<'w, MazeGrid>,
+    layout: ResMut<'w, DungeonLayout>,
+    bounds: ResMut<'w, LevelBounds>,
+    seed: Res<'w, DungeonSeed>,
+    cleared: Res<'w, ClearedRooms>,
+}
+
+fn create_dungeon(mut builder: DungeonBuilder){
+    build_dungeon(&mut builder);
+}
+
+// clears the current dungeon and builds it again from DungeonSeed (e.g. after a save was loaded)
+fn rebuild_dungeon(
+    mut builder: DungeonBuilder,
+    mut rebuild_events: EventReader<RebuildDungeonEvent>,
+    elements: Query<Entity, With<DungeonElement>>,
+){
+    if rebuild_events.read().count() == 0 {
+        return;
     }
+    for entity in elements.iter() {
+        builder.commands.entity(entity).despawn_recursive();
+    }
+    *builder.maze_grid = MazeGrid::new(GRID_WIDTH, GRID_HEIGHT);
+    build_dungeon(&mut builder);
 }
 
-fn create_dungeon( //main function that calls all other spawining functions
+// shrines go on top of rooms that are already built, so the rooms and their enemies don't depend on the spacing
+fn spawn_shrines(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-    mut maze_grid: ResMut<MazeGrid>,
+    mut layout: ResMut<DungeonLayout>,
+    settings: Res<Settings>,
+){
+    for index in shrine_rooms(&layout.rooms, settings.shrine_spacing) {
+        let room = &mut layout.rooms[index];
+        room.shrine = true;
+        let shrine_entity = spawn_shrine(&mut commands, tile_to_world(room.shrine_tile()).extend(1.), index);
+        commands.entity(shrine_entity).insert(DungeonElement);
+    }
+}
 
+fn record_cleared_room(
+    mut cleared: ResMut<ClearedRooms>,
+    mut defeated_events: EventReader<EnemyDefeatedEvent>,
 ){
-    commands.spawn((Camera2dBundle::default(),));
+    for event in defeated_events.read() {
+        cleared.0.insert(event.room);
+    }
+}
+
+fn build_dungeon(builder: &mut DungeonBuilder){ //main function that calls all other spawining functions
+    let DungeonBuilder { commands, asset_server, texture_atlases, maze_grid, layout, bounds, seed, cleared } = builder;
+    let mut rng = StdRng::seed_from_u64(seed.0);
+    **layout = DungeonLayout::default();
 
     //starting room
     let room1_start_position = Vec3::new(
@@ -89,12 +295,18 @@ fn create_dungeon( //main function that calls all other spawining functions
         0.0,
     );
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        3,
-        room1_start_position,
-        0,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::Start,
+            door_position: 3,
+            start_position: room1_start_position,
+            enemy: 0,
+        },
     );
 
     //battle rooms
@@ -103,14 +315,20 @@ fn create_dungeon( //main function that calls all other spawining functions
         15.0 * TILE_SIZE as f32 - TILE_SIZE as f32/2.0, 
         0.0,
     );
-    let rand2: usize = random();
+    let rand2: usize = rng.gen();
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        4,
-        room2_start_position,
-        (rand2%2 +1) as u32,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::Battle,
+            door_position: 4,
+            start_position: room2_start_position,
+            enemy: (rand2%2 +1) as u32,
+        },
     );
 
     let room3_start_position = Vec3::new(
@@ -118,14 +336,20 @@ fn create_dungeon( //main function that calls all other spawining functions
         15.0 * TILE_SIZE as f32 - TILE_SIZE as f32/2.0, 
         0.0,
     );
-    let rand3: usize = random();
+    let rand3: usize = rng.gen();
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        4,
-        room3_start_position,
-        (rand3%2 +1) as u32,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::Battle,
+            door_position: 4,
+            start_position: room3_start_position,
+            enemy: (rand3%2 +1) as u32,
+        },
     );
 
     let room4_start_position = Vec3::new(
@@ -133,14 +357,20 @@ fn create_dungeon( //main function that calls all other spawining functions
         15.0 * TILE_SIZE as f32 - TILE_SIZE as f32/2.0, 
         0.0,
     );
-    let rand4: usize = random();
+    let rand4: usize = rng.gen();
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        4,
-        room4_start_position,
-        (rand4%2 +1) as u32,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::Battle,
+            door_position: 4,
+            start_position: room4_start_position,
+            enemy: (rand4%2 +1) as u32,
+        },
     );
 
     let room5_start_position = Vec3::new(
@@ -148,14 +378,20 @@ fn create_dungeon( //main function that calls all other spawining functions
         -23.0 * TILE_SIZE as f32 - TILE_SIZE as f32/2.0, 
         0.0,
     );
-    let rand5: usize = random();
+    let rand5: usize = rng.gen();
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        2,
-        room5_start_position,
-        (rand5%2 +1) as u32,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::Battle,
+            door_position: 2,
+            start_position: room5_start_position,
+            enemy: (rand5%2 +1) as u32,
+        },
     );
 
     let room6_start_position = Vec3::new(
@@ -163,14 +399,20 @@ fn create_dungeon( //main function that calls all other spawining functions
         -23.0 * TILE_SIZE as f32 - TILE_SIZE as f32/2.0, 
         0.0,
     );
-    let rand6: usize = random();
+    let rand6: usize = rng.gen();
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        2,
-        room6_start_position,
-        (rand6%2 +1) as u32,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::Battle,
+            door_position: 2,
+            start_position: room6_start_position,
+            enemy: (rand6%2 +1) as u32,
+        },
     );
 
     let room7_start_position = Vec3::new(
@@ -178,14 +420,20 @@ fn create_dungeon( //main function that calls all other spawining functions
         -23.0 * TILE_SIZE as f32 - TILE_SIZE as f32/2.0, 
         0.0,
     );
-    let rand7: usize = random();
+    let rand7: usize = rng.gen();
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        2,
-        room7_start_position,
-        (rand7%2 +1) as u32,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::Battle,
+            door_position: 2,
+            start_position: room7_start_position,
+            enemy: (rand7%2 +1) as u32,
+        },
     );
 
     let room8_start_position = Vec3::new(
@@ -193,14 +441,20 @@ fn create_dungeon( //main function that calls all other spawining functions
         2.0 * TILE_SIZE as f32 - TILE_SIZE as f32/2.0, 
         0.0,
     );
-    let rand8: usize = random();
+    let rand8: usize = rng.gen();
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        1,
-        room8_start_position,
-        (rand8%2 +1) as u32,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::Battle,
+            door_position: 1,
+            start_position: room8_start_position,
+            enemy: (rand8%2 +1) as u32,
+        },
     );
     //boss room
     let room9_start_position = Vec3::new(
@@ -209,12 +463,18 @@ fn create_dungeon( //main function that calls all other spawining functions
         0.0,
     );
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        5,
-        room9_start_position,
-        3, //CHANGE TO BOSS TYPE
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::Boss,
+            door_position: 5,
+            start_position: room9_start_position,
+            enemy: 3, //CHANGE TO BOSS TYPE
+        },
     );
     //end room
     let room10_start_position = Vec3::new(
@@ -223,12 +483,18 @@ fn create_dungeon( //main function that calls all other spawining functions
         0.0,
     );
     spawn_room(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
-        1,
-        room10_start_position,
-        0,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
+        cleared,
+        RoomSpec {
+            kind: RoomKind::End,
+            door_position: 1,
+            start_position: room10_start_position,
+            enemy: 0,
+        },
     );
 
     let hallway1_start_position = Vec3::new(
@@ -238,9 +504,11 @@ fn create_dungeon( //main function that calls all other spawining functions
     );
     //hallways
     spawn_hallway(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
         hallway1_start_position,
     );
 
@@ -250,9 +518,11 @@ fn create_dungeon( //main function that calls all other spawining functions
         0.0,
     );
     spawn_hallway(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlases,
+        commands,
+        asset_server,
+        texture_atlases,
+        layout,
+        &mut rng,
         hallway2_start_position,
     );
     //maze
@@ -267,8 +537,11 @@ fn create_dungeon( //main function that calls all other spawining functions
         (6.0 * TILE_SIZE as f32) / 2.0, 
         10.0,
     );
-    spawn_door(&mut commands, &asset_server, &mut texture_atlases, final_room_center);
-    generate_maze(maze_grid, commands, asset_server, texture_atlases, maze1_start_position);
+    spawn_door(commands, asset_server, texture_atlases, layout, final_room_center);
+    generate_maze(maze_grid, commands, asset_server, texture_atlases, layout, &mut rng, maze1_start_position);
+
+    // publish where the rooms actually ended up so the camera and screens can clamp to it
+    **bounds = layout.world_bounds();
 
 
   
@@ -276,15 +549,33 @@ fn create_dungeon( //main function that calls all other spawining functions
         
 }
 
-fn spawn_room(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>, 
+// what build_dungeon asks spawn_room for
+struct RoomSpec {
+    kind: RoomKind,
     door_position: usize, //1 = left door, 2 = top door, 3 = right door, 4 = bottom door
     start_position: Vec3,
     enemy: u32,
+}
 
+fn spawn_room(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>, 
+    layout: &mut DungeonLayout,
+    rng: &mut StdRng,
+    cleared: &ClearedRooms,
+    room: RoomSpec,
 ){
+    let RoomSpec { kind, door_position, start_position, enemy } = room;
+    let room_index = layout.rooms.len();
+    layout.rooms.push(RoomInfo {
+        kind,
+        origin: world_to_tile(start_position),
+        size: IVec2::new(6, 6),
+        enemy,
+        shrine: false,
+    });
+
     let tile_sheet_handle: Handle<Image> = asset_server.load("mossTiles.png");
     let tile_layout = TextureAtlasLayout::from_grid(UVec2::splat(TILE_SIZE), 2, 2, None, None);
     let tile_layout_len = tile_layout.textures.len();
@@ -345,10 +636,12 @@ fn spawn_room(
                        },
                        Wall,
                    ))
-                   .insert(Background);
+                   .insert(Background)
+                   .insert(DungeonElement);
+               layout.walls.insert(world_to_tile(t));
            } else {
                // add regular tile
-               let rand: usize = random();
+               let rand: usize = rng.gen();
                commands
                    .spawn((
                        SpriteBundle {
@@ -365,7 +658,9 @@ fn spawn_room(
                        },
                        Tile,
                    ))
-                   .insert(Background);
+                   .insert(Background)
+                   .insert(DungeonElement);
+               layout.floors.insert(world_to_tile(t));
            }
 
            i += 1;
@@ -374,13 +669,13 @@ fn spawn_room(
        
    }
    ////// spawning enemy at a point in room ////// 
-   if(enemy != 0){
-    let new_rand: usize = random();
+   if(enemy != 0 && !cleared.0.contains(&room_index)){
     let random_x = start_position.x + 2.0 * TILE_SIZE as f32;
     let random_y = start_position.y + 2.0 * TILE_SIZE as f32;
     
     let enemy_position = Vec3::new(random_x, random_y, 1.0);
-    spawn_enemy(commands, asset_server, texture_atlases, enemy_position, enemy);
+    let enemy_entity = spawn_enemy(commands, asset_server, texture_atlases, enemy_position, enemy, room_index);
+    commands.entity(enemy_entity).insert(DungeonElement);
    }
    
 
@@ -391,6 +686,8 @@ fn spawn_hallway(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
+    layout: &mut DungeonLayout,
+    rng: &mut StdRng,
     start_position: Vec3,
 ) -> Vec3 {
     const HALLWAY_ROWS: usize = 4; // ttal rows, including the walls
@@ -427,10 +724,12 @@ fn spawn_hallway(
                         layout: wall_layout_handle.clone(),
                     },
                     Wall,
+                    DungeonElement,
                 ));
+                layout.walls.insert(world_to_tile(t));
             } else {
                 // inner rows are tiles
-                let rand: usize = random();
+                let rand: usize = rng.gen();
                 commands.spawn((
                     SpriteBundle {
                         texture: tile_sheet_handle.clone(),
@@ -445,7 +744,9 @@ fn spawn_hallway(
                         layout: tile_layout_handle.clone(),
                     },
                     Tile,
+                    DungeonElement,
                 ));
+                layout.floors.insert(world_to_tile(t));
             }
             t += Vec3::new(TILE_SIZE as f32, 0.0, 0.0); // move to the right for the next tile/wall
         }
@@ -495,8 +796,7 @@ fn in_UST(grid: &Vec<Vec<GridCell>>, row: usize, col: usize) -> bool {
 }
 
 // Return a randomly selected cell that's unvisited (Not in UST)
-fn get_random_unvisited_cell(grid: &Vec<Vec<GridCell>>) -> Option<(usize, usize)> {
-    let mut rng = rand::thread_rng();
+fn get_random_unvisited_cell(grid: &Vec<Vec<GridCell>>, rng: &mut StdRng) -> Option<(usize, usize)> {
     let mut unvisited_cells: Vec<(usize, usize)> = Vec::new();
 
     // Collect all unvisited cells
@@ -542,8 +842,7 @@ fn mark_with_direction(
 }
 
 // Function to randomly pick one of the four directions
-fn random_direction() -> Direction {
-    let mut rng = rand::thread_rng();
+fn random_direction(rng: &mut StdRng) -> Direction {
     let direction_index: usize = rng.gen_range(0..4); // Generate a random index (0 to 3)
 
     match direction_index {
@@ -561,19 +860,19 @@ fn is_within_bounds(grid: &Vec<Vec<GridCell>>, row: usize, col: usize) -> bool {
 }
 
 // Create a path from a given cell that connects to UST
-fn create_path(grid: &mut Vec<Vec<GridCell>>, row: usize, col: usize) {
+fn create_path(grid: &mut Vec<Vec<GridCell>>, rng: &mut StdRng, row: usize, col: usize) {
     let mut current_row = row;
     let mut current_col = col;
 
     // If the current cell is part of the UST, stop processing
-    if in_UST(&grid, row, col) {
+    if in_UST(grid, row, col) {
         //println!("Found a path at ({}, {})", current_row, current_col);
         return; // Stop if we find a Tile
     }
 
     // Assuming `current_row` and `current_col` are the current cell coordinates
 
-    let mut direction = random_direction(); // Start with a random direction
+    let mut direction = random_direction(rng); // Start with a random direction
 
     loop {
         // Get the new cell coordinates based on the current position and direction
@@ -585,13 +884,13 @@ fn create_path(grid: &mut Vec<Vec<GridCell>>, row: usize, col: usize) {
             mark_with_direction(grid, current_row, current_col, direction);
 
             // Recursively move to the new cell
-            create_path(grid, new_row, new_col);
+            create_path(grid, rng, new_row, new_col);
 
             add_to_UST(grid, current_row, current_col); // Add current cell to UST
             break;
         } else {
             // If out of bounds, pick a new random direction and try again
-            direction = random_direction();
+            direction = random_direction(rng);
         }
     }
 }
@@ -621,23 +920,24 @@ fn print_grid(grid: &Vec<Vec<GridCell>>) {
 
 
 fn generate_maze(
-    mut maze_grid: ResMut<MazeGrid>,  
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
+    maze_grid: &mut MazeGrid,  
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
+    layout: &mut DungeonLayout,
+    rng: &mut StdRng,
     start_position: Vec3,
 ) {
     /////////////////////  Generate a maze blueprint using Wilson's Algo /////////////////////
 
-    let mut grid = &mut maze_grid.grid; // Use the grid from the resource
+    let grid = &mut maze_grid.grid; // Use the grid from the resource
 
     // Randomly select a cell
-    let mut rng = rand::thread_rng();
     let random_row = rng.gen_range(0..GRID_HEIGHT);
     let random_col = rng.gen_range(0..GRID_WIDTH);
 
     // Mark the randomly selected cell as visited (mutable borrow)
-    add_to_UST(&mut grid, random_row, random_col);
+    add_to_UST(grid, random_row, random_col);
     grid[random_row][random_col].cell_type = Cell::Tile; // Mark it as a Tile
 
     // Now that the cell is marked, we can access it immutably
@@ -647,7 +947,7 @@ fn generate_maze(
     // );
 
     // Continue finding and visiting random unvisited cells until all cells are visited
-    while let Some((row, col)) = get_random_unvisited_cell(&grid) {
+    while let Some((row, col)) = get_random_unvisited_cell(grid, rng) {
         //println!("Randomly selected new unvisited cell at ({}, {})", row, col);
 
         let cell = &grid[row][col]; // Access the cell in the grid
@@ -656,22 +956,24 @@ fn generate_maze(
             //println!("The selected cell is a Tile at ({}, {})", row, col);
             continue;
         } else {
-            if is_within_bounds(&mut grid, row, col) {
+            if is_within_bounds(grid, row, col) {
                 // Only execute this block if the cell is a Wall
                 //println!("The selected cell is a Wall at ({}, {})", row, col);
 
                 // Call `create_path` to explore from this unvisited cell
-                create_path(&mut grid, row, col);
+                create_path(grid, rng, row, col);
             }
         }
     }
     //print_grid(&grid);
 
-    let actual_grid = blueprint_to_grid(&mut commands, &asset_server, &mut texture_atlases, &grid);
-    let doubled_grid = double_grid(&mut commands, &asset_server, &mut texture_atlases, &actual_grid);
+    let actual_grid = blueprint_to_grid(commands, asset_server, texture_atlases, grid);
+    let doubled_grid = double_grid(commands, asset_server, texture_atlases, &actual_grid);
    // print_grid(&doubled_grid);
     
-    spawn_maze(&mut commands, &asset_server, &mut texture_atlases, &doubled_grid, start_position);
+    maze_grid.origin = world_to_tile(start_position);
+    maze_grid.size = IVec2::new(doubled_grid[0].len() as i32, doubled_grid.len() as i32);
+    spawn_maze(commands, asset_server, texture_atlases, layout, rng, &doubled_grid, start_position);
 }
 
 // function that takes maze blueprint as input and returns a spawned maze as output
@@ -679,6 +981,8 @@ fn spawn_maze(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
+    layout: &mut DungeonLayout,
+    rng: &mut StdRng,
     grid: &Vec<Vec<GridCell>>,
     start_position: Vec3,
 ) {
@@ -737,9 +1041,11 @@ fn spawn_maze(
                         },
                         Wall,
                     ))
-                    .insert(Background);
+                    .insert(Background)
+                    .insert(DungeonElement);
+                layout.walls.insert(world_to_tile(t));
             } else {
-                let rand: usize = random();
+                let rand: usize = rng.gen();
                 commands
                     .spawn((
                         SpriteBundle {
@@ -756,7 +1062,9 @@ fn spawn_maze(
                         },
                         Tile,
                     ))
-                    .insert(Background);
+                    .insert(Background)
+                    .insert(DungeonElement);
+                layout.floors.insert(world_to_tile(t));
             }
             
             t += Vec3::new(TILE_SIZE as f32, 0., 0.);
@@ -880,8 +1188,10 @@ fn spawn_door(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
+    layout: &mut DungeonLayout,
     position: Vec3,
 ){
+    layout.door = Some(world_to_tile(position));
     // load textures and create texture atlases
     let door_texture_handle = asset_server.load("enddoor.png");
     let door_layout = TextureAtlasLayout::from_grid(UVec2::splat(DOOR_SIZE), 1, 1, None, None);
@@ -900,6 +1210,39 @@ fn spawn_door(
             index: 0, 
             layout: door_layout_handle.clone(),
         },
-        Door
+        Door,
+        DungeonElement,
     ));
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rooms(kinds: &[RoomKind]) -> Vec<RoomInfo> {
+        kinds
+            .iter()
+            .enumerate()
+            .map(|(i, kind)| RoomInfo { kind: *kind, origin: IVec2::new(i as i32 * 10, 0), size: IVec2::new(6, 6), enemy: 1, shrine: false })
+            .collect()
+    }
+
+    #[test]
+    fn shrines_go_in_every_few_battle_rooms() {
+        let layout = rooms(&[RoomKind::Start, RoomKind::Battle, RoomKind::Battle, RoomKind::Battle, RoomKind::Battle, RoomKind::Battle, RoomKind::Battle, RoomKind::Boss, RoomKind::End]);
+        assert_eq!(shrine_rooms(&layout, 3), vec![3, 6]);
+        assert_eq!(shrine_rooms(&layout, 2), vec![2, 4, 6]);
+        // the start, boss and exit never get one
+        assert_eq!(shrine_rooms(&layout, 1), vec![1, 2, 3, 4, 5, 6]);
+        assert!(shrine_rooms(&layout, 0).is_empty());
+    }
+
+    #[test]
+    fn a_shrine_stays_out_of_the_enemys_way() {
+        let room = &rooms(&[RoomKind::Battle])[0];
+        // inside the walls
+        let inside = |tile: IVec2| (tile - room.origin).cmpge(IVec2::ONE).all() && (tile - room.origin).cmplt(room.size - 1).all();
+        assert!(inside(room.shrine_tile()));
+        assert_ne!(room.shrine_tile(), room.enemy_tile());
+        assert!(room.shrine_tile().as_vec2().distance(room.enemy_tile().as_vec2()) > 1.);
+    }
+}
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::GameState;
+use crate::player::{Player, PlayerStats};
+use crate::controls::{Action, ActionState};
+use crate::locale::Locale;
+
+const WEAK: f32 = 2.;       // damage multiplier against an archetype weak to the element
+const RESIST: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Element {
+    Fire,
+    Ice,
+    Lightning,
+    Holy,
+    Shadow,
+}
+
+impl Element {
+    pub const ALL: [Element; 5] = [Element::Fire, Element::Ice, Element::Lightning, Element::Holy, Element::Shadow];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Element::Fire => "element-fire",
+            Element::Ice => "element-ice",
+            Element::Lightning => "element-lightning",
+            Element::Holy => "element-holy",
+            Element::Shadow => "element-shadow",
+        }
+    }
+}
+
+// skill tree nodes that teach a spell on top of their stat bonus
+const SPELL_NODES: [(u32, Element); 5] = [
+    (2, Element::Fire),
+    (8, Element::Ice),
+    (15, Element::Lightning),
+    (16, Element::Holy),
+    (17, Element::Shadow),
+];
+
+pub fn spell_for_node(index: u32) -> Option<Element> {
+    SPELL_NODES.iter().find(|(node, _)| *node == index).map(|(_, element)| *element)
+}
+
+// weaknesses and resistances of each enemy archetype, the same etypes EnemyStats::new uses
+pub fn affinity(etype: u32, element: Element) -> f32 {
+    match etype {
+        2 => match element {
+            Element::Lightning | Element::Holy => WEAK,
+            Element::Shadow => RESIST,
+            _ => 1.,
+        },
+        // the boss
+        3 => match element {
+            Element::Holy => WEAK,
+            Element::Fire | Element::Shadow => RESIST,
+            _ => 1.,
+        },
+        _ => match element {
+            Element::Fire => WEAK,
+            Element::Ice => RESIST,
+            _ => 1.,
+        },
+    }
+}
+
+// the spell the Magic key casts, plain magic when none is picked
+#[derive(Resource, Default)]
+pub struct SelectedSpell(pub Option<Element>);
+
+#[derive(Component)]
+struct SpellText;
+
+pub struct ElementPlugin;
+
+impl Plugin for ElementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedSpell>();
+        app.add_systems(Startup, setup_spell_text);
+        app.add_systems(OnEnter(GameState::BattleMode), show_spell_text);
+        app.add_systems(OnExit(GameState::BattleMode), hide_spell_text);
+        app.add_systems(Update, cycle_spell.run_if(in_state(GameState::BattleMode)));
+    }
+}
+
+fn setup_spell_text(mut commands: Commands) {
+    commands.spawn((
+        SpellText,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 24.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(30.),
+            bottom: Val::Px(30.),
+            ..default()
+        }),
+        Visibility::Hidden,
+    ));
+}
+
+fn show_spell_text(mut text: Query<&mut Visibility, With<SpellText>>) {
+    for mut visibility in text.iter_mut() {
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_spell_text(mut text: Query<&mut Visibility, With<SpellText>>) {
+    for mut visibility in text.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// E steps through plain magic and every spell the player has learned
+fn cycle_spell(
+    actions: Res<ActionState>,
+    mut selected: ResMut<SelectedSpell>,
+    player: Query<&PlayerStats, With<Player>>,
+    locale: Res<Locale>,
+    mut text: Query<&mut Text, With<SpellText>>,
+) {
+    let Ok(player_stats) = player.get_single() else {
+        return;
+    };
+    // a spell from another run is not known any more
+    if let Some(element) = selected.0 {
+        if !player_stats.spells.contains(&element) {
+            selected.0 = None;
+        }
+    }
+    if actions.just_pressed(Action::CycleSpell) && !player_stats.spells.is_empty() {
+        selected.0 = match selected.0 {
+            None => player_stats.spells.first().copied(),
+            Some(element) => {
+                let i = player_stats.spells.iter().position(|spell| *spell == element).unwrap_or(0);
+                player_stats.spells.get(i + 1).copied()
+            }
+        };
+    }
+
+    let label = if player_stats.spells.is_empty() {
+        String::new()
+    } else {
+        let spell = locale.text(selected.0.map(|element| element.key()).unwrap_or("spell-plain"));
+        locale.format("spell-selected", &[("spell", spell.into())])
+    };
+    for mut text in text.iter_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_archetype_has_one_weakness_and_some_resistance() {
+        for etype in 0..=3 {
+            let multipliers: Vec<f32> = Element::ALL.iter().map(|element| affinity(etype, *element)).collect();
+            assert_eq!(multipliers.iter().filter(|m| **m == WEAK).count(), if etype == 2 { 2 } else { 1 }, "etype {}", etype);
+            assert!(multipliers.contains(&RESIST), "etype {}", etype);
+        }
+    }
+
+    #[test]
+    fn the_boss_fears_holy_and_shrugs_off_fire() {
+        assert_eq!(affinity(3, Element::Holy), WEAK);
+        assert_eq!(affinity(3, Element::Fire), RESIST);
+        assert_eq!(affinity(3, Element::Ice), 1.);
+    }
+
+    #[test]
+    fn spells_come_from_their_nodes() {
+        assert_eq!(spell_for_node(2), Some(Element::Fire));
+        assert_eq!(spell_for_node(17), Some(Element::Shadow));
+        assert_eq!(spell_for_node(3), None);
+    }
+}
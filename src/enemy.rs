@@ -1,6 +1,11 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use crate::GameState;
 use crate::player::Player;
+use crate::brain::{Brain, brain_for};
+use crate::utility::UtilityConfig;
+use crate::settings::Settings;
+use crate::boss::BOSS_NAME;
 
 const TILE_SIZE: u32 = 144;
 const ENEMY_SIZE: u32 = 144;
@@ -12,9 +17,10 @@ pub struct Enemy{
     direction: i32,
     left_boundary: f32, 
     right_boundary: f32,
+    pub room: usize,    // index of the room the enemy was placed in
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct EnemyStats {
     pub physatk: u32,
     pub physdef: u32,
@@ -25,6 +31,14 @@ pub struct EnemyStats {
     pub hp: u32,
     pub etype: u32,
     pub next_action_tick: u32,
+    #[serde(default)]
+    pub phase: u32,     // boss phase reached, 0 until its hp first drops past a threshold
+    #[serde(default)]
+    pub adds: u32,      // adds the boss has summoned into the fight
+    #[serde(default)]
+    pub guarding: bool,
+    #[serde(default)]
+    pub focus: u32,
 }
 
 impl EnemyStats {
@@ -40,6 +54,10 @@ impl EnemyStats {
                 hp: 25,
                 etype,
                 next_action_tick: 0,
+                phase: 0,
+                adds: 0,
+                guarding: false,
+                focus: 0,
             },
             2 => Self {
                 physatk: 2,
@@ -51,6 +69,10 @@ impl EnemyStats {
                 hp: 35,
                 etype,
                 next_action_tick: 0,
+                phase: 0,
+                adds: 0,
+                guarding: false,
+                focus: 0,
             },
             3 => Self {
                 // Boss stats
@@ -63,6 +85,10 @@ impl EnemyStats {
                 hp: 50,
                 etype,
                 next_action_tick: 0,
+                phase: 0,
+                adds: 0,
+                guarding: false,
+                focus: 0,
             },
             _ => Self {
                 physatk: 1,
@@ -74,10 +100,24 @@ impl EnemyStats {
                 hp: 25,
                 etype,
                 next_action_tick: 0,
+                phase: 0,
+                adds: 0,
+                guarding: false,
+                focus: 0,
             },
         }
     }
 
+    // message key of the archetype's name
+    pub fn name_key(&self) -> &'static str {
+        match self.etype {
+            1 => "enemy-stray",
+            2 => "enemy-hunter",
+            3 => BOSS_NAME,
+            _ => "enemy-lurker",
+        }
+    }
+
     pub fn sprite_path(&self) -> &'static str {
         match self.etype {
             1 => "enemyPlaceHolder.png",
@@ -92,6 +132,8 @@ pub struct EnemyPlugin;
     
 impl Plugin for EnemyPlugin{
     fn build(&self, app: &mut App){
+        app.insert_resource(UtilityConfig::load());
+        app.add_systems(PreUpdate, give_brains);
         app.add_systems(Update, enemy_pace.run_if(in_state(GameState::InGame)));
     }
 }
@@ -102,7 +144,8 @@ pub fn spawn_enemy(
     texture_atlases: &mut ResMut<Assets<TextureAtlasLayout>>,
     position: Vec3,
     etype: u32,
-) {
+    room: usize,
+) -> Entity {
     let enemy_stats = EnemyStats::new(etype);
     // load textures and create texture atlases
     let enemy_texture_handle = asset_server.load(enemy_stats.sprite_path());
@@ -129,17 +172,34 @@ pub fn spawn_enemy(
             direction: 1,
             left_boundary,
             right_boundary,
+            room,
         },
         enemy_stats,
-    ));
+    )).id()
+}
+
+// enemies that have not been given a brain yet
+type NewEnemies = (With<Enemy>, Without<Brain>);
+
+// enemies spawned last frame get the brain for their type, all sharing the config loaded at startup
+fn give_brains(
+    mut commands: Commands,
+    config: Res<UtilityConfig>,
+    enemies: Query<(Entity, &EnemyStats), NewEnemies>,
+) {
+    for (entity, enemy_stats) in enemies.iter() {
+        commands.entity(entity).insert(Brain(brain_for(enemy_stats.etype, &config)));
+    }
 }
 
 fn enemy_pace(
     time: Res<Time>,
+    settings: Res<Settings>,
     mut query: Query<(&mut Transform, &mut Enemy)>,
 ) {
+    let speed = ENEMY_SPEED * settings.enemy_movement.factor();
     for (mut transform, mut enemy) in query.iter_mut() {
-        transform.translation.x += enemy.direction as f32 * ENEMY_SPEED * time.delta_seconds();
+        transform.translation.x += enemy.direction as f32 * speed * time.delta_seconds();
         //turn if needed
         if transform.translation.x > enemy.right_boundary {
             enemy.direction = -1;
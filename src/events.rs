@@ -1,4 +1,6 @@
 use bevy::prelude::*;
+use crate::battle::PlayerAction;
+use crate::battle_log::LogEntry;
 
 // collision event
 #[derive(Event)]
@@ -6,4 +8,44 @@ pub struct EnemyCollisionEvent;
 
 // end game event
 #[derive(Event)]
-pub struct EndGameEvent;
\ No newline at end of file
+pub struct EndGameEvent;
+// enemy defeated in battle; room is the enemy's index in DungeonLayout::rooms
+#[derive(Event)]
+pub struct EnemyDefeatedEvent {
+    pub room: usize,
+}
+
+// tear down and respawn the dungeon from DungeonSeed
+#[derive(Event)]
+pub struct RebuildDungeonEvent;
+
+// save/load the game to/from the save file
+#[derive(Event)]
+pub struct SaveGameEvent;
+
+// sent by write_save once it has tried, saved is false if nothing was written
+#[derive(Event)]
+pub struct GameSavedEvent {
+    pub saved: bool,
+}
+
+#[derive(Event)]
+pub struct LoadGameEvent;
+
+// throw the current run away and start over with a new dungeon and a fresh character
+#[derive(Event)]
+pub struct NewRunEvent;
+
+// shake the camera; trauma is 0..1 and adds up until it decays
+#[derive(Event)]
+pub struct CameraShakeEvent {
+    pub trauma: f32,
+}
+
+// the player picked an action in battle, from the keyboard or a replay
+#[derive(Event, Clone, Copy)]
+pub struct PlayerActionEvent(pub PlayerAction);
+
+// something happened in battle; sent once for every new battle log entry
+#[derive(Event, Clone)]
+pub struct CombatEvent(pub LogEntry);
@@ -1,5 +1,4 @@
 use bevy::prelude::*;
-use bevy::input::common_conditions::input_just_pressed;
 use std::time::Duration;
 
 use crate::player::PlayerStats;
@@ -12,10 +11,13 @@ use crate::battle::enemy_attack;
 use crate::enemy::find_closest_enemy;
 
 use crate::player::Player;
-use crate::WIN_W;
-use crate::WIN_H;
-use crate::player::LEVEL_W;
-use crate::player::LEVEL_H;
+use crate::dungeon::LevelBounds;
+use crate::battle_log::{Actor, BattleAction, Outcome, LogEntry};
+use crate::events::CombatEvent;
+use crate::locale::Locale;
+use crate::settings::Settings;
+
+const HEALTH_BAR_SIZE: f32 = 480.;     // square, squashed by the bar's scale like the background image
 
 #[derive(Component)]
 struct FightScene;
@@ -60,6 +62,35 @@ struct PlayerHealthBarBackground;
 #[derive(Component)]
 struct EnemyHealthBarBackground;
 
+#[derive(Component)]    // damage number or "MISS" drifting up from whoever took the blow, gone when the timer runs out
+struct FloatingText(Timer);
+
+#[derive(Component)]    // moves a battle sprite off its spot and back again
+struct Motion {
+    base: Vec3,     // where the sprite stands when it is not moving
+    kind: MotionKind,
+    timer: Timer,
+}
+
+#[derive(Clone, Copy)]
+enum MotionKind {
+    Lunge(f32),     // toward the other side, 1 is to the right
+    Shake,
+}
+
+#[derive(Component)]    // tints a battle sprite on and off for a moment
+struct Flash {
+    color: Color,
+    restore: Color,     // the sprite's tint before the flash, put back when it ends
+    timer: Timer,
+}
+
+#[derive(Component)]    // a speck of light rising off someone who healed
+struct Sparkle {
+    velocity: Vec2,
+    timer: Timer,
+}
+
 pub struct FightScenePlugin;
 
 
@@ -76,15 +107,15 @@ impl Plugin for FightScenePlugin {
         app.add_systems(OnEnter(GameState::BattleMode), show_battle_ui);
         app.add_systems(OnExit(GameState::BattleMode), hide_battle_ui);
         app.add_systems(Update, execute_animations); 
-        app.add_systems(Update, trigger_animation::<PlayerSprite>.run_if(input_just_pressed(KeyCode::Digit1)));
-        app.add_systems(Update, trigger_animation::<MagicSprite>.run_if(input_just_pressed(KeyCode::Digit2)));
         app.add_systems(Update, init_upon_collision);
         app.add_systems(Update, (update_enemy_health_bar.after(battle_input)));
         app.add_systems(Update, (update_player_health_bar.after(enemy_attack)));
+        app.add_systems(Update, react_to_combat.run_if(in_state(GameState::BattleMode)));
+        app.add_systems(Update, (animate_motions, animate_flashes, animate_floating_text, animate_sparkles));
+        app.add_systems(Update, color_health_bars.run_if(resource_changed::<Settings>));
     }
 }
-fn trigger_animation<S: Component>(mut query: Query<&mut AnimationConfig, With<S>>) {
-    let mut animation = query.single_mut();
+fn trigger_animation(animation: &mut AnimationConfig) {
     // create a new timer when the animation is triggered
     animation.frame_timer = AnimationConfig::timer_from_fps(animation.fps);
 }
@@ -140,6 +171,9 @@ fn init_upon_collision(
             GameState::SkillTreeMenu => next_state.set(GameState::BattleMode),
             GameState::EndCredits => next_state.set(GameState::EndCredits),
             GameState::DefeatScreen => next_state.set(GameState::DefeatScreen),
+            GameState::MapView => next_state.set(GameState::MapView),
+            GameState::Journal => next_state.set(GameState::Journal),
+            GameState::Controls => next_state.set(GameState::Controls),
         }
     }
 }
@@ -168,7 +202,6 @@ fn setup_battle_ui(
     let enemy_texture_handle = asset_server.load("enemyPlaceHolder.png");
    
     let healthbar_background_handle = asset_server.load("healthbarBackground.png");
-    let healthbar_handle = asset_server.load("healthbar.png");
 
     // background
     commands.spawn((
@@ -281,7 +314,7 @@ fn setup_battle_ui(
     // player health bar: scales based on health
     commands.spawn((
         SpriteBundle {
-            texture: healthbar_handle.clone(),
+            sprite: Sprite { custom_size: Some(Vec2::splat(HEALTH_BAR_SIZE)), ..default() },
             transform: Transform {
                 translation: Vec3::new(-400.0-(240.0*(1.0-unsafe { player_health })), 200., 2.), // position health bar above the background
                 scale: Vec3::new(1.0 * unsafe { player_health }, 0.1, 2.0), // scale based on player's health
@@ -313,7 +346,7 @@ fn setup_battle_ui(
     // enemy health bar: scales based on health
     commands.spawn((
         SpriteBundle {
-            texture: healthbar_handle.clone(),
+            sprite: Sprite { custom_size: Some(Vec2::splat(HEALTH_BAR_SIZE)), ..default() },
             transform: Transform {
                 translation: Vec3::new(400.0-(240.0*(1.0-unsafe { enemy_health })), 200., 2.), // position health bar above the background
                 scale: Vec3::new(1.0 * unsafe { enemy_health }, 0.1, 1.0), // scale based on enemy's health
@@ -365,6 +398,15 @@ fn update_player_health_bar(
     }
 }
 
+type HealthBarFill = Or<(With<PlayerHealthBar>, With<EnemyHealthBar>)>;
+
+// the fill is a plain sprite so the palette decides its color
+fn color_health_bars(settings: Res<Settings>, mut bars: Query<&mut Sprite, HealthBarFill>) {
+    for mut sprite in bars.iter_mut() {
+        sprite.color = settings.palette().health;
+    }
+}
+
 // Hide
 fn show_battle_ui(
     mut commands: Commands,
@@ -378,6 +420,7 @@ fn show_battle_ui(
     mut enemy_hb: Query<&mut Transform, (With<EnemyHealthBar>, Without<BattleBackground>, Without<PlayerSprite>, Without<EnemySprite>, Without<PlayerHealthBar>, Without<PlayerHealthBarBackground>, Without<EnemyHealthBarBackground>, Without<MagicSprite>)>,
     mut enemy_hbb: Query<&mut Transform, (With<EnemyHealthBarBackground>, Without<BattleBackground>, Without<PlayerSprite>, Without<EnemySprite>, Without<PlayerHealthBar>, Without<PlayerHealthBarBackground>, Without<EnemyHealthBar>, Without<MagicSprite>)>,
     player: Query<&Transform, (With<Player>, Without<BattleBackground>, Without<PlayerSprite>, Without<EnemySprite>, Without<PlayerHealthBar>, Without<PlayerHealthBarBackground>, Without<EnemyHealthBar>, Without<EnemyHealthBarBackground>, Without<MagicSprite>)>,
+    bounds: Res<LevelBounds>,
 ) { 
 
     for entity in query.iter() {
@@ -387,64 +430,387 @@ fn show_battle_ui(
     //for sprite in sprites.iter() {
 
     let mut bg = background.single_mut();
-    let x_bound = LEVEL_W / 2. - WIN_W / 2.;
-    let y_bound = LEVEL_H / 2. - WIN_H / 2.;
+    let screen = bounds.screen_center(pt.translation);
 
-    bg.translation.x = pt.translation.x.clamp(-x_bound, x_bound);   // same logic as camera/player movement
-    bg.translation.y = pt.translation.y.clamp(-y_bound, y_bound);   // same logic as camera/player movement
+    bg.translation.x = screen.x;
+    bg.translation.y = screen.y;
     bg.translation.z = pt.translation.z + 1.0;
 
     let mut ps = player_sp.single_mut();
 
-    ps.translation.x = pt.translation.x.clamp(-x_bound, x_bound)-200.0;   // same logic as camera/player movement
-    ps.translation.y = pt.translation.y.clamp(-y_bound, y_bound)-100.0;   // same logic as camera/player movement
+    ps.translation.x = screen.x-200.0;
+    ps.translation.y = screen.y-100.0;
     ps.translation.z = pt.translation.z + 1.2;
 
     let mut ms = magic_sp.single_mut();
 
-    ms.translation.x = pt.translation.x.clamp(-x_bound, x_bound)-200.0;   // same logic as camera/player movement
-    ms.translation.y = pt.translation.y.clamp(-y_bound, y_bound)-100.0;   // same logic as camera/player movement
+    ms.translation.x = screen.x-200.0;
+    ms.translation.y = screen.y-100.0;
     ms.translation.z = pt.translation.z + 1.3;
 
     let mut es = enemy_sp.single_mut();
 
-    es.translation.x = pt.translation.x.clamp(-x_bound, x_bound)+200.0;   // same logic as camera/player movement
-    es.translation.y = pt.translation.y.clamp(-y_bound, y_bound)-100.0;   // same logic as camera/player movement
+    es.translation.x = screen.x+200.0;
+    es.translation.y = screen.y-100.0;
     es.translation.z = pt.translation.z + 1.1;
 
     let mut phb = player_hb.single_mut();
 
-    phb.translation.x = pt.translation.x.clamp(-x_bound, x_bound)-400.0-(240.0*(1.0-unsafe { player_health }));   // same logic as camera/player movement
-    phb.translation.y = pt.translation.y.clamp(-y_bound, y_bound)+200.0;   // same logic as camera/player movement
+    phb.translation.x = screen.x-400.0-(240.0*(1.0-unsafe { player_health }));
+    phb.translation.y = screen.y+200.0;
     phb.translation.z = pt.translation.z + 1.2;
     unsafe { player_hpbar_posx = phb.translation.x};    //setting intital posx here
 
     let mut phbb = player_hbb.single_mut();
 
-    phbb.translation.x = pt.translation.x.clamp(-x_bound, x_bound)-400.0;   // same logic as camera/player movement
-    phbb.translation.y = pt.translation.y.clamp(-y_bound, y_bound)+200.0;   // same logic as camera/player movement
+    phbb.translation.x = screen.x-400.0;
+    phbb.translation.y = screen.y+200.0;
     phbb.translation.z = pt.translation.z + 1.1;
  
     let mut ehb = enemy_hb.single_mut();    //enemy health bar 
 
-    ehb.translation.x = pt.translation.x.clamp(-x_bound, x_bound)+400.0-(240.0*(1.0-unsafe { enemy_health }));   // same logic as camera/player movement
-    ehb.translation.y = pt.translation.y.clamp(-y_bound, y_bound)+200.0;   // same logic as camera/player movement
+    ehb.translation.x = screen.x+400.0-(240.0*(1.0-unsafe { enemy_health }));
+    ehb.translation.y = screen.y+200.0;
     ehb.translation.z = pt.translation.z + 1.2;
     unsafe { enemy_hpbar_posx = ehb.translation.x};
 
     let mut ehbb = enemy_hbb.single_mut();  //enemy health bar background
 
-    ehbb.translation.x = pt.translation.x.clamp(-x_bound, x_bound)+400.0;   // same logic as camera/player movement
-    ehbb.translation.y = pt.translation.y.clamp(-y_bound, y_bound)+200.0;   // same logic as camera/player movement
+    ehbb.translation.x = screen.x+400.0;
+    ehbb.translation.y = screen.y+200.0;
     ehbb.translation.z = pt.translation.z + 1.1;
     //}/**/
 }
 
+// the floating numbers and sparkles a battle leaves behind
+type BattleEffect = Or<(With<FloatingText>, With<Sparkle>)>;
+
 fn hide_battle_ui(
     mut commands: Commands,
     query: Query<Entity, With<FightScene>>,
+    effects: Query<Entity, BattleEffect>,
+    mut moving: Query<(Entity, &mut Transform, &Motion)>,
+    mut flashing: Query<(Entity, &mut Sprite, &Flash)>,
 ) {
     for entity in query.iter() {
         commands.entity(entity).insert(Visibility::Hidden);
     }
-}
\ No newline at end of file
+    for entity in effects.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (entity, mut transform, motion) in moving.iter_mut() {
+        transform.translation = motion.base;
+        commands.entity(entity).remove::<Motion>();
+    }
+    for (entity, mut sprite, flash) in flashing.iter_mut() {
+        sprite.color = flash.restore;
+        commands.entity(entity).remove::<Flash>();
+    }
+}
+
+type BattleSprite = (Entity, &'static Transform, &'static Sprite, Option<&'static Motion>, Option<&'static Flash>);
+
+// plays out each logged exchange: the attacker moves, the target reacts and the numbers float up
+fn react_to_combat(
+    mut commands: Commands,
+    mut combat_events: EventReader<CombatEvent>,
+    mut player_animation: Query<&mut AnimationConfig, (With<PlayerSprite>, Without<MagicSprite>)>,
+    mut magic_animation: Query<&mut AnimationConfig, (With<MagicSprite>, Without<PlayerSprite>)>,
+    player_sprite: Query<BattleSprite, (With<PlayerSprite>, Without<EnemySprite>)>,
+    enemy_sprite: Query<BattleSprite, (With<EnemySprite>, Without<PlayerSprite>)>,
+    locale: Res<Locale>,
+) {
+    let (Ok(player), Ok(enemy)) = (player_sprite.get_single(), enemy_sprite.get_single()) else {
+        return;
+    };
+    // a sprite already mid-motion keeps the spot it started from, and one mid-flash the tint it had before
+    let base = |(entity, transform, sprite, motion, flash): (Entity, &Transform, &Sprite, Option<&Motion>, Option<&Flash>)| {
+        let position = motion.map(|m| m.base).unwrap_or(transform.translation);
+        (entity, position, flash.map(|f| f.restore).unwrap_or(sprite.color))
+    };
+    let player = base(player);
+    let enemy = base(enemy);
+
+    for CombatEvent(entry) in combat_events.read() {
+        let (actor, target, direction) = match entry.actor {
+            Actor::Player => (player, enemy, 1.),
+            Actor::Enemy => (enemy, player, -1.),
+            Actor::System => continue,
+        };
+        match entry.action {
+            BattleAction::Attack => {
+                commands.entity(actor.0).insert(Motion::new(actor.1, MotionKind::Lunge(direction), 0.3));
+                if entry.actor == Actor::Player {
+                    if let Ok(mut animation) = player_animation.get_single_mut() {
+                        trigger_animation(&mut animation);
+                    }
+                }
+            }
+            BattleAction::Magic | BattleAction::Spell(_) => {
+                if entry.actor == Actor::Player {
+                    if let Ok(mut animation) = magic_animation.get_single_mut() {
+                        trigger_animation(&mut animation);
+                    }
+                } else {
+                    // the enemy glows as it gathers its psychic force
+                    commands.entity(actor.0).insert(Flash::new(Color::srgb(0.7, 0.4, 1.), actor.2, 0.4));
+                }
+            }
+            BattleAction::Heal => {
+                spawn_sparkles(&mut commands, actor.1);
+                spawn_floating_text(&mut commands, actor.1, format!("+{}", entry.amount), Color::srgb(0.4, 1., 0.4), 36.);
+            }
+            BattleAction::Guard => {
+                commands.entity(actor.0).insert(Flash::new(Color::srgb(0.6, 0.8, 1.), actor.2, 0.4));
+            }
+            _ => {}
+        }
+        if matches!(entry.action, BattleAction::Attack | BattleAction::Magic | BattleAction::Spell(_) | BattleAction::Add) {
+            if entry.amount > 0 {
+                commands.entity(target.0).insert(Motion::new(target.1, MotionKind::Shake, 0.35));
+                commands.entity(target.0).insert(Flash::new(Color::srgb(1., 0.3, 0.3), target.2, 0.35));
+            }
+            let (text, color, size) = hit_text(entry, &locale);
+            spawn_floating_text(&mut commands, target.1, text, color, size);
+        }
+    }
+}
+
+// what floats up over the target of an attack
+fn hit_text(entry: &LogEntry, locale: &Locale) -> (String, Color, f32) {
+    let amount = |key: &str| locale.format(key, &[("amount", entry.amount.into())]);
+    match entry.outcome {
+        Outcome::Miss => (locale.text("hit-miss"), Color::srgb(0.8, 0.8, 0.8), 32.),
+        Outcome::Dodged => (locale.text("hit-dodge"), Color::srgb(0.4, 0.8, 1.), 32.),
+        Outcome::Crit => (amount("hit-crit"), Color::srgb(1., 0.8, 0.1), 44.),
+        Outcome::Graze => (amount("hit-graze"), Color::srgb(0.8, 0.8, 0.8), 28.),
+        Outcome::Blocked => (amount("hit-blocked"), Color::srgb(0.7, 0.7, 0.9), 32.),
+        Outcome::SuperEffective => (amount("hit-super-effective"), Color::srgb(1., 0.45, 0.2), 40.),
+        Outcome::Resisted => (amount("hit-resisted"), Color::srgb(0.6, 0.6, 0.7), 28.),
+        _ => (entry.amount.to_string(), Color::WHITE, 36.),
+    }
+}
+
+fn spawn_floating_text(commands: &mut Commands, over: Vec3, text: String, color: Color, font_size: f32) {
+    commands.spawn((
+        FloatingText(Timer::from_seconds(1.0, TimerMode::Once)),
+        Text2dBundle {
+            text: Text::from_section(text, TextStyle { font_size, color, ..default() }),
+            transform: Transform::from_translation(over + Vec3::new(0., 150., 1.)),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_sparkles(commands: &mut Commands, around: Vec3) {
+    for i in 0..10 {
+        let angle = i as f32 * 0.63;
+        commands.spawn((
+            Sparkle {
+                velocity: Vec2::new(angle.cos() * 40., 80. + 30. * angle.sin().abs()),
+                timer: Timer::from_seconds(0.8, TimerMode::Once),
+            },
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(0.6, 1., 0.6),
+                    custom_size: Some(Vec2::splat(8.)),
+                    ..default()
+                },
+                transform: Transform::from_translation(around + Vec3::new(angle.cos() * 60., angle.sin() * 60., 1.)),
+                ..default()
+            },
+        ));
+    }
+}
+
+impl Motion {
+    fn new(base: Vec3, kind: MotionKind, seconds: f32) -> Self {
+        Self { base, kind, timer: Timer::from_seconds(seconds, TimerMode::Once) }
+    }
+}
+
+impl Flash {
+    fn new(color: Color, restore: Color, seconds: f32) -> Self {
+        Self { color, restore, timer: Timer::from_seconds(seconds, TimerMode::Once) }
+    }
+}
+
+// with reduced motion the sprite holds still for the length of the motion
+fn animate_motions(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut moving: Query<(Entity, &mut Transform, &mut Motion)>,
+) {
+    for (entity, mut transform, mut motion) in moving.iter_mut() {
+        motion.timer.tick(time.delta());
+        if motion.timer.finished() {
+            transform.translation = motion.base;
+            commands.entity(entity).remove::<Motion>();
+            continue;
+        }
+        let t = motion.timer.fraction();
+        let offset = match motion.kind {
+            _ if settings.reduced_motion => 0.,
+            MotionKind::Lunge(direction) => direction * 80. * (t * std::f32::consts::PI).sin(),
+            MotionKind::Shake => 12. * (t * 40.).sin() * (1. - t),
+        };
+        transform.translation = motion.base + Vec3::new(offset, 0., 0.);
+    }
+}
+
+fn animate_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut flashing: Query<(Entity, &mut Sprite, &mut Flash)>,
+) {
+    for (entity, mut sprite, mut flash) in flashing.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            sprite.color = flash.restore;
+            commands.entity(entity).remove::<Flash>();
+            continue;
+        }
+        // blink a few times over the flash, or hold the tint with reduced motion
+        let on = settings.reduced_motion || ((flash.timer.fraction() * 6.) as u32).is_multiple_of(2);
+        sprite.color = if on { flash.color } else { flash.restore };
+    }
+}
+
+// numbers drift up and fade out, with reduced motion they only fade
+fn animate_floating_text(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut floating: Query<(Entity, &mut FloatingText, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut floating_text, mut transform, mut text) in floating.iter_mut() {
+        floating_text.0.tick(time.delta());
+        if floating_text.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if !settings.reduced_motion {
+            transform.translation.y += 60. * time.delta_seconds();
+        }
+        for section in text.sections.iter_mut() {
+            section.style.color.set_alpha(1. - floating_text.0.fraction());
+        }
+    }
+}
+
+fn animate_sparkles(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut sparkles: Query<(Entity, &mut Sparkle, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut sparkle, mut transform, mut sprite) in sparkles.iter_mut() {
+        sparkle.timer.tick(time.delta());
+        if sparkle.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if !settings.reduced_motion {
+            transform.translation += (sparkle.velocity * time.delta_seconds()).extend(0.);
+        }
+        sprite.color.set_alpha(1. - sparkle.timer.fraction());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::locale::Language;
+
+    const TINT: Color = Color::srgb(0.5, 0.6, 0.7);
+    const ENEMY_SPOT: Vec3 = Vec3::new(300., 0., 0.);
+
+    // the battle sprites and the animation systems, with time stepping a tenth of a second per update
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+        app.add_event::<CombatEvent>();
+        app.insert_resource(Locale::new(Language::English));
+        app.init_resource::<Settings>();
+        app.add_systems(Update, (react_to_combat, animate_motions, animate_flashes).chain());
+        app.world_mut().spawn((PlayerSprite, Transform::default(), Sprite::default()));
+        app.world_mut().spawn((EnemySprite, Transform::from_translation(ENEMY_SPOT), Sprite { color: TINT, ..default() }));
+        app.update();
+        app
+    }
+
+    fn player_attacks(app: &mut App, amount: u32) {
+        let entry = LogEntry { battle: 1, actor: Actor::Player, action: BattleAction::Attack, amount, outcome: Outcome::Hit };
+        app.world_mut().send_event(CombatEvent(entry));
+        app.update();
+    }
+
+    fn enemy(app: &mut App) -> Entity {
+        app.world_mut().query_filtered::<Entity, With<EnemySprite>>().single(app.world())
+    }
+
+    #[test]
+    fn a_hit_lunges_the_attacker_and_shakes_the_target() {
+        let mut app = test_app();
+        player_attacks(&mut app, 6);
+        let world = app.world_mut();
+        let lunge = world.query_filtered::<&Motion, With<PlayerSprite>>().single(world);
+        assert!(matches!(lunge.kind, MotionKind::Lunge(direction) if direction > 0.));
+        let (shake, flash) = world.query_filtered::<(&Motion, &Flash), With<EnemySprite>>().single(world);
+        assert!(matches!(shake.kind, MotionKind::Shake));
+        assert_eq!(shake.base, ENEMY_SPOT);
+        assert_eq!(flash.restore, TINT);
+    }
+
+    #[test]
+    fn a_blow_that_misses_only_shows_its_number() {
+        let mut app = test_app();
+        player_attacks(&mut app, 0);
+        let entity = enemy(&mut app);
+        assert!(app.world().get::<Flash>(entity).is_none());
+        assert!(app.world().get::<Motion>(entity).is_none());
+        assert_eq!(app.world_mut().query::<&FloatingText>().iter(app.world()).count(), 1);
+    }
+
+    #[test]
+    fn flash_and_shake_end_where_they_started() {
+        let mut app = test_app();
+        player_attacks(&mut app, 6);
+        // a second hit lands mid-flash and must not take the flash colour as the tint to go back to
+        player_attacks(&mut app, 6);
+        for _ in 0..6 {
+            app.update();
+        }
+        let entity = enemy(&mut app);
+        assert!(app.world().get::<Flash>(entity).is_none());
+        assert!(app.world().get::<Motion>(entity).is_none());
+        assert_eq!(app.world().get::<Sprite>(entity).unwrap().color, TINT);
+        assert_eq!(app.world().get::<Transform>(entity).unwrap().translation, ENEMY_SPOT);
+    }
+
+    #[test]
+    fn reduced_motion_holds_sprites_still_and_the_flash_steady() {
+        let mut app = test_app();
+        app.add_systems(Update, animate_floating_text);
+        app.world_mut().resource_mut::<Settings>().reduced_motion = true;
+        player_attacks(&mut app, 6);
+        let entity = enemy(&mut app);
+        let flash = app.world().get::<Flash>(entity).unwrap().color;
+        let number = app.world_mut().query_filtered::<&Transform, With<FloatingText>>().single(app.world()).translation;
+        // the flash would have blinked off by the second frame; it is over by the third
+        for _ in 0..2 {
+            app.update();
+            assert_eq!(app.world().get::<Transform>(entity).unwrap().translation, ENEMY_SPOT);
+            assert_eq!(app.world().get::<Sprite>(entity).unwrap().color, flash);
+            let world = app.world_mut();
+            let player = world.query_filtered::<&Transform, With<PlayerSprite>>().single(world).translation;
+            assert_eq!(player, Vec3::ZERO);
+            assert_eq!(world.query_filtered::<&Transform, With<FloatingText>>().single(world).translation, number);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashSet;
+
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::{GameState, PauseState};
+use crate::controls::{Action, ActionState, InputContext, ReadInput, read_actions};
+
+const STICK_DEADZONE: f32 = 0.2;    // tilt below this is drift, not input
+const STICK_PRESS: f32 = 0.6;       // tilt that counts as a d-pad press for menus and facing
+
+// pad layout per context, the pad's version of the keyboard Bindings
+const OVERWORLD_PAD: [(GamepadButtonType, Action); 10] = [
+    (GamepadButtonType::DPadUp, Action::Up),
+    (GamepadButtonType::DPadDown, Action::Down),
+    (GamepadButtonType::DPadLeft, Action::Left),
+    (GamepadButtonType::DPadRight, Action::Right),
+    (GamepadButtonType::North, Action::SkillTree),
+    (GamepadButtonType::Select, Action::Map),
+    (GamepadButtonType::West, Action::Journal),
+    (GamepadButtonType::RightTrigger, Action::CycleZoom),
+    (GamepadButtonType::South, Action::Interact),
+    (GamepadButtonType::Start, Action::Pause),
+];
+
+const BATTLE_PAD: [(GamepadButtonType, Action); 8] = [
+    (GamepadButtonType::DPadUp, Action::Up),
+    (GamepadButtonType::DPadDown, Action::Down),
+    (GamepadButtonType::South, Action::Confirm),
+    (GamepadButtonType::East, Action::Back),
+    (GamepadButtonType::North, Action::CycleSpell),
+    (GamepadButtonType::LeftTrigger, Action::LogUp),
+    (GamepadButtonType::RightTrigger, Action::LogDown),
+    (GamepadButtonType::Start, Action::Pause),
+];
+
+const MENU_PAD: [(GamepadButtonType, Action); 16] = [
+    (GamepadButtonType::DPadUp, Action::Up),
+    (GamepadButtonType::DPadDown, Action::Down),
+    (GamepadButtonType::DPadLeft, Action::Left),
+    (GamepadButtonType::DPadRight, Action::Right),
+    (GamepadButtonType::South, Action::Confirm),
+    (GamepadButtonType::East, Action::Back),
+    (GamepadButtonType::North, Action::SkillTree),
+    (GamepadButtonType::Select, Action::Map),
+    (GamepadButtonType::West, Action::Journal),
+    (GamepadButtonType::Start, Action::Controls),
+    (GamepadButtonType::LeftTrigger, Action::ZoomOut),
+    (GamepadButtonType::RightTrigger, Action::ZoomIn),
+    (GamepadButtonType::LeftTrigger2, Action::SpendStrength),
+    (GamepadButtonType::RightTrigger2, Action::SpendMagic),
+    (GamepadButtonType::LeftThumb, Action::SpendAgility),
+    (GamepadButtonType::RightThumb, Action::SpendHealth),
+];
+
+fn pad_layout(context: InputContext) -> &'static [(GamepadButtonType, Action)] {
+    match context {
+        InputContext::Overworld => &OVERWORLD_PAD,
+        InputContext::Battle => &BATTLE_PAD,
+        InputContext::Menu => &MENU_PAD,
+    }
+}
+
+// the pad being read; the first one plugged in, handed to another one if it is unplugged
+#[derive(Resource, Default)]
+pub struct ActivePad(pub Option<Gamepad>);
+
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActivePad>();
+        app.add_systems(PreUpdate, track_pads.after(InputSystem));
+        app.add_systems(PreUpdate, read_pad.after(track_pads).after(read_actions).in_set(ReadInput));
+    }
+}
+
+// keeps ActivePad pointing at a connected pad as they come and go
+fn track_pads(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+    mut active: ResMut<ActivePad>,
+) {
+    for event in connection_events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                if active.0.is_none() {
+                    active.0 = Some(event.gamepad);
+                    info!("Using gamepad {}", info.name);
+                }
+            }
+            GamepadConnection::Disconnected => {
+                if active.0 == Some(event.gamepad) {
+                    active.0 = gamepads.iter().next();
+                    match active.0 {
+                        Some(gamepad) => info!("Gamepad unplugged, switched to {:?}", gamepad),
+                        None => info!("Gamepad unplugged"),
+                    }
+                }
+            }
+        }
+    }
+}
+
+// the left stick, with the deadzone taken out
+fn stick_tilt(x: f32, y: f32) -> Vec2 {
+    let tilt = Vec2::new(x, y);
+    if tilt.length() < STICK_DEADZONE {
+        Vec2::ZERO
+    } else {
+        tilt.clamp_length_max(1.)
+    }
+}
+
+// the d-pad directions a tilted stick stands in for
+fn stick_directions(tilt: Vec2) -> HashSet<Action> {
+    let mut directions = HashSet::new();
+    if tilt.y > STICK_PRESS {
+        directions.insert(Action::Up);
+    }
+    if tilt.y < -STICK_PRESS {
+        directions.insert(Action::Down);
+    }
+    if tilt.x < -STICK_PRESS {
+        directions.insert(Action::Left);
+    }
+    if tilt.x > STICK_PRESS {
+        directions.insert(Action::Right);
+    }
+    directions
+}
+
+// adds the active pad's buttons and stick to the actions the keyboard produced this frame
+fn read_pad(
+    active: Res<ActivePad>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    state: Res<State<GameState>>,
+    pause: Res<State<PauseState>>,
+    mut actions: ResMut<ActionState>,
+    mut held_directions: Local<HashSet<Action>>,
+) {
+    let Some(gamepad) = active.0 else {
+        held_directions.clear();
+        return;
+    };
+    for (button_type, action) in pad_layout(InputContext::current(state.get(), pause.get())).iter() {
+        let button = GamepadButton::new(gamepad, *button_type);
+        if buttons.pressed(button) {
+            actions.press(*action, buttons.just_pressed(button));
+        }
+    }
+
+    let x = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.);
+    let y = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.);
+    let tilt = stick_tilt(x, y);
+    actions.set_movement(tilt);
+    // a stick pushed past STICK_PRESS is a press on the first frame and held after that
+    let directions = stick_directions(tilt);
+    for direction in directions.iter() {
+        actions.press(*direction, !held_directions.contains(direction));
+    }
+    *held_directions = directions;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::gamepad::{GamepadAxisChangedEvent, GamepadButtonChangedEvent, GamepadEvent, GamepadInfo};
+    use bevy::input::InputPlugin;
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin, StatesPlugin));
+        app.init_state::<GameState>();
+        app.init_state::<PauseState>();
+        app.init_resource::<ActionState>();
+        // read_actions would clear this every frame, the tests do it by hand instead
+        app.add_systems(PreUpdate, (|mut actions: ResMut<ActionState>| actions.clear()).in_set(ReadInput).before(read_pad));
+        app.add_plugins(GamepadPlugin);
+        app.update();
+        app
+    }
+
+    fn connect(app: &mut App, id: usize) {
+        let info = GamepadInfo { name: format!("test pad {}", id) };
+        app.world_mut().send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(Gamepad::new(id), GamepadConnection::Connected(info))));
+        app.update();
+    }
+
+    fn disconnect(app: &mut App, id: usize) {
+        app.world_mut().send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(Gamepad::new(id), GamepadConnection::Disconnected)));
+        app.update();
+    }
+
+    fn button(app: &mut App, id: usize, button_type: GamepadButtonType, value: f32) {
+        app.world_mut().send_event(GamepadEvent::Button(GamepadButtonChangedEvent::new(Gamepad::new(id), button_type, value)));
+        app.update();
+    }
+
+    fn stick(app: &mut App, id: usize, x: f32, y: f32) {
+        app.world_mut().send_event(GamepadEvent::Axis(GamepadAxisChangedEvent::new(Gamepad::new(id), GamepadAxisType::LeftStickX, x)));
+        app.world_mut().send_event(GamepadEvent::Axis(GamepadAxisChangedEvent::new(Gamepad::new(id), GamepadAxisType::LeftStickY, y)));
+        app.update();
+    }
+
+    #[test]
+    fn first_pad_plugged_in_is_used_until_unplugged() {
+        let mut app = test_app();
+        connect(&mut app, 0);
+        connect(&mut app, 1);
+        assert_eq!(app.world().resource::<ActivePad>().0, Some(Gamepad::new(0)));
+
+        disconnect(&mut app, 0);
+        assert_eq!(app.world().resource::<ActivePad>().0, Some(Gamepad::new(1)));
+
+        disconnect(&mut app, 1);
+        assert_eq!(app.world().resource::<ActivePad>().0, None);
+    }
+
+    #[test]
+    fn stick_feeds_analog_movement_past_the_deadzone() {
+        let mut app = test_app();
+        connect(&mut app, 0);
+
+        stick(&mut app, 0, 0.1, 0.05);
+        assert_eq!(app.world().resource::<ActionState>().movement(), Vec2::ZERO);
+
+        stick(&mut app, 0, 0.5, 0.);
+        let actions = app.world().resource::<ActionState>();
+        assert!((actions.movement().x - 0.5).abs() < 1e-6);
+        // half a tilt walks, but isn't a menu press
+        assert!(!actions.pressed(Action::Right));
+
+        stick(&mut app, 0, 0.9, 0.);
+        let actions = app.world().resource::<ActionState>();
+        assert!(actions.just_pressed(Action::Right));
+        app.update();
+        let actions = app.world().resource::<ActionState>();
+        assert!(actions.pressed(Action::Right) && !actions.just_pressed(Action::Right));
+    }
+
+    #[test]
+    fn buttons_follow_the_current_context() {
+        let mut app = test_app();
+        connect(&mut app, 0);
+
+        // North opens the skill tree while exploring...
+        button(&mut app, 0, GamepadButtonType::North, 1.);
+        assert!(app.world().resource::<ActionState>().just_pressed(Action::SkillTree));
+        button(&mut app, 0, GamepadButtonType::North, 0.);
+
+        // ...and changes spells in a battle
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::BattleMode);
+        app.update();
+        button(&mut app, 0, GamepadButtonType::North, 1.);
+        let actions = app.world().resource::<ActionState>();
+        assert!(actions.just_pressed(Action::CycleSpell));
+        assert!(!actions.pressed(Action::SkillTree));
+    }
+
+    #[test]
+    fn unplugged_pad_stops_sending_actions() {
+        let mut app = test_app();
+        connect(&mut app, 0);
+        button(&mut app, 0, GamepadButtonType::DPadUp, 1.);
+        assert!(app.world().resource::<ActionState>().pressed(Action::Up));
+
+        disconnect(&mut app, 0);
+        assert!(!app.world().resource::<ActionState>().pressed(Action::Up));
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+use crate::GameState;
+use crate::battle_log::BattleLog;
+use crate::controls::{Action, ActionState};
+use crate::locale::{Locale, Localized};
+
+const JOURNAL_LINES: usize = 20;    // lines of the log shown at once
+
+#[derive(Component)]    // root ui node of the journal screen
+struct JournalUI;
+
+#[derive(Component)]    // the text the log lines are written into
+struct JournalText;
+
+// how far the journal is scrolled up from the newest line
+#[derive(Resource, Default)]
+struct JournalScroll(usize);
+
+pub struct JournalPlugin;
+
+impl Plugin for JournalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JournalScroll>();
+        app.add_systems(Startup, setup_journal);
+        app.add_systems(Update, toggle_journal);
+        app.add_systems(Update, scroll_journal.run_if(in_state(GameState::Journal)));
+        app.add_systems(Update, update_journal.after(scroll_journal).run_if(in_state(GameState::Journal)));
+        app.add_systems(OnEnter(GameState::Journal), show_journal);
+        app.add_systems(OnExit(GameState::Journal), hide_journal);
+    }
+}
+
+fn setup_journal(mut commands: Commands) {
+    commands.spawn((
+        JournalUI,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(40.0)),
+                row_gap: Val::Px(20.0),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Localized::new("journal-title"),
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::srgb(1.0, 1.0, 0.0),
+                    ..default()
+                },
+            ),
+        ));
+        parent.spawn((
+            JournalText,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 22.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+        ));
+        parent.spawn((
+            Localized::new("journal-help"),
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::srgb(0.6, 0.6, 0.6),
+                    ..default()
+                },
+            ),
+        ));
+    });
+}
+
+// J opens the journal from the overworld, same toggle style as the skill tree
+fn toggle_journal(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    actions: Res<ActionState>,
+) {
+    if actions.just_pressed(Action::Journal) {
+        match state.get() {
+            GameState::Welcome => next_state.set(GameState::Welcome),
+            GameState::InGame => next_state.set(GameState::Journal),
+            GameState::SkillTreeMenu => next_state.set(GameState::SkillTreeMenu),
+            GameState::BattleMode => next_state.set(GameState::BattleMode),
+            GameState::EndCredits => next_state.set(GameState::EndCredits),
+            GameState::DefeatScreen => next_state.set(GameState::DefeatScreen),
+            GameState::MapView => next_state.set(GameState::MapView),
+            GameState::Journal => next_state.set(GameState::InGame),
+            GameState::Controls => next_state.set(GameState::Controls),
+        }
+    }
+}
+
+fn show_journal(
+    mut commands: Commands,
+    query: Query<Entity, With<JournalUI>>,
+    mut scroll: ResMut<JournalScroll>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(Visibility::Visible);
+    }
+    // always open on the most recent battle
+    scroll.0 = 0;
+}
+
+fn hide_journal(
+    mut commands: Commands,
+    query: Query<Entity, With<JournalUI>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(Visibility::Hidden);
+    }
+}
+
+fn scroll_journal(
+    actions: Res<ActionState>,
+    mut scroll_events: EventReader<MouseWheel>,
+    battle_log: Res<BattleLog>,
+    mut scroll: ResMut<JournalScroll>,
+) {
+    let mut lines = scroll.0 as i32;
+    for event in scroll_events.read() {
+        if event.y > 0. {
+            lines += 1;
+        } else if event.y < 0. {
+            lines -= 1;
+        }
+    }
+    if actions.just_pressed(Action::Up) {
+        lines += 1;
+    }
+    if actions.just_pressed(Action::Down) {
+        lines -= 1;
+    }
+    // one header line per battle on top of the entries
+    let total = battle_log.entries.len() + battle_log.battles as usize;
+    let lines = lines.clamp(0, total.saturating_sub(JOURNAL_LINES) as i32) as usize;
+    if lines != scroll.0 {
+        scroll.0 = lines;
+    }
+}
+
+fn update_journal(
+    battle_log: Res<BattleLog>,
+    scroll: Res<JournalScroll>,
+    locale: Res<Locale>,
+    mut text: Query<&mut Text, With<JournalText>>,
+) {
+    if !battle_log.is_changed() && !scroll.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut battle = 0;
+    for entry in battle_log.entries.iter() {
+        if entry.battle != battle {
+            battle = entry.battle;
+            lines.push(locale.format("journal-battle", &[("battle", battle.into())]));
+        }
+        lines.push(format!("  {}", entry.describe(&locale)));
+    }
+    if lines.is_empty() {
+        lines.push(locale.text("journal-empty"));
+    }
+
+    let end = lines.len() - scroll.0.min(lines.len());
+    let start = end.saturating_sub(JOURNAL_LINES);
+    text.sections[0].value = lines[start..end].join("\n");
+}
@@ -1,3 +1,97 @@
+use bevy::prelude::*;
+
+//mod map;
+pub mod player;
+pub mod skill_tree;
+pub mod text_box;
+pub mod fight_scene;
+pub mod enemy;
+pub mod events;
+pub mod battle;
+pub mod end_credits;
+pub mod attack;
+pub mod turn_order;
+pub mod defeat;
+pub mod welcome;
+pub mod dungeon;
+pub mod minimap;
+pub mod save;
+pub mod map_view;
+pub mod camera;
+pub mod battle_log;
+pub mod journal;
+pub mod replay;
+pub mod brain;
+pub mod mcts;
+pub mod utility;
+pub mod boss;
+pub mod battle_flow;
+pub mod element;
+pub mod controls;
+pub mod gamepad;
+pub mod pause;
+pub mod menu;
+pub mod settings;
+pub mod audio;
+pub mod locale;
+pub mod shrine;
+
+pub const WIN_W: f32 = 1280.;
+pub const WIN_H: f32 = 720.;
+
+// Global states for the game
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    InGame,
+    Welcome,
+    SkillTreeMenu,
+    BattleMode,
+    EndCredits,
+    DefeatScreen,
+    MapView,
+    Journal,
+    Controls,
+}
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextState {
+    #[default]
+    TextHidden,
+    TextShown,
+}
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MenuState {
+    #[default]
+    MainMenu,
+    AttackMenu,
+    Text,
+}
+
+// the pause overlay sits on top of whatever GameState it was opened in, which keeps running underneath
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+    Settings,   // the settings screen, opened from the pause menu
+}
+
+// phases of a single battle; only exists in BattleMode, so every battle starts over at Intro
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[source(GameState = GameState::BattleMode)]
+pub enum BattleState {
+    #[default]
+    Intro,
+    ChooseAction,
+    ResolvePlayer,
+    ResolveEnemy,
+    Victory,    // rewards screen
+    Defeat,
+    Fled,
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
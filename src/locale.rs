@@ -0,0 +1,224 @@
+use std::fs;
+
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use fluent_bundle::concurrent::FluentBundle;
+use serde::{Deserialize, Serialize};
+use unic_langid::LanguageIdentifier;
+
+use crate::settings::Settings;
+
+const LOCALE_DIR: &str = "assets/locales";
+// English is built in, so a missing or half-done translation still has a line for every key
+const ENGLISH: &str = include_str!("../assets/locales/en.ftl");
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    // also the name of its file in LOCALE_DIR
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+        }
+    }
+
+    // each language under its own name, so players can find theirs whatever is showing
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+}
+
+fn bundle(language: Language, source: String) -> FluentBundle<FluentResource> {
+    let id: LanguageIdentifier = language.code().parse().expect("language codes are valid identifiers");
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // the isolation marks fluent puts around arguments show up as boxes in bevy's font
+    bundle.set_use_isolating(false);
+    let resource = match FluentResource::try_new(source) {
+        Ok(resource) => resource,
+        Err((resource, errors)) => {
+            warn!("Could not parse every message for {}: {:?}", language.code(), errors);
+            resource
+        }
+    };
+    if let Err(errors) = bundle.add_resource(resource) {
+        warn!("Duplicate messages for {}: {:?}", language.code(), errors);
+    }
+    bundle
+}
+
+// every player-facing line by its message key, in the language picked in the settings
+#[derive(Resource)]
+pub struct Locale {
+    language: Language,
+    translation: Option<FluentBundle<FluentResource>>,  // none for English
+    english: FluentBundle<FluentResource>,
+}
+
+impl Locale {
+    pub fn new(language: Language) -> Self {
+        let translation = match language {
+            Language::English => None,
+            _ => {
+                let path = format!("{}/{}.ftl", LOCALE_DIR, language.code());
+                match fs::read_to_string(&path) {
+                    Ok(text) => Some(bundle(language, text)),
+                    Err(e) => {
+                        warn!("Could not read {}: {}, using English", path, e);
+                        None
+                    }
+                }
+            }
+        };
+        Locale {
+            language,
+            translation,
+            english: bundle(Language::English, ENGLISH.to_string()),
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn text(&self, key: &str) -> String {
+        self.format(key, &[])
+    }
+
+    // the message filled in with `args`; English when it isn't translated yet, the key itself when it doesn't exist
+    pub fn format(&self, key: &str, args: &[(&str, FluentValue)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args.iter() {
+            fluent_args.set(*name, value.clone());
+        }
+        for bundle in self.translation.iter().chain(std::iter::once(&self.english)) {
+            let Some(pattern) = bundle.get_message(key).and_then(|message| message.value()) else {
+                continue;
+            };
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+            if !errors.is_empty() {
+                warn!("Could not format {}: {:?}", key, errors);
+            }
+            return text.into_owned();
+        }
+        warn!("No message for {}", key);
+        key.to_string()
+    }
+}
+
+// a fixed piece of ui text, filled in when spawned and again whenever the language changes
+#[derive(Component)]
+pub struct Localized {
+    pub key: &'static str,
+    pub args: Vec<(&'static str, i64)>,
+    pub names: Vec<(&'static str, &'static str)>,   // arguments that are message keys themselves, e.g. an enemy's name
+}
+
+impl Localized {
+    pub fn new(key: &'static str) -> Self {
+        Localized { key, args: Vec::new(), names: Vec::new() }
+    }
+
+    pub fn with(mut self, name: &'static str, value: i64) -> Self {
+        self.args.push((name, value));
+        self
+    }
+
+    pub fn with_name(mut self, name: &'static str, key: &'static str) -> Self {
+        self.names.push((name, key));
+        self
+    }
+
+    pub fn text(&self, locale: &Locale) -> String {
+        let args: Vec<(&str, FluentValue)> = self.args.iter().map(|(name, value)| (*name, (*value).into()))
+            .chain(self.names.iter().map(|(name, key)| (*name, locale.text(key).into())))
+            .collect();
+        locale.format(self.key, &args)
+    }
+}
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, switch_language.run_if(resource_changed::<Settings>));
+        app.add_systems(PostUpdate, relabel_text.before(UiSystem::Layout));
+    }
+}
+
+fn switch_language(settings: Res<Settings>, mut locale: ResMut<Locale>) {
+    if settings.language != locale.language() {
+        *locale = Locale::new(settings.language);
+    }
+}
+
+fn relabel_text(locale: Res<Locale>, mut labels: Query<(Ref<Localized>, &mut Text)>) {
+    for (localized, mut text) in labels.iter_mut() {
+        if !locale.is_changed() && !localized.is_changed() {
+            continue;
+        }
+        text.sections[0].value = localized.text(&locale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // messages start at the beginning of a line, comments and continued lines don't
+    fn message_keys(source: &str) -> Vec<&str> {
+        source
+            .lines()
+            .filter(|line| !line.starts_with(' ') && !line.starts_with('#'))
+            .filter_map(|line| line.split_once(" =").map(|(key, _)| key))
+            .collect()
+    }
+
+    #[test]
+    fn arguments_and_plurals_are_filled_in() {
+        let locale = Locale::new(Language::English);
+        assert_eq!(locale.format("log-player-attack", &[("amount", 5.into())]), "Enemy was attacked for 5 damage!");
+        assert_eq!(locale.format("victory-skill-points", &[("points", 1.into())]), "+1 skill point");
+        assert_eq!(locale.format("victory-skill-points", &[("points", 3.into())]), "+3 skill points");
+        assert_eq!(locale.format("log-player-guard", &[("amount", 2.into()), ("max", 3.into())]), "You raised your guard. Focus 2/3.");
+    }
+
+    #[test]
+    fn names_in_labels_follow_the_language() {
+        let slain = Localized::new("defeat-slain-by").with_name("enemy", "enemy-stray");
+        assert_eq!(slain.text(&Locale::new(Language::English)), "Slain by a stray");
+        assert_eq!(slain.text(&Locale::new(Language::Spanish)), "Abatido por un errante");
+        let penalty = Localized::new("defeat-penalty").with("points", 2);
+        assert_eq!(penalty.text(&Locale::new(Language::English)), Locale::new(Language::English).format("defeat-penalty", &[("points", 2.into())]));
+    }
+
+    #[test]
+    fn missing_messages_fall_back() {
+        let locale = Locale::new(Language::English);
+        assert_eq!(locale.text("no-such-message"), "no-such-message");
+    }
+
+    #[test]
+    fn every_translation_has_every_message() {
+        let english = message_keys(ENGLISH);
+        for language in Language::ALL.iter().filter(|language| **language != Language::English) {
+            let path = format!("{}/{}.ftl", LOCALE_DIR, language.code());
+            let text = fs::read_to_string(&path).expect("translation exists");
+            let translated = message_keys(&text);
+            let missing: Vec<&&str> = english.iter().filter(|key| !translated.contains(key)).collect();
+            assert!(missing.is_empty(), "{} is missing {:?}", path, missing);
+        }
+    }
+}
@@ -1,104 +1,101 @@
-use bevy::{prelude::*, window::PresentMode};
-
-//mod map;
-mod player;
-mod skill_tree;
-mod text_box;
-mod fight_scene;
-mod enemy;
-mod events;
-mod battle;
-mod end_credits;
-mod attack;
-mod turn_order;
-mod defeat;
-mod node;
-mod welcome;
-mod dungeon;
+use bevy::prelude::*;
 
 //use map::MapPlugin;
-use welcome::WelcomePlugin;
-use player::PlayerPlugin;
-use turn_order::TurnOrder;
-use crate::skill_tree::SkillTreePlugin;
-use text_box::TextboxPlugin;
-use fight_scene::FightScenePlugin;
-use enemy::EnemyPlugin;
-use events::EnemyCollisionEvent;
-use events::EndGameEvent;
-use battle::BattlePlugin;
-use end_credits::EndCreditsPlugin;
-use defeat::DefeatScreenPlugin; 
-use node::NodePlugin;
-use dungeon::DungeonPlugin;
-
-const TITLE: &str = "main";
-const WIN_W: f32 = 1280.;
-const WIN_H: f32 = 720.;
+use main::welcome::WelcomePlugin;
+use main::player::PlayerPlugin;
+use main::skill_tree::SkillTreePlugin;
+use main::text_box::TextboxPlugin;
+use main::fight_scene::FightScenePlugin;
+use main::enemy::EnemyPlugin;
+use main::events::EnemyCollisionEvent;
+use main::events::EndGameEvent;
+use main::battle::BattlePlugin;
+use main::end_credits::EndCreditsPlugin;
+use main::defeat::DefeatScreenPlugin; 
+use main::dungeon::DungeonPlugin;
+use main::minimap::MinimapPlugin;
+use main::save::SavePlugin;
+use main::map_view::MapViewPlugin;
+use main::camera::CameraPlugin;
+use main::battle_log::BattleLogPlugin;
+use main::journal::JournalPlugin;
+use main::replay::ReplayPlugin;
+use main::boss::BossPlugin;
+use main::battle_flow::BattleFlowPlugin;
+use main::element::ElementPlugin;
+use main::controls::ControlsPlugin;
+use main::gamepad::GamepadPlugin;
+use main::pause::PausePlugin;
+use main::settings::{Settings, SettingsPlugin};
+use main::audio::SoundPlugin;
+use main::locale::{Locale, LocalePlugin};
+use main::shrine::ShrinePlugin;
+use main::events::{EnemyDefeatedEvent, RebuildDungeonEvent, SaveGameEvent, GameSavedEvent, LoadGameEvent, NewRunEvent, CameraShakeEvent, PlayerActionEvent, CombatEvent};
 
-// Global states for the game
-#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
-enum GameState {
-    #[default]
-    InGame,
-    Welcome,
-    SkillTreeMenu,
-    BattleMode,
-    EndCredits,
-    DefeatScreen,
-}
+use main::{GameState, TextState, MenuState, BattleState, PauseState};
 
-#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
-enum TextState {
-    #[default]
-    TextHidden,
-    TextShown,
-}
-
-#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
-enum MenuState {
-    #[default]
-    MainMenu,
-    AttackMenu,
-    Text,
-}
-
-#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
-enum BattleState {
-    #[default]
-    PlayerTurn,
-    EnemyTurn,
-}
+const TITLE: &str = "main";
 
 fn main() {
+    let settings = Settings::load();
+    let locale = Locale::new(settings.language);
     App::new()
         .insert_resource(ClearColor(Color::Srgba(Srgba::gray(0.25))))
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: TITLE.into(),
-                resolution: (WIN_W, WIN_H).into(),
-                present_mode: PresentMode::Fifo,
+                resolution: (settings.resolution.0 as f32, settings.resolution.1 as f32).into(),
+                present_mode: settings.present_mode(),
+                mode: settings.window_mode(),
                 ..default()
             }),
             ..default()
         }))
+        .insert_resource(settings)
+        .insert_resource(locale)
         .init_state::<TextState>()
         .init_state::<GameState>()
         .init_state::<MenuState>()
+        .init_state::<PauseState>()
         .add_plugins(WelcomePlugin)
         .add_plugins(DungeonPlugin)
-        .init_state::<BattleState>()
+        .add_sub_state::<BattleState>()
         .add_plugins(PlayerPlugin)
         .add_plugins(SkillTreePlugin)
         .add_plugins(BattlePlugin)
+        .add_plugins(BattleFlowPlugin)
         .add_plugins(EnemyPlugin)
+        .add_plugins(BossPlugin)
+        .add_plugins(ElementPlugin)
         .add_plugins(FightScenePlugin)
         .add_event::<EnemyCollisionEvent>()
         .add_event::<EndGameEvent>()
+        .add_event::<EnemyDefeatedEvent>()
+        .add_event::<RebuildDungeonEvent>()
+        .add_event::<SaveGameEvent>()
+        .add_event::<GameSavedEvent>()
+        .add_event::<LoadGameEvent>()
+        .add_event::<NewRunEvent>()
+        .add_event::<CameraShakeEvent>()
+        .add_event::<PlayerActionEvent>()
+        .add_event::<CombatEvent>()
         .add_plugins(TextboxPlugin)
         .add_plugins(EndCreditsPlugin)
         .add_plugins(DefeatScreenPlugin)
-        .add_plugins(NodePlugin)
+        .add_plugins(MinimapPlugin)
+        .add_plugins(SavePlugin)
+        .add_plugins(MapViewPlugin)
+        .add_plugins(CameraPlugin)
+        .add_plugins(BattleLogPlugin)
+        .add_plugins(JournalPlugin)
+        .add_plugins(ReplayPlugin)
+        .add_plugins(ControlsPlugin)
+        .add_plugins(GamepadPlugin)
+        .add_plugins(PausePlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(SoundPlugin)
+        .add_plugins(LocalePlugin)
+        .add_plugins(ShrinePlugin)
         /*
             add other plugins here
         */
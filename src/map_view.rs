@@ -0,0 +1,337 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+use crate::GameState;
+use crate::player::Player;
+use crate::dungeon::{DungeonLayout, RoomKind, world_to_tile};
+use crate::minimap::{MapData, MinimapImage};
+use crate::controls::{Action, ActionState};
+use crate::locale::Localized;
+
+const MAP_SCALE: f32 = 8.;      // screen pixels per tile at zoom 1
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 4.;
+const ZOOM_STEP: f32 = 1.25;
+const PAN_SPEED: f32 = 600.;    // screen pixels per second
+const MARKER_SIZE: f32 = 10.;
+
+// Camera for the full-screen map plus the markers the player has placed
+#[derive(Resource)]
+pub struct MapView {
+    pub zoom: f32,
+    pub center: Vec2,   // tile coordinate shown in the middle of the screen
+    pub markers: Vec<IVec2>,
+}
+
+impl MapView {
+    fn new() -> Self {
+        Self {
+            zoom: 1.,
+            center: Vec2::ZERO,
+            markers: Vec::new(),
+        }
+    }
+
+    fn scale(&self) -> f32 {
+        MAP_SCALE * self.zoom
+    }
+}
+
+#[derive(Component)]    // root ui node of the map screen
+struct MapViewUI;
+
+#[derive(Component)]    // the explored tiles image that gets panned and zoomed
+struct MapViewTiles;
+
+#[derive(Component)]    // labels and markers drawn over the tiles, respawned every frame
+struct MapViewOverlay;
+
+pub struct MapViewPlugin;
+
+impl Plugin for MapViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MapView::new());
+        app.add_systems(PostStartup, setup_map_view);
+        app.add_systems(Update, toggle_map_view);
+        app.add_systems(Update, pan_and_zoom_map.run_if(in_state(GameState::MapView)));
+        app.add_systems(Update, place_map_marker.after(pan_and_zoom_map).run_if(in_state(GameState::MapView)));
+        app.add_systems(Update, draw_map_view.after(place_map_marker).run_if(in_state(GameState::MapView)));
+        app.add_systems(OnEnter(GameState::MapView), show_map_view);
+        app.add_systems(OnExit(GameState::MapView), hide_map_view);
+    }
+}
+
+fn setup_map_view(
+    mut commands: Commands,
+    minimap_image: Res<MinimapImage>,
+) {
+    commands.spawn((
+        MapViewUI,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgb(0.05, 0.05, 0.08)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            MapViewTiles,
+            ImageBundle {
+                image: UiImage::new(minimap_image.0.clone()),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+        parent.spawn((
+            Localized::new("map-help"),
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..default()
+            }),
+        ));
+    });
+}
+
+// same toggle as the skill tree, but on M
+fn toggle_map_view(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    actions: Res<ActionState>,
+) {
+    if actions.just_pressed(Action::Map) {
+        match state.get() {
+            GameState::Welcome => next_state.set(GameState::Welcome),
+            GameState::InGame => next_state.set(GameState::MapView),
+            GameState::SkillTreeMenu => next_state.set(GameState::SkillTreeMenu),
+            GameState::BattleMode => next_state.set(GameState::BattleMode),
+            GameState::EndCredits => next_state.set(GameState::EndCredits),
+            GameState::DefeatScreen => next_state.set(GameState::DefeatScreen),
+            GameState::MapView => next_state.set(GameState::InGame),
+            GameState::Journal => next_state.set(GameState::Journal),
+            GameState::Controls => next_state.set(GameState::Controls),
+        }
+    }
+}
+
+fn show_map_view(
+    mut commands: Commands,
+    query: Query<Entity, With<MapViewUI>>,
+    mut map_view: ResMut<MapView>,
+    player: Query<&Transform, With<Player>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(Visibility::Visible);
+    }
+    // open the map centered on the player
+    if let Ok(pt) = player.get_single() {
+        map_view.center = world_to_tile(pt.translation).as_vec2();
+    }
+}
+
+fn hide_map_view(
+    mut commands: Commands,
+    query: Query<Entity, With<MapViewUI>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(Visibility::Hidden);
+    }
+}
+
+fn pan_and_zoom_map(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut map_view: ResMut<MapView>,
+) {
+    let mut pan = Vec2::ZERO;
+    if actions.pressed(Action::Left) {
+        pan.x -= 1.;
+    }
+    if actions.pressed(Action::Right) {
+        pan.x += 1.;
+    }
+    if actions.pressed(Action::Up) {
+        pan.y += 1.;
+    }
+    if actions.pressed(Action::Down) {
+        pan.y -= 1.;
+    }
+    if pan != Vec2::ZERO {
+        // pan at a constant screen speed no matter the zoom
+        let step = pan.normalize() * PAN_SPEED * time.delta_seconds() / map_view.scale();
+        map_view.center += step;
+    }
+
+    let mut zoom = map_view.zoom;
+    for event in scroll_events.read() {
+        if event.y > 0. {
+            zoom *= ZOOM_STEP;
+        } else if event.y < 0. {
+            zoom /= ZOOM_STEP;
+        }
+    }
+    if actions.just_pressed(Action::ZoomIn) {
+        zoom *= ZOOM_STEP;
+    }
+    if actions.just_pressed(Action::ZoomOut) {
+        zoom /= ZOOM_STEP;
+    }
+    map_view.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+}
+
+// screen position (from the top left of the window) of the top left corner of the tiles image
+fn map_origin(map_view: &MapView, window: &Window, min: IVec2, max: IVec2) -> Vec2 {
+    let scale = map_view.scale();
+    Vec2::new(
+        window.width() / 2. - (map_view.center.x - min.x as f32 + 0.5) * scale,
+        window.height() / 2. - (max.y as f32 - map_view.center.y + 0.5) * scale,
+    )
+}
+
+// left click toggles a marker on the tile under the cursor
+fn place_map_marker(
+    buttons: Res<ButtonInput<MouseButton>>,
+    window: Query<&Window>,
+    layout: Res<DungeonLayout>,
+    mut map_view: ResMut<MapView>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let (min, max) = layout.tile_bounds();
+    let origin = map_origin(&map_view, window, min, max);
+    let offset = (cursor - origin) / map_view.scale();
+    let tile = IVec2::new(min.x + offset.x.floor() as i32, max.y - offset.y.floor() as i32);
+    if tile.x < min.x || tile.x > max.x || tile.y < min.y || tile.y > max.y {
+        return;
+    }
+
+    if let Some(i) = map_view.markers.iter().position(|marker| *marker == tile) {
+        map_view.markers.remove(i);
+    } else {
+        map_view.markers.push(tile);
+    }
+}
+
+fn spawn_map_marker(parent: &mut ChildBuilder, tile: IVec2, min: IVec2, max: IVec2, scale: f32, color: Color) {
+    parent.spawn((
+        MapViewOverlay,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px((tile.x - min.x) as f32 * scale + (scale - MARKER_SIZE) / 2.),
+                top: Val::Px((max.y - tile.y) as f32 * scale + (scale - MARKER_SIZE) / 2.),
+                width: Val::Px(MARKER_SIZE),
+                height: Val::Px(MARKER_SIZE),
+                ..default()
+            },
+            background_color: BackgroundColor(color),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_map_label(parent: &mut ChildBuilder, key: &'static str, tile: IVec2, min: IVec2, max: IVec2, scale: f32) {
+    parent.spawn((
+        MapViewOverlay,
+        Localized::new(key),
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px((tile.x - min.x) as f32 * scale),
+            top: Val::Px((max.y - tile.y) as f32 * scale),
+            ..default()
+        }),
+    ));
+}
+
+// positions the tiles image from the pan/zoom and redraws labels and markers on top of it
+fn draw_map_view(
+    mut commands: Commands,
+    map_view: Res<MapView>,
+    map: MapData,
+    window: Query<&Window>,
+    player: Query<&Transform, With<Player>>,
+    mut tiles_node: Query<(Entity, &mut Style), With<MapViewTiles>>,
+    overlay: Query<Entity, With<MapViewOverlay>>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let Ok((tiles_entity, mut style)) = tiles_node.get_single_mut() else {
+        return;
+    };
+    for entity in overlay.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let MapData { layout, exploration, maze_grid, settings, .. } = map;
+    let (min, max) = layout.tile_bounds();
+    let scale = map_view.scale();
+    let origin = map_origin(&map_view, window, min, max);
+    style.left = Val::Px(origin.x);
+    style.top = Val::Px(origin.y);
+    style.width = Val::Px((max.x - min.x + 1) as f32 * scale);
+    style.height = Val::Px((max.y - min.y + 1) as f32 * scale);
+
+    commands.entity(tiles_entity).with_children(|parent| {
+        // label the special rooms once any of their tiles has been seen
+        for room in layout.rooms.iter() {
+            let label = match room.kind {
+                RoomKind::Start => "map-start",
+                RoomKind::Boss => "map-boss",
+                RoomKind::End => "map-exit",
+                RoomKind::Battle if room.shrine => "map-shrine",
+                RoomKind::Battle => continue,
+            };
+            let seen = (0..room.size.x)
+                .any(|x| (0..room.size.y).any(|y| exploration.explored.contains(&(room.origin + IVec2::new(x, y)))));
+            if seen {
+                spawn_map_label(parent, label, room.origin + IVec2::new(0, room.size.y - 1), min, max, scale);
+            }
+        }
+        if exploration.explored.contains(&maze_grid.center_tile()) {
+            spawn_map_label(parent, "map-maze", maze_grid.center_tile(), min, max, scale);
+        }
+
+        for marker in map_view.markers.iter() {
+            spawn_map_marker(parent, *marker, min, max, scale, Color::srgb(0.2, 0.6, 1.0));
+        }
+        if let Ok(pt) = player.get_single() {
+            spawn_map_marker(parent, world_to_tile(pt.translation), min, max, scale, settings.palette().player);
+        }
+    });
+}
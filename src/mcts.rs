@@ -1,2 +1,97 @@
-use bevy::prelude::*;
+use rand::prelude::*;
 
+use crate::player::PlayerStats;
+use crate::enemy::EnemyStats;
+use crate::brain::{BattleSnapshot, EnemyAction, EnemyBrain};
+use crate::boss::moveset;
+use crate::battle_log::Outcome;
+use crate::battle::{guarded, physical_strike, magic_attack, heal, resolve_enemy_action};
+
+const MCTS_ITERATIONS: usize = 300;
+const ROLLOUT_TURNS: u32 = 8;       // exchanges played out before an unfinished rollout is scored
+const EXPLORATION: f32 = 1.4;       // UCB1 exploration constant
+
+// Flat Monte Carlo tree search: UCB1 picks which move to try next,
+// random playouts with the real damage rolls score it
+pub struct MctsBrain;
+
+impl EnemyBrain for MctsBrain {
+    fn name(&self) -> &'static str {
+        "mcts"
+    }
+
+    fn choose_action(&mut self, snapshot: &BattleSnapshot, rng: &mut dyn RngCore) -> EnemyAction {
+        // playouts use their own stream seeded from one draw, so replays stay deterministic
+        let mut rollout_rng = StdRng::seed_from_u64(rng.next_u64());
+        // a boss only searches the moves its current phase allows
+        let moves = moveset(snapshot.enemy_stats);
+        let mut visits = vec![0u32; moves.len()];
+        let mut rewards = vec![0f32; moves.len()];
+
+        for i in 0..MCTS_ITERATIONS {
+            let choice = if i < visits.len() {
+                i
+            } else {
+                let total = i as f32;
+                (0..visits.len())
+                    .max_by(|a, b| ucb(rewards[*a], visits[*a], total).total_cmp(&ucb(rewards[*b], visits[*b], total)))
+                    .unwrap_or(0)
+            };
+            let reward = rollout(
+                moves[choice],
+                snapshot.player_stats.clone(),
+                snapshot.enemy_stats.clone(),
+                &mut rollout_rng,
+            );
+            visits[choice] += 1;
+            rewards[choice] += reward;
+        }
+
+        let best = (0..visits.len()).max_by_key(|i| visits[*i]).unwrap_or(0);
+        moves[best]
+    }
+}
+
+fn ucb(reward: f32, visits: u32, total: f32) -> f32 {
+    let visits = visits.max(1) as f32;
+    reward / visits + EXPLORATION * (total.ln() / visits).sqrt()
+}
+
+// plays the battle on from the enemy's move with both sides acting at random,
+// 1 if the enemy wins, 0 if it loses, otherwise how far ahead it is
+fn rollout(first: EnemyAction, mut player_stats: PlayerStats, mut enemy_stats: EnemyStats, rng: &mut StdRng) -> f32 {
+    let mut action = first;
+    for _ in 0..ROLLOUT_TURNS {
+        resolve_enemy_action(action, &mut player_stats, &mut enemy_stats, rng);
+        if player_stats.hp == 0 {
+            return 1.;
+        }
+
+        match rng.gen_range(0..3) {
+            0 => {
+                let (damage, strike) = physical_strike(5, player_stats.atk, enemy_stats.physdef, player_stats.spd, enemy_stats.speed, rng);
+                let (damage, _) = guarded(damage, strike.outcome(), &mut enemy_stats.guarding);
+                enemy_stats.hp = enemy_stats.hp.saturating_sub(damage);
+            }
+            1 => {
+                let damage = magic_attack(5, player_stats.matk, enemy_stats.mgkdef, rng);
+                let (damage, _) = guarded(damage, Outcome::Hit, &mut enemy_stats.guarding);
+                enemy_stats.hp = enemy_stats.hp.saturating_sub(damage);
+            }
+            _ => {
+                let heal_amt = heal(4, player_stats.magic);
+                player_stats.hp = (player_stats.hp + heal_amt).min(player_stats.max_hp);
+            }
+        }
+        if enemy_stats.hp == 0 {
+            return 0.;
+        }
+
+        // the enemy's later moves are played out among the ones it is allowed
+        let moves = moveset(&enemy_stats);
+        action = moves[rng.gen_range(0..moves.len())];
+    }
+    let enemy_left = enemy_stats.hp as f32 / enemy_stats.max_hp.max(1) as f32;
+    let player_left = player_stats.hp as f32 / player_stats.max_hp.max(1) as f32;
+    0.5 + 0.5 * (enemy_left - player_left)
+}
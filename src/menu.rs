@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use crate::controls::{Action, ActionState};
+
+// the look shared by the button menus: battle, pause and settings
+pub const BUTTON_COLOR: Color = Color::srgba(0.1, 0.1, 0.15, 0.9);
+pub const FOCUS_COLOR: Color = Color::srgba(0.3, 0.3, 0.5, 0.95);
+pub const DISABLED_TEXT: Color = Color::srgb(0.45, 0.45, 0.45);
+
+pub fn button_color(focused: bool) -> BackgroundColor {
+    BackgroundColor(if focused { FOCUS_COLOR } else { BUTTON_COLOR })
+}
+
+pub fn label_color(enabled: bool) -> Color {
+    if enabled { Color::WHITE } else { DISABLED_TEXT }
+}
+
+// hovering or Up/Down moves the focus over `count` buttons, wrapping around;
+// returns the index of the button clicked, or the focused one if Confirm was pressed
+pub fn pick_button<'a>(
+    focus: &mut usize,
+    count: usize,
+    actions: &ActionState,
+    interactions: impl IntoIterator<Item = (usize, Ref<'a, Interaction>)>,
+) -> Option<usize> {
+    let mut picked = None;
+    for (index, interaction) in interactions {
+        if !interaction.is_changed() {
+            continue;
+        }
+        match *interaction {
+            Interaction::Hovered => *focus = index,
+            Interaction::Pressed => {
+                *focus = index;
+                picked = Some(index);
+            }
+            Interaction::None => {}
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+    if actions.just_pressed(Action::Up) {
+        *focus = (*focus + count - 1) % count;
+    }
+    if actions.just_pressed(Action::Down) {
+        *focus = (*focus + 1) % count;
+    }
+    if actions.just_pressed(Action::Confirm) {
+        picked = Some(*focus % count);
+    }
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pressed(actions: &[Action]) -> ActionState {
+        let mut state = ActionState::default();
+        for action in actions {
+            state.press(*action, true);
+        }
+        state
+    }
+
+    #[test]
+    fn up_and_down_wrap_around() {
+        let mut focus = 0;
+        assert_eq!(pick_button(&mut focus, 3, &pressed(&[Action::Up]), []), None);
+        assert_eq!(focus, 2);
+        assert_eq!(pick_button(&mut focus, 3, &pressed(&[Action::Down]), []), None);
+        assert_eq!(focus, 0);
+    }
+
+    #[test]
+    fn confirm_picks_the_focused_button() {
+        let mut focus = 1;
+        assert_eq!(pick_button(&mut focus, 3, &pressed(&[Action::Down, Action::Confirm]), []), Some(2));
+        assert_eq!(pick_button(&mut focus, 0, &pressed(&[Action::Confirm]), []), None);
+    }
+
+    #[test]
+    fn clicks_and_hovers_move_the_focus() {
+        let mut world = World::new();
+        let hovered = world.spawn(Interaction::Hovered).id();
+        let clicked = world.spawn(Interaction::Pressed).id();
+        let mut query = world.query::<Ref<Interaction>>();
+
+        let mut focus = 0;
+        let interactions = [(1, query.get(&world, hovered).unwrap())];
+        assert_eq!(pick_button(&mut focus, 3, &ActionState::default(), interactions), None);
+        assert_eq!(focus, 1);
+
+        let interactions = [(2, query.get(&world, clicked).unwrap())];
+        assert_eq!(pick_button(&mut focus, 3, &ActionState::default(), interactions), Some(2));
+        assert_eq!(focus, 2);
+    }
+
+    #[test]
+    fn disabled_buttons_grey_out_their_label() {
+        assert_eq!(label_color(false), DISABLED_TEXT);
+        assert_eq!(button_color(true).0, FOCUS_COLOR);
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+
+use crate::GameState;
+use crate::player::Player;
+use crate::enemy::Enemy;
+use crate::dungeon::{DungeonLayout, ClearedRooms, MazeGrid, RoomKind, world_to_tile};
+use crate::settings::Settings;
+
+const REVEAL_RADIUS: i32 = 4;       // tiles around the player that get explored
+const MINIMAP_SCALE: f32 = 3.;      // screen pixels per tile
+const MARKER_SIZE: f32 = 6.;
+
+const UNEXPLORED_COLOR: [u8; 4] = [0, 0, 0, 0];
+const FLOOR_COLOR: [u8; 4] = [170, 170, 170, 255];
+const WALL_COLOR: [u8; 4] = [70, 70, 70, 255];
+const START_ROOM_COLOR: [u8; 4] = [90, 160, 220, 255];
+const BOSS_ROOM_COLOR: [u8; 4] = [200, 70, 70, 255];
+const END_ROOM_COLOR: [u8; 4] = [230, 200, 60, 255];
+const SHRINE_ROOM_COLOR: [u8; 4] = [120, 210, 200, 255];
+
+// Tiles the player has seen so far (fog of war for the minimap)
+#[derive(Resource, Default)]
+pub struct Exploration {
+    pub explored: HashSet<IVec2>,
+}
+
+// explored tiles painted one pixel per tile, shared with the full-screen map
+#[derive(Resource)]
+pub struct MinimapImage(pub Handle<Image>);
+
+// the dungeon and what the player knows of it, which the maps are drawn from
+#[derive(SystemParam)]
+pub struct MapData<'w> {
+    pub layout: Res<'w, DungeonLayout>,
+    pub exploration: Res<'w, Exploration>,
+    pub cleared: Res<'w, ClearedRooms>,
+    pub maze_grid: Res<'w, MazeGrid>,
+    pub settings: Res<'w, Settings>,
+}
+
+#[derive(Component)]    // root ui node of the minimap
+struct Minimap;
+
+#[derive(Component)]    // the image the explored tiles are painted into
+struct MinimapTiles;
+
+#[derive(Component)]    // player/enemy/door dots, respawned every frame
+struct MinimapMarker;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Exploration>()
+            .add_systems(PostStartup, setup_minimap)
+            .add_systems(Update, reveal_tiles.run_if(in_state(GameState::InGame)))
+            .add_systems(Update, draw_minimap.after(reveal_tiles))
+            .add_systems(Update, draw_minimap_markers.after(draw_minimap).run_if(in_state(GameState::InGame)))
+            .add_systems(OnEnter(GameState::InGame), show_minimap)
+            .add_systems(OnExit(GameState::InGame), hide_minimap);
+    }
+}
+
+fn new_minimap_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &UNEXPLORED_COLOR,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // keep tiles as crisp squares when scaled up
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+fn minimap_size(layout: &DungeonLayout) -> UVec2 {
+    let (min, max) = layout.tile_bounds();
+    (max - min + IVec2::ONE).as_uvec2()
+}
+
+fn setup_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    layout: Res<DungeonLayout>,
+) {
+    let size = minimap_size(&layout);
+    let handle = images.add(new_minimap_image(size));
+    commands.insert_resource(MinimapImage(handle.clone()));
+
+    commands.spawn((
+        Minimap,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            MinimapTiles,
+            ImageBundle {
+                image: UiImage::new(handle),
+                style: Style {
+                    width: Val::Px(size.x as f32 * MINIMAP_SCALE),
+                    height: Val::Px(size.y as f32 * MINIMAP_SCALE),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    });
+}
+
+// marks every tile within REVEAL_RADIUS of the player as explored
+fn reveal_tiles(
+    player: Query<&Transform, With<Player>>,
+    mut exploration: ResMut<Exploration>,
+) {
+    let Ok(pt) = player.get_single() else {
+        return;
+    };
+    let center = world_to_tile(pt.translation);
+    for dx in -REVEAL_RADIUS..=REVEAL_RADIUS {
+        for dy in -REVEAL_RADIUS..=REVEAL_RADIUS {
+            let tile = center + IVec2::new(dx, dy);
+            // only touch the resource when something new is revealed so change detection stays meaningful
+            if dx * dx + dy * dy <= REVEAL_RADIUS * REVEAL_RADIUS && !exploration.explored.contains(&tile) {
+                exploration.explored.insert(tile);
+            }
+        }
+    }
+}
+
+// repaints the minimap image whenever the layout or the explored tiles change
+fn draw_minimap(
+    layout: Res<DungeonLayout>,
+    exploration: Res<Exploration>,
+    minimap_image: Res<MinimapImage>,
+    mut images: ResMut<Assets<Image>>,
+    mut tiles_node: Query<&mut Style, With<MinimapTiles>>,
+) {
+    if !layout.is_changed() && !exploration.is_changed() {
+        return;
+    }
+    let Some(image) = images.get_mut(&minimap_image.0) else {
+        return;
+    };
+
+    let (min, max) = layout.tile_bounds();
+    let size = minimap_size(&layout);
+    if image.size() != size {
+        *image = new_minimap_image(size);
+        for mut style in tiles_node.iter_mut() {
+            style.width = Val::Px(size.x as f32 * MINIMAP_SCALE);
+            style.height = Val::Px(size.y as f32 * MINIMAP_SCALE);
+        }
+    }
+
+    let mut room_tiles: HashMap<IVec2, (RoomKind, bool)> = HashMap::new();
+    for room in layout.rooms.iter() {
+        for x in 0..room.size.x {
+            for y in 0..room.size.y {
+                room_tiles.insert(room.origin + IVec2::new(x, y), (room.kind, room.shrine));
+            }
+        }
+    }
+
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            let tile = IVec2::new(x, y);
+            let color = if !exploration.explored.contains(&tile) {
+                UNEXPLORED_COLOR
+            } else if layout.walls.contains(&tile) {
+                WALL_COLOR
+            } else if layout.floors.contains(&tile) {
+                match room_tiles.get(&tile) {
+                    Some((RoomKind::Start, _)) => START_ROOM_COLOR,
+                    Some((RoomKind::Boss, _)) => BOSS_ROOM_COLOR,
+                    Some((RoomKind::End, _)) => END_ROOM_COLOR,
+                    Some((_, true)) => SHRINE_ROOM_COLOR,
+                    _ => FLOOR_COLOR,
+                }
+            } else {
+                UNEXPLORED_COLOR
+            };
+            // image rows run top to bottom, tile y runs bottom to top
+            let px = (x - min.x) as u32;
+            let py = (max.y - y) as u32;
+            let i = ((py * size.x + px) * 4) as usize;
+            image.data[i..i + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+fn spawn_marker(parent: &mut ChildBuilder, tile: IVec2, min: IVec2, max: IVec2, color: Color) {
+    parent.spawn((
+        MinimapMarker,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px((tile.x - min.x) as f32 * MINIMAP_SCALE + (MINIMAP_SCALE - MARKER_SIZE) / 2.),
+                top: Val::Px((max.y - tile.y) as f32 * MINIMAP_SCALE + (MINIMAP_SCALE - MARKER_SIZE) / 2.),
+                width: Val::Px(MARKER_SIZE),
+                height: Val::Px(MARKER_SIZE),
+                ..default()
+            },
+            background_color: BackgroundColor(color),
+            ..default()
+        },
+    ));
+}
+
+// player, remaining/defeated enemies and the exit door on top of the explored tiles
+fn draw_minimap_markers(
+    mut commands: Commands,
+    map: MapData,
+    tiles_node: Query<Entity, With<MinimapTiles>>,
+    markers: Query<Entity, With<MinimapMarker>>,
+    player: Query<&Transform, With<Player>>,
+    enemies: Query<&Transform, With<Enemy>>,
+) {
+    let Ok(tiles_entity) = tiles_node.get_single() else {
+        return;
+    };
+    let Ok(pt) = player.get_single() else {
+        return;
+    };
+    for entity in markers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let MapData { layout, exploration, cleared, settings, .. } = map;
+    let (min, max) = layout.tile_bounds();
+    let palette = settings.palette();
+    commands.entity(tiles_entity).with_children(|parent| {
+        for room_index in cleared.0.iter() {
+            if let Some(room) = layout.rooms.get(*room_index) {
+                spawn_marker(parent, room.enemy_tile(), min, max, Color::srgb(0.35, 0.35, 0.35));
+            }
+        }
+        for et in enemies.iter() {
+            let tile = world_to_tile(et.translation);
+            if exploration.explored.contains(&tile) {
+                spawn_marker(parent, tile, min, max, palette.enemy);
+            }
+        }
+        if let Some(door) = layout.door {
+            if exploration.explored.contains(&door) {
+                spawn_marker(parent, door, min, max, Color::srgb(1.0, 0.85, 0.0));
+            }
+        }
+        spawn_marker(parent, world_to_tile(pt.translation), min, max, palette.player);
+    });
+}
+
+fn show_minimap(
+    mut commands: Commands,
+    query: Query<Entity, With<Minimap>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(Visibility::Visible);
+    }
+}
+
+fn hide_minimap(
+    mut commands: Commands,
+    query: Query<Entity, With<Minimap>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(Visibility::Hidden);
+    }
+}
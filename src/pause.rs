@@ -0,0 +1,395 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use crate::{GameState, PauseState};
+use crate::controls::{Action, ActionState, ReadInput};
+use crate::events::{SaveGameEvent, GameSavedEvent, LoadGameEvent, NewRunEvent};
+use crate::locale::{Locale, Localized};
+use crate::menu::{BUTTON_COLOR, button_color, label_color, pick_button};
+
+#[derive(Clone, Copy, PartialEq)]
+enum PauseOption {
+    Resume,
+    Settings,
+    Save,
+    Load,
+    QuitToTitle,
+    QuitToDesktop,
+}
+
+impl PauseOption {
+    const ALL: [PauseOption; 6] = [
+        PauseOption::Resume,
+        PauseOption::Settings,
+        PauseOption::Save,
+        PauseOption::Load,
+        PauseOption::QuitToTitle,
+        PauseOption::QuitToDesktop,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            PauseOption::Resume => "pause-resume",
+            PauseOption::Settings => "pause-settings",
+            PauseOption::Save => "pause-save",
+            PauseOption::Load => "pause-load",
+            PauseOption::QuitToTitle => "pause-quit-title",
+            PauseOption::QuitToDesktop => "pause-quit-desktop",
+        }
+    }
+
+    // saving and loading leave the battle behind, so they wait until it is over
+    fn enabled(&self, state: &GameState) -> bool {
+        match self {
+            PauseOption::Save | PauseOption::Load => *state == GameState::InGame,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Component)]    // root ui node of the pause overlay
+struct PauseUI;
+
+#[derive(Component)]
+struct PauseButton {
+    option: PauseOption,
+    index: usize,
+    enabled: bool,
+}
+
+#[derive(Component)]
+struct PauseLabel;
+
+#[derive(Component)]    // a line under the buttons, e.g. to say the game was saved
+struct PauseStatus;
+
+// the button Up/Down or the mouse last moved to
+#[derive(Resource, Default)]
+struct PauseFocus(usize);
+
+// the option picked this frame, carried out by apply_pause_option
+#[derive(Resource, Default)]
+struct PausePick(Option<PauseOption>);
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PauseFocus>();
+        app.init_resource::<PausePick>();
+        // the overlay gets the input first and nothing underneath sees it while paused
+        app.add_systems(PreUpdate, open_pause_menu.after(ReadInput).run_if(in_state(PauseState::Running)));
+        app.add_systems(PreUpdate, (pick_pause_option, apply_pause_option).chain().after(ReadInput).run_if(in_state(PauseState::Paused)));
+        app.add_systems(Update, (style_pause_menu, show_save_result).run_if(in_state(PauseState::Paused)));
+        app.add_systems(OnEnter(PauseState::Paused), spawn_pause_menu);
+        app.add_systems(OnExit(PauseState::Paused), despawn_pause_menu);
+        // time stays frozen on the settings screen too
+        app.add_systems(OnExit(PauseState::Running), freeze_time);
+        app.add_systems(OnEnter(PauseState::Running), thaw_time);
+    }
+}
+
+// Escape or Start pauses, but only out in the dungeon or in a battle
+fn open_pause_menu(
+    mut actions: ResMut<ActionState>,
+    state: Res<State<GameState>>,
+    mut next_pause: ResMut<NextState<PauseState>>,
+    mut focus: ResMut<PauseFocus>,
+) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+    match state.get() {
+        GameState::InGame | GameState::BattleMode => {
+            focus.0 = 0;
+            next_pause.set(PauseState::Paused);
+            actions.clear();
+        }
+        GameState::Welcome
+        | GameState::SkillTreeMenu
+        | GameState::EndCredits
+        | GameState::DefeatScreen
+        | GameState::MapView
+        | GameState::Journal
+        | GameState::Controls => {}
+    }
+}
+
+// gameplay runs on virtual time, so timers, chasing enemies and battle phases all stop where they are
+fn freeze_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn thaw_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn spawn_pause_menu(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    locale: Res<Locale>,
+) {
+    commands.spawn((
+        PauseUI,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+            // keeps clicks away from the battle menu underneath
+            focus_policy: FocusPolicy::Block,
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.),
+                padding: UiRect::all(Val::Px(30.)),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+            ..default()
+        })
+        .with_children(|panel| {
+            panel.spawn((
+                Localized::new("pause-title"),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 48.,
+                        color: Color::srgb(1., 1., 0.),
+                        ..default()
+                    },
+                ),
+            ));
+            for (index, option) in PauseOption::ALL.iter().enumerate() {
+                panel.spawn((
+                    PauseButton { option: *option, index, enabled: option.enabled(state.get()) },
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(260.),
+                            padding: UiRect::axes(Val::Px(12.), Val::Px(6.)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: BackgroundColor(BUTTON_COLOR),
+                        ..default()
+                    },
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        PauseLabel,
+                        Localized::new(option.key()),
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 28.,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ),
+                    ));
+                });
+            }
+            panel.spawn((
+                PauseStatus,
+                TextBundle::from_section(
+                    if *state.get() == GameState::InGame { String::new() } else { locale.text("pause-battle-note") },
+                    TextStyle {
+                        font_size: 20.,
+                        color: Color::srgb(0.7, 0.7, 0.7),
+                        ..default()
+                    },
+                ),
+            ));
+        });
+    });
+}
+
+fn despawn_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Up/Down or hovering moves the focus, Confirm or a click picks, Back resumes; then the input is used up
+fn pick_pause_option(
+    mut actions: ResMut<ActionState>,
+    mut focus: ResMut<PauseFocus>,
+    mut pick: ResMut<PausePick>,
+    buttons: Query<(&PauseButton, Ref<Interaction>)>,
+) {
+    let picked = pick_button(&mut focus.0, PauseOption::ALL.len(), &actions, buttons.iter().map(|(button, interaction)| (button.index, interaction)))
+        .and_then(|index| buttons.iter().map(|(button, _)| button).find(|button| button.index == index));
+    pick.0 = match picked {
+        Some(button) if button.enabled => Some(button.option),
+        _ if actions.just_pressed(Action::Back) => Some(PauseOption::Resume),
+        _ => None,
+    };
+    actions.clear();
+}
+
+// says whether the save went through once write_save has tried it
+fn show_save_result(
+    mut saved_events: EventReader<GameSavedEvent>,
+    mut status: Query<&mut Text, With<PauseStatus>>,
+    locale: Res<Locale>,
+) {
+    let Some(event) = saved_events.read().last() else {
+        return;
+    };
+    let line = locale.text(if event.saved { "pause-saved" } else { "pause-save-failed" });
+    for mut text in status.iter_mut() {
+        text.sections[0].value = line.clone();
+    }
+}
+
+fn apply_pause_option(
+    mut pick: ResMut<PausePick>,
+    mut next_pause: ResMut<NextState<PauseState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut save_events: EventWriter<SaveGameEvent>,
+    mut load_events: EventWriter<LoadGameEvent>,
+    mut new_run_events: EventWriter<NewRunEvent>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    let Some(option) = pick.0.take() else {
+        return;
+    };
+    match option {
+        PauseOption::Resume => next_pause.set(PauseState::Running),
+        PauseOption::Settings => next_pause.set(PauseState::Settings),
+        // stays paused so the player can see it worked
+        PauseOption::Save => {
+            save_events.send(SaveGameEvent);
+        }
+        PauseOption::Load => {
+            load_events.send(LoadGameEvent);
+            next_pause.set(PauseState::Running);
+        }
+        // anything not saved is gone, the title screen leads into a fresh run
+        PauseOption::QuitToTitle => {
+            new_run_events.send(NewRunEvent);
+            next_pause.set(PauseState::Running);
+            next_state.set(GameState::Welcome);
+        }
+        PauseOption::QuitToDesktop => {
+            exit_events.send(AppExit::Success);
+        }
+    }
+}
+
+fn style_pause_menu(
+    focus: Res<PauseFocus>,
+    mut buttons: Query<(&PauseButton, &Children, &mut BackgroundColor)>,
+    mut labels: Query<&mut Text, With<PauseLabel>>,
+) {
+    for (button, children, mut background) in buttons.iter_mut() {
+        *background = button_color(button.index == focus.0);
+        for child in children.iter() {
+            if let Ok(mut label) = labels.get_mut(*child) {
+                label.sections[0].style.color = label_color(button.enabled);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::state::app::StatesPlugin;
+    use crate::locale::Language;
+
+    fn test_app(state: GameState) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin));
+        app.init_state::<GameState>();
+        app.init_state::<PauseState>();
+        app.init_resource::<ActionState>();
+        app.insert_resource(Locale::new(Language::English));
+        app.add_event::<SaveGameEvent>();
+        app.add_event::<GameSavedEvent>();
+        app.add_event::<LoadGameEvent>();
+        app.add_event::<NewRunEvent>();
+        app.add_plugins(PausePlugin);
+        app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+        app.update();
+        app
+    }
+
+    // the press is read on the next update and the state change lands on the one after
+    fn press(app: &mut App, actions: &[Action]) {
+        for action in actions {
+            app.world_mut().resource_mut::<ActionState>().press(*action, true);
+        }
+        app.update();
+        app.update();
+    }
+
+    fn pause_state(app: &App) -> PauseState {
+        app.world().resource::<State<PauseState>>().get().clone()
+    }
+
+    fn status(app: &mut App) -> String {
+        let mut query = app.world_mut().query_filtered::<&Text, With<PauseStatus>>();
+        query.single(app.world()).sections[0].value.clone()
+    }
+
+    #[test]
+    fn pausing_freezes_time_until_resumed() {
+        let mut app = test_app(GameState::InGame);
+        press(&mut app, &[Action::Pause]);
+        assert_eq!(pause_state(&app), PauseState::Paused);
+        assert!(app.world().resource::<Time<Virtual>>().is_paused());
+
+        press(&mut app, &[Action::Back]);
+        assert_eq!(pause_state(&app), PauseState::Running);
+        assert!(!app.world().resource::<Time<Virtual>>().is_paused());
+    }
+
+    #[test]
+    fn menus_and_cutscenes_cant_be_paused() {
+        let mut app = test_app(GameState::SkillTreeMenu);
+        press(&mut app, &[Action::Pause]);
+        assert_eq!(pause_state(&app), PauseState::Running);
+        assert!(!app.world().resource::<Time<Virtual>>().is_paused());
+    }
+
+    #[test]
+    fn saved_is_only_shown_once_the_save_is_written() {
+        let mut app = test_app(GameState::InGame);
+        press(&mut app, &[Action::Pause]);
+        press(&mut app, &[Action::Down]);
+        press(&mut app, &[Action::Down, Action::Confirm]);
+        assert_eq!(app.world().resource::<Events<SaveGameEvent>>().len(), 1);
+        assert_eq!(status(&mut app), "");
+        assert_eq!(pause_state(&app), PauseState::Paused);
+
+        app.world_mut().send_event(GameSavedEvent { saved: false });
+        app.update();
+        assert_eq!(status(&mut app), "Could not save the game");
+        app.world_mut().send_event(GameSavedEvent { saved: true });
+        app.update();
+        assert_eq!(status(&mut app), "Game saved");
+    }
+
+    #[test]
+    fn saving_waits_until_the_battle_is_over() {
+        let mut app = test_app(GameState::BattleMode);
+        press(&mut app, &[Action::Pause]);
+        press(&mut app, &[Action::Down]);
+        press(&mut app, &[Action::Down, Action::Confirm]);
+        assert!(app.world().resource::<Events<SaveGameEvent>>().is_empty());
+        assert_eq!(pause_state(&app), PauseState::Paused);
+    }
+}
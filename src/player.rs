@@ -1,18 +1,19 @@
 use bevy::prelude::*;
-use crate::dungeon::{Wall, Door};
+use serde::{Deserialize, Serialize};
+use crate::dungeon::{Wall, Door, LevelBounds};
+use crate::boss::Sealed;
+use crate::element::Element;
+use crate::controls::{Action, ActionState};
 use crate::enemy::Enemy;
 use crate::events::{EnemyCollisionEvent, EndGameEvent};
+use crate::defeat::Checkpoint;
 use crate::GameState;
-use crate::{WIN_W, WIN_H}; 
 
 const TILE_SIZE: u32 = 144;
 
 const PLAYER_SPEED: f32 = 500.;
 const ACCEL_RATE: f32 = 5000.;
 
-pub const LEVEL_W: f32 = 16200.;
-pub const LEVEL_H: f32 = 16000.;
-
 const ANIM_TIME: f32 = 0.2;
 enum PlayerType {
     Character,
@@ -49,8 +50,8 @@ impl From<Vec3> for Sides {
 }
 
 #[derive(Component)]
-struct Velocity {
-    velocity: Vec2,
+pub struct Velocity {
+    pub velocity: Vec2,
 }
 
 impl Velocity {
@@ -72,13 +73,12 @@ impl Plugin for PlayerPlugin{
     fn build(&self, app: &mut App){
         app.add_systems(Startup, init_player)
         .add_systems(Update, move_player.run_if(in_state(GameState::InGame)))
-        .add_systems(Update, animate_player.after(move_player))
-        .add_systems(Update, move_camera.after(move_player).run_if(in_state(GameState::InGame)));
+        .add_systems(Update, animate_player.after(move_player));
     }
 
 }
     
-    #[derive(Component)]
+    #[derive(Component, Clone, Serialize, Deserialize)]
     pub struct PlayerStats {
         pub atk: u32,
         pub def: u32,
@@ -96,6 +96,13 @@ impl Plugin for PlayerPlugin{
         pub health: u32,
 
         pub next_action_tick: u32,
+
+        #[serde(default)]
+        pub spells: Vec<Element>,   // elements learned from the skill tree, in the order they were unlocked
+        #[serde(default)]
+        pub guarding: bool,     // the next hit taken is softened
+        #[serde(default)]
+        pub focus: u32,         // built by guarding, spent on the next attack
     }
 
     impl PlayerStats {
@@ -115,6 +122,9 @@ impl Plugin for PlayerPlugin{
                 agility: 1,
                 health: 1,
                 next_action_tick: 0,
+                spells: Vec::new(),
+                guarding: false,
+                focus: 0,
             }
         }
 
@@ -130,7 +140,7 @@ impl Plugin for PlayerPlugin{
             self.hp += amt;
         }
 
-        pub fn update_stats(&mut self, bonus: Mut<BonusStats>) {
+        pub fn update_stats(&mut self, bonus: &BonusStats) {
             self.atk = 5 * self.strength + bonus.atk;
             self.def = 5 * self.strength + bonus.def;
             self.matk = 5 * self.magic + bonus.matk;
@@ -140,7 +150,7 @@ impl Plugin for PlayerPlugin{
         }
     }
 
-    #[derive(Component)]
+    #[derive(Component, Clone, Serialize, Deserialize)]
     pub struct BonusStats {
         pub atk: u32,
         pub def: u32,
@@ -192,12 +202,13 @@ pub fn init_player(
         Player,
             PlayerStats::new(),
             BonusStats::new(),
+            Checkpoint::default(),
     ));
 }
 
 fn animate_player(
     time: Res<Time>,
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut player: Query<
         (
             &Velocity,
@@ -212,16 +223,16 @@ fn animate_player(
     let mut counter: usize = 0;
     let mut direction = 8;
 
-    if input.pressed(KeyCode::KeyD) { //move right
+    if actions.pressed(Action::Right) { //move right
         direction = 0;
     }
-    if input.pressed(KeyCode::KeyA) { //move left
+    if actions.pressed(Action::Left) { //move left
         direction = 4;
     }
-    if input.pressed(KeyCode::KeyS) { //move down
+    if actions.pressed(Action::Down) { //move down
         direction = 8;
     }
-    if input.pressed(KeyCode::KeyW) { //move up
+    if actions.pressed(Action::Up) { //move up
         direction = 12;
     }
    
@@ -235,14 +246,17 @@ fn animate_player(
     }
 }
 
+// where a door is and whether the boss still keeps it shut
+type DoorCollider = (&'static Transform, Has<Sealed>);
 
 fn move_player(
     time: Res<Time>,
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
+    bounds: Res<LevelBounds>,
     //mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
     wall_query: Query<&Transform, (With<Wall>, Without<Player>)>,
     enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
-    door_query: Query<&Transform, (With<Door>, Without<Player>)>,
+    door_query: Query<DoorCollider, (With<Door>, Without<Player>)>,
     mut player: Query<(&mut Transform, &mut Velocity, &mut TextureAtlas), (With<Player>, Without<Background>)>,
     mut enemy_event_writer: EventWriter<EnemyCollisionEvent>,
     mut end_event_writer: EventWriter<EndGameEvent>,
@@ -252,27 +266,36 @@ fn move_player(
     let mut deltav = Vec2::splat(0.);
 
 
-    if input.pressed(KeyCode::KeyA) {
+    if actions.pressed(Action::Left) {
         deltav.x -= 1.;
     }
 
-    if input.pressed(KeyCode::KeyD) {
+    if actions.pressed(Action::Right) {
         deltav.x += 1.;
     }
 
-    if input.pressed(KeyCode::KeyW) {
+    if actions.pressed(Action::Up) {
         deltav.y += 1.;
     }
 
-    if input.pressed(KeyCode::KeyS) {
+    if actions.pressed(Action::Down) {
         deltav.y -= 1.;
     }
 
+    // a tilted stick takes over from the keys, and a half tilt only walks
+    let tilt = actions.movement();
+    let top_speed = if tilt != Vec2::ZERO {
+        deltav = tilt;
+        PLAYER_SPEED * tilt.length()
+    } else {
+        PLAYER_SPEED
+    };
+
     let deltat = time.delta_seconds();
     let acc = ACCEL_RATE * deltat;
 
     pv.velocity = if deltav.length() > 0. {
-        (pv.velocity + (deltav.normalize_or_zero() * acc)).clamp_length_max(PLAYER_SPEED)
+        (pv.velocity + (deltav.normalize_or_zero() * acc)).clamp_length_max(top_speed)
     } else if pv.velocity.length() > acc {
         pv.velocity + (pv.velocity.normalize_or_zero() * -acc)
     } else {
@@ -282,9 +305,7 @@ fn move_player(
 
     let new_pos = pt.translation + Vec3::new(change.x, 0., 0.);
     
-    if new_pos.x >= -(LEVEL_W / 2.) + (TILE_SIZE as f32) / 2.
-        && new_pos.x <= LEVEL_W / 2. - (TILE_SIZE as f32) / 2.
-    {
+    if bounds.contains(new_pos, TILE_SIZE as f32) {
         //check collision
         if !check_wall_collision(new_pos, &wall_query) && !check_enemy_collision(new_pos, &enemy_query, &mut enemy_event_writer) &&
         !check_door_collision(new_pos, &door_query, &mut end_event_writer){
@@ -293,9 +314,7 @@ fn move_player(
     }
 
     let new_pos = pt.translation + Vec3::new(0., change.y, 0.);
-    if new_pos.y >= -(LEVEL_H / 2.) + (TILE_SIZE as f32) / 2.
-        && new_pos.y <= LEVEL_H / 2. - (TILE_SIZE as f32) / 2.
-    {
+    if bounds.contains(new_pos, TILE_SIZE as f32) {
          //check collision
          if !check_wall_collision(new_pos, &wall_query) && !check_enemy_collision(new_pos, &enemy_query, &mut enemy_event_writer) && 
          !check_door_collision(new_pos, &door_query, &mut end_event_writer){
@@ -336,29 +355,20 @@ fn check_enemy_collision(
 
 fn check_door_collision(
     new_pos: Vec3,
-    collider_query: &Query<&Transform, (With<Door>, Without<Player>)>,
+    collider_query: &Query<DoorCollider, (With<Door>, Without<Player>)>,
     mut collision_events: &mut EventWriter<EndGameEvent>,
 ) -> bool {
-    for collider_transform in collider_query.iter() {
+    for (collider_transform, sealed) in collider_query.iter() {
         let a: Sides = new_pos.into();
         let b: Sides = collider_transform.translation.into();
         if a.bottom <= b.top && a.top >= b.bottom && a.right >= b.left && a.left <= b.right {
-            collision_events.send(EndGameEvent);
+            // a sealed door is just a wall until the boss is beaten
+            if !sealed {
+                collision_events.send(EndGameEvent);
+            }
             return true;
         }
     }
     return false;
 }
-
-fn move_camera(
-    player: Query<&Transform, With<Player>>,
-    mut camera: Query<&mut Transform, (Without<Player>, With<Camera>)>,
-) {
-    let pt = player.single();
-    let mut ct = camera.single_mut();
-
-    let x_bound = LEVEL_W / 2. - WIN_W / 2.;
-    let y_bound = LEVEL_H / 2. - WIN_H / 2.;
-    ct.translation.x = pt.translation.x.clamp(-x_bound, x_bound);
-    ct.translation.y = pt.translation.y.clamp(-y_bound, y_bound);
-}    
\ No newline at end of file
+    
\ No newline at end of file
@@ -0,0 +1,397 @@
+use std::fs;
+use std::path::Path;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::GameState;
+use crate::BattleState;
+use crate::player::{Player, PlayerStats};
+use crate::enemy::{Enemy, EnemyStats, spawn_enemy, find_closest_enemy};
+use crate::brain::brain_for;
+use crate::utility::UtilityConfig;
+use crate::turn_order::{player_acts_next, start_turn_order};
+use crate::battle::{BattleRng, PlayerAction, TurnResult, battle_input, player_turn, enemy_turn};
+use crate::battle_log::BattleLog;
+use crate::events::PlayerActionEvent;
+use crate::controls::{Action, ActionState};
+
+const RECORDING_DIR: &str = "recordings";
+const LAST_RECORDING: &str = "recordings/last_battle.ron";
+const REPLAY_STEP: f32 = 1.0;   // seconds between replayed actions
+
+// bump whenever the combat math changes; a recording only replays under the rules it was played with
+pub const RULES_VERSION: u32 = 2;
+
+// One battle as played: the starting stats, the BattleRng seed and every action the player took.
+// The final hp values let a replay check it ended up in the same place.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BattleRecording {
+    #[serde(default)]   // recordings from before the rules were versioned read as 0
+    pub version: u32,
+    pub seed: u64,
+    pub player: PlayerStats,
+    pub enemy: EnemyStats,
+    pub actions: Vec<PlayerAction>,
+    pub final_player_hp: u32,
+    pub final_enemy_hp: u32,
+}
+
+// the recording of the battle in progress
+#[derive(Resource, Default)]
+pub struct BattleRecorder {
+    recording: Option<BattleRecording>,
+}
+
+impl BattleRecorder {
+    pub fn record(&mut self, action: PlayerAction, player_stats: &PlayerStats, enemy_stats: &EnemyStats) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.actions.push(action);
+        }
+        self.update_hp(player_stats, enemy_stats);
+    }
+
+    pub fn update_hp(&mut self, player_stats: &PlayerStats, enemy_stats: &EnemyStats) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.final_player_hp = player_stats.hp;
+            recording.final_enemy_hp = enemy_stats.hp;
+        }
+    }
+}
+
+// a recorded battle being played back in the fight scene
+#[derive(Resource)]
+pub struct BattleReplay {
+    recording: Option<BattleRecording>,
+    next: usize,
+    timer: Timer,
+    saved_player: Option<PlayerStats>,  // the real player's stats, put back when the replay ends
+    enemy: Option<Entity>,
+}
+
+impl BattleReplay {
+    pub fn active(&self) -> bool {
+        self.recording.is_some()
+    }
+}
+
+impl Default for BattleReplay {
+    fn default() -> Self {
+        Self {
+            recording: None,
+            next: 0,
+            timer: Timer::from_seconds(REPLAY_STEP, TimerMode::Repeating),
+            saved_player: None,
+            enemy: None,
+        }
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BattleRecorder>();
+        app.init_resource::<BattleReplay>();
+        app.add_systems(OnEnter(GameState::BattleMode), begin_battle);
+        app.add_systems(OnExit(GameState::BattleMode), finish_battle);
+        app.add_systems(Update, start_replay.run_if(in_state(GameState::InGame)));
+        app.add_systems(Update, drive_replay.before(battle_input).run_if(in_state(BattleState::ChooseAction)));
+    }
+}
+
+// replays a recording without any of the game running, e.g. from tests
+pub fn replay_battle(recording: &BattleRecording, config: &UtilityConfig) -> (PlayerStats, EnemyStats, BattleLog) {
+    let mut rng = StdRng::seed_from_u64(recording.seed);
+    let mut player_stats = recording.player.clone();
+    let mut enemy_stats = recording.enemy.clone();
+    let mut brain = brain_for(recording.enemy.etype, config);
+    let mut battle_log = BattleLog::default();
+    battle_log.start_battle();
+    start_turn_order(&mut player_stats, &mut enemy_stats);
+
+    for action in recording.actions.iter() {
+        if player_stats.hp == 0 {
+            break;
+        }
+        match player_turn(*action, &mut player_stats, &mut enemy_stats, &mut rng, &mut battle_log) {
+            TurnResult::Continue => {
+                while player_stats.hp > 0 && !player_acts_next(&player_stats, &enemy_stats) {
+                    enemy_turn(&mut player_stats, &mut enemy_stats, brain.as_mut(), &mut rng, &mut battle_log);
+                }
+            }
+            TurnResult::Victory | TurnResult::Fled => break,
+        }
+    }
+    (player_stats, enemy_stats, battle_log)
+}
+
+pub fn load_recording(path: &str) -> Result<BattleRecording, String> {
+    fs::read_to_string(path).map_err(|e| e.to_string())
+        .and_then(|text| parse_recording(&text))
+}
+
+// a recording made under other combat rules would play out differently, so it is refused
+pub fn parse_recording(text: &str) -> Result<BattleRecording, String> {
+    let recording: BattleRecording = ron::from_str(text).map_err(|e| e.to_string())?;
+    if recording.version != RULES_VERSION {
+        return Err(format!("recorded under rules version {}, this build plays version {}", recording.version, RULES_VERSION));
+    }
+    Ok(recording)
+}
+
+// the player and the enemy they are fighting, found the same way the battle finds them
+#[derive(SystemParam)]
+struct Combatants<'w, 's> {
+    commands: Commands<'w, 's>,
+    player_stats: Query<'w, 's, &'static PlayerStats, With<Player>>,
+    enemy_stats: Query<'w, 's, &'static EnemyStats, With<Enemy>>,
+    enemy_query: Query<'w, 's, (Entity, &'static Transform), With<Enemy>>,
+    player_query: Query<'w, 's, &'static Transform, With<Player>>,
+}
+
+impl Combatants<'_, '_> {
+    fn stats(&self) -> Option<(&PlayerStats, &EnemyStats)> {
+        let closest_enemy = find_closest_enemy(&self.commands, &self.enemy_query, &self.player_query)?;
+        Some((self.player_stats.get_single().ok()?, self.enemy_stats.get(closest_enemy).ok()?))
+    }
+}
+
+// seeds BattleRng for the new battle and starts recording it (unless it is itself a replay)
+fn begin_battle(
+    mut battle_rng: ResMut<BattleRng>,
+    mut recorder: ResMut<BattleRecorder>,
+    mut replay: ResMut<BattleReplay>,
+    combatants: Combatants,
+) {
+    if let Some(recording) = replay.recording.as_ref() {
+        battle_rng.0 = StdRng::seed_from_u64(recording.seed);
+        replay.next = 0;
+        replay.timer.reset();
+        return;
+    }
+
+    let seed: u64 = random();
+    battle_rng.0 = StdRng::seed_from_u64(seed);
+    let Some((player, enemy)) = combatants.stats() else {
+        return;
+    };
+    recorder.recording = Some(BattleRecording {
+        version: RULES_VERSION,
+        seed,
+        player: player.clone(),
+        enemy: enemy.clone(),
+        actions: Vec::new(),
+        final_player_hp: player.hp,
+        final_enemy_hp: enemy.hp,
+    });
+}
+
+// writes the finished battle to disk, or cleans up after a replay
+fn finish_battle(
+    mut commands: Commands,
+    mut recorder: ResMut<BattleRecorder>,
+    mut replay: ResMut<BattleReplay>,
+    mut player_stats: Query<&mut PlayerStats, With<Player>>,
+    enemies: Query<Entity, With<Enemy>>,
+) {
+    if replay.active() {
+        if let (Some(saved), Ok(mut stats)) = (replay.saved_player.take(), player_stats.get_single_mut()) {
+            *stats = saved;
+        }
+        if let Some(entity) = replay.enemy.take() {
+            if enemies.get(entity).is_ok() {
+                commands.entity(entity).despawn();
+            }
+        }
+        replay.recording = None;
+        info!("Replay finished");
+        return;
+    }
+
+    let Some(recording) = recorder.recording.take() else {
+        return;
+    };
+    let text = match ron::ser::to_string_pretty(&recording, ron::ser::PrettyConfig::default()) {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("Could not serialize battle recording: {}", e);
+            return;
+        }
+    };
+    // every battle is kept by seed, and the latest one is what F8 replays
+    let path = format!("{}/battle_{}.ron", RECORDING_DIR, recording.seed);
+    if let Err(e) = fs::create_dir_all(RECORDING_DIR)
+        .and_then(|_| fs::write(&path, &text))
+        .and_then(|_| fs::write(LAST_RECORDING, &text))
+    {
+        warn!("Could not write {}: {}", path, e);
+        return;
+    }
+    info!("Battle recorded to {}", path);
+}
+
+// F8 plays the last recorded battle back in the fight scene
+fn start_replay(
+    actions: Res<ActionState>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
+    mut replay: ResMut<BattleReplay>,
+    mut player: Query<(&Transform, &mut PlayerStats), With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !actions.just_pressed(Action::Replay) {
+        return;
+    }
+    if !Path::new(LAST_RECORDING).exists() {
+        info!("No battle recording found at {}", LAST_RECORDING);
+        return;
+    }
+    let recording = match load_recording(LAST_RECORDING) {
+        Ok(recording) => recording,
+        Err(e) => {
+            warn!("Could not load {}: {}", LAST_RECORDING, e);
+            return;
+        }
+    };
+    let Ok((pt, mut player_stats)) = player.get_single_mut() else {
+        return;
+    };
+
+    // the recorded enemy stands right on the player so it is the one the battle picks
+    let enemy = spawn_enemy(&mut commands, &asset_server, &mut texture_atlases, pt.translation + Vec3::new(1., 0., 0.), recording.enemy.etype, usize::MAX);
+    commands.entity(enemy).insert(recording.enemy.clone());
+    replay.saved_player = Some(player_stats.clone());
+    *player_stats = recording.player.clone();
+    replay.enemy = Some(enemy);
+    replay.recording = Some(recording);
+
+    next_state.set(GameState::BattleMode);
+    info!("Replaying {}", LAST_RECORDING);
+}
+
+// feeds the recorded actions into the battle one at a time
+fn drive_replay(
+    time: Res<Time>,
+    mut replay: ResMut<BattleReplay>,
+    mut action_events: EventWriter<PlayerActionEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !replay.active() {
+        return;
+    }
+    replay.timer.tick(time.delta());
+    if !replay.timer.just_finished() {
+        return;
+    }
+    let next = replay.next;
+    let action = replay.recording.as_ref().and_then(|recording| recording.actions.get(next).copied());
+    match action {
+        Some(action) => {
+            action_events.send(PlayerActionEvent(action));
+            replay.next += 1;
+        }
+        // the recording ran out before the battle ended
+        None => next_state.set(GameState::InGame),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::{Language, Locale};
+
+    fn recording(seed: u64, etype: u32, actions: Vec<PlayerAction>) -> BattleRecording {
+        let player = PlayerStats::new();
+        let enemy = EnemyStats::new(etype);
+        BattleRecording {
+            version: RULES_VERSION,
+            seed,
+            final_player_hp: player.hp,
+            final_enemy_hp: enemy.hp,
+            player,
+            enemy,
+            actions,
+        }
+    }
+
+    #[test]
+    fn replay_is_deterministic() {
+        let actions = vec![PlayerAction::Attack, PlayerAction::Magic, PlayerAction::Heal, PlayerAction::Attack, PlayerAction::Attack];
+        let first = replay_battle(&recording(42, 1, actions.clone()), &UtilityConfig::default());
+        let second = replay_battle(&recording(42, 1, actions), &UtilityConfig::default());
+
+        assert_eq!(first.0.hp, second.0.hp);
+        assert_eq!(first.1.hp, second.1.hp);
+        let locale = Locale::new(Language::English);
+        let lines = |log: &BattleLog| log.entries.iter().map(|entry| entry.describe(&locale)).collect::<Vec<_>>();
+        assert_eq!(lines(&first.2), lines(&second.2));
+    }
+
+    #[test]
+    fn recording_round_trips_through_ron() {
+        let original = recording(7, 2, vec![PlayerAction::Attack, PlayerAction::Wait, PlayerAction::Run]);
+        let text = ron::ser::to_string_pretty(&original, ron::ser::PrettyConfig::default()).unwrap();
+        let loaded = parse_recording(&text).unwrap();
+
+        assert_eq!(loaded.seed, original.seed);
+        assert_eq!(loaded.actions, original.actions);
+        assert_eq!(replay_battle(&loaded, &UtilityConfig::default()).1.hp, replay_battle(&original, &UtilityConfig::default()).1.hp);
+    }
+
+    #[test]
+    fn replay_stops_when_the_player_runs() {
+        let (_, _, battle_log) = replay_battle(&recording(3, 1, vec![PlayerAction::Run, PlayerAction::Attack]), &UtilityConfig::default());
+        // battle start and the run, the attack after it never happens
+        assert_eq!(battle_log.entries.len(), 2);
+    }
+
+    // A synthetic regression snapshot, not a played session: the final hp values are whatever
+    // replay_battle gave when the rules last changed. If the combat math changes on purpose,
+    // bump RULES_VERSION and update them here.
+    const SNAPSHOT_BATTLE: &str = "(
+        version: 2,
+        seed: 1234,
+        player: (atk: 2, def: 1, matk: 1, mdef: 1, spd: 1, max_hp: 40, hp: 40, skill_points: 0, ability_points: 0,
+            strength: 3, magic: 2, agility: 1, health: 1, next_action_tick: 0),
+        enemy: (physatk: 1, physdef: 1, mgkatk: 1, mgkdef: 1, speed: 1, max_hp: 25, hp: 25, etype: 1, next_action_tick: 0),
+        actions: [Attack, Attack, Heal, Magic, Attack, Attack, Attack, Attack],
+        final_player_hp: 26,
+        final_enemy_hp: 0,
+    )";
+
+    #[test]
+    fn snapshot_battle_replays_to_the_same_result() {
+        let recording = parse_recording(SNAPSHOT_BATTLE).unwrap();
+        let (player_stats, enemy_stats, _) = replay_battle(&recording, &UtilityConfig::default());
+        assert_eq!(player_stats.hp, recording.final_player_hp);
+        assert_eq!(enemy_stats.hp, recording.final_enemy_hp);
+    }
+
+    #[test]
+    fn recordings_from_other_rules_are_refused() {
+        let current = format!("version: {},", RULES_VERSION);
+        let unversioned = SNAPSHOT_BATTLE.replace(&current, "");
+        assert!(parse_recording(&unversioned).is_err());
+        let newer = SNAPSHOT_BATTLE.replace(&current, &format!("version: {},", RULES_VERSION + 1));
+        assert!(parse_recording(&newer).is_err());
+    }
+
+    #[test]
+    fn recordings_from_other_rules_are_not_loaded_for_replay() {
+        let dir = std::env::temp_dir().join(format!("replay_version_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("old_battle.ron");
+        let older = SNAPSHOT_BATTLE.replace(&format!("version: {},", RULES_VERSION), &format!("version: {},", RULES_VERSION - 1));
+        fs::write(&path, older).unwrap();
+        let error = load_recording(path.to_str().unwrap()).err().unwrap();
+        assert!(error.contains("rules version"), "{}", error);
+
+        fs::write(&path, SNAPSHOT_BATTLE).unwrap();
+        assert!(load_recording(path.to_str().unwrap()).is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use rand::random;
+use serde::{Deserialize, Serialize};
+
+use crate::GameState;
+use crate::player::{Player, PlayerStats, BonusStats};
+use crate::skill_tree::SkillTreeUINode;
+use crate::dungeon::{DungeonSeed, ClearedRooms};
+use crate::minimap::Exploration;
+use crate::events::{SaveGameEvent, GameSavedEvent, LoadGameEvent, NewRunEvent, RebuildDungeonEvent};
+use crate::controls::{Action, ActionState};
+use crate::defeat::Checkpoint;
+
+const SAVE_DIR: &str = "saves";
+const SAVE_FILE: &str = "saves/savegame.ron";
+
+// Everything needed to put a run back together; the dungeon itself is rebuilt from the seed
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub seed: u64,
+    pub player_position: (f32, f32),
+    pub player_stats: PlayerStats,
+    pub bonus_stats: BonusStats,
+    pub unlocked_nodes: Vec<u32>,
+    pub cleared_rooms: Vec<usize>,
+    pub explored: Vec<(i32, i32)>,
+    #[serde(default)]
+    pub checkpoint: (f32, f32),     // where a defeat respawns the player
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, quick_save_input.run_if(in_state(GameState::InGame)));
+        app.add_systems(Update, write_save.after(quick_save_input));
+        app.add_systems(Update, read_save.after(quick_save_input));
+        app.add_systems(Update, start_new_run);
+    }
+}
+
+// F5 to quick save, F9 to quick load
+fn quick_save_input(
+    actions: Res<ActionState>,
+    mut save_events: EventWriter<SaveGameEvent>,
+    mut load_events: EventWriter<LoadGameEvent>,
+) {
+    if actions.just_pressed(Action::QuickSave) {
+        save_events.send(SaveGameEvent);
+    } else if actions.just_pressed(Action::QuickLoad) {
+        load_events.send(LoadGameEvent);
+    }
+}
+
+fn write_save(
+    mut save_events: EventReader<SaveGameEvent>,
+    mut saved_events: EventWriter<GameSavedEvent>,
+    seed: Res<DungeonSeed>,
+    cleared: Res<ClearedRooms>,
+    exploration: Res<Exploration>,
+    player: Query<(&Transform, &PlayerStats, &BonusStats, &Checkpoint), With<Player>>,
+    nodes: Query<&SkillTreeUINode>,
+) {
+    if save_events.read().count() == 0 {
+        return;
+    }
+    let Ok((pt, player_stats, bonus_stats, checkpoint)) = player.get_single() else {
+        saved_events.send(GameSavedEvent { saved: false });
+        return;
+    };
+
+    let save = SaveGame {
+        seed: seed.0,
+        player_position: (pt.translation.x, pt.translation.y),
+        player_stats: player_stats.clone(),
+        bonus_stats: bonus_stats.clone(),
+        unlocked_nodes: nodes.iter().filter(|node| node.unlocked).map(|node| node.index).collect(),
+        cleared_rooms: cleared.0.iter().copied().collect(),
+        explored: exploration.explored.iter().map(|tile| (tile.x, tile.y)).collect(),
+        checkpoint: (checkpoint.0.x, checkpoint.0.y),
+    };
+
+    let text = match ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default()) {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("Could not serialize save game: {}", e);
+            saved_events.send(GameSavedEvent { saved: false });
+            return;
+        }
+    };
+    if let Err(e) = fs::create_dir_all(SAVE_DIR).and_then(|_| fs::write(SAVE_FILE, text)) {
+        warn!("Could not write {}: {}", SAVE_FILE, e);
+        saved_events.send(GameSavedEvent { saved: false });
+        return;
+    }
+    info!("Game saved to {}", SAVE_FILE);
+    saved_events.send(GameSavedEvent { saved: true });
+}
+
+fn read_save(
+    mut load_events: EventReader<LoadGameEvent>,
+    mut rebuild_events: EventWriter<RebuildDungeonEvent>,
+    mut seed: ResMut<DungeonSeed>,
+    mut cleared: ResMut<ClearedRooms>,
+    mut exploration: ResMut<Exploration>,
+    mut player: Query<(&mut Transform, &mut PlayerStats, &mut BonusStats, &mut Checkpoint), With<Player>>,
+    mut nodes: Query<(&mut SkillTreeUINode, &mut TextureAtlas)>,
+) {
+    if load_events.read().count() == 0 {
+        return;
+    }
+    if !Path::new(SAVE_FILE).exists() {
+        info!("No save game found at {}", SAVE_FILE);
+        return;
+    }
+    let save: SaveGame = match fs::read_to_string(SAVE_FILE).map_err(|e| e.to_string())
+        .and_then(|text| ron::from_str(&text).map_err(|e| e.to_string()))
+    {
+        Ok(save) => save,
+        Err(e) => {
+            warn!("Could not load {}: {}", SAVE_FILE, e);
+            return;
+        }
+    };
+
+    seed.0 = save.seed;
+    cleared.0 = save.cleared_rooms.into_iter().collect();
+    exploration.explored = save.explored.into_iter().map(|(x, y)| IVec2::new(x, y)).collect();
+
+    if let Ok((mut pt, mut player_stats, mut bonus_stats, mut checkpoint)) = player.get_single_mut() {
+        pt.translation.x = save.player_position.0;
+        pt.translation.y = save.player_position.1;
+        *player_stats = save.player_stats;
+        *bonus_stats = save.bonus_stats;
+        *checkpoint = Checkpoint(Vec2::new(save.checkpoint.0, save.checkpoint.1));
+    }
+    for (mut node, mut texture_atlas) in nodes.iter_mut() {
+        node.unlocked = save.unlocked_nodes.contains(&node.index);
+        texture_atlas.index = if node.unlocked { 1 } else { 0 };
+    }
+
+    rebuild_events.send(RebuildDungeonEvent);
+    info!("Game loaded from {}", SAVE_FILE);
+}
+
+// a new seed and everything else back to how a run starts; the player starts again at the origin
+fn start_new_run(
+    mut new_run_events: EventReader<NewRunEvent>,
+    mut rebuild_events: EventWriter<RebuildDungeonEvent>,
+    mut seed: ResMut<DungeonSeed>,
+    mut cleared: ResMut<ClearedRooms>,
+    mut exploration: ResMut<Exploration>,
+    mut player: Query<(&mut Transform, &mut PlayerStats, &mut BonusStats, &mut Checkpoint), With<Player>>,
+    mut nodes: Query<(&mut SkillTreeUINode, &mut TextureAtlas)>,
+) {
+    if new_run_events.read().count() == 0 {
+        return;
+    }
+    seed.0 = random();
+    cleared.0.clear();
+    exploration.explored.clear();
+
+    if let Ok((mut pt, mut player_stats, mut bonus_stats, mut checkpoint)) = player.get_single_mut() {
+        pt.translation.x = 0.;
+        pt.translation.y = 0.;
+        *player_stats = PlayerStats::new();
+        *bonus_stats = BonusStats::new();
+        *checkpoint = Checkpoint::default();
+    }
+    for (mut node, mut texture_atlas) in nodes.iter_mut() {
+        node.unlocked = false;
+        texture_atlas.index = 0;
+    }
+
+    rebuild_events.send(RebuildDungeonEvent);
+    info!("Started a new run with seed {}", seed.0);
+}
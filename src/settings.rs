@@ -0,0 +1,758 @@
+use std::fs;
+
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode, WindowResized};
+use serde::{Deserialize, Serialize};
+
+use crate::{GameState, PauseState, WIN_H};
+use crate::controls::{Action, ActionState, ReadInput};
+use crate::locale::{Language, Locale, Localized};
+use crate::menu::{BUTTON_COLOR, button_color, label_color, pick_button};
+
+const CONFIG_DIR: &str = "config";
+const SETTINGS_FILE: &str = "config/settings.ron";
+
+const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+const UI_SCALES: [f32; 4] = [0.75, 1.0, 1.25, 1.5];
+const TEXT_SCALES: [f32; 4] = [1.0, 1.25, 1.5, 1.75];
+const VOLUME_STEP: f32 = 0.1;
+const SHRINE_SPACINGS: [usize; 4] = [2, 3, 4, 0];    // rooms between shrines, 0 for none
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    fn next(self, step: i32) -> Self {
+        let all = [DisplayMode::Windowed, DisplayMode::Borderless, DisplayMode::Fullscreen];
+        all[cycle(all.iter().position(|mode| *mode == self).unwrap_or(0), step, all.len())]
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "window-windowed",
+            DisplayMode::Borderless => "window-borderless",
+            DisplayMode::Fullscreen => "window-fullscreen",
+        }
+    }
+}
+
+// how quickly battle phases play out, and the action text with them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextSpeed {
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl TextSpeed {
+    // multiplies how fast battle phase timers run
+    pub fn factor(&self) -> f32 {
+        match self {
+            TextSpeed::Slow => 0.6,
+            TextSpeed::Normal => 1.,
+            TextSpeed::Fast => 2.,
+        }
+    }
+
+    fn next(self, step: i32) -> Self {
+        let all = [TextSpeed::Slow, TextSpeed::Normal, TextSpeed::Fast];
+        all[cycle(all.iter().position(|speed| *speed == self).unwrap_or(1), step, all.len())]
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            TextSpeed::Slow => "speed-slow",
+            TextSpeed::Normal => "speed-normal",
+            TextSpeed::Fast => "speed-fast",
+        }
+    }
+}
+
+// how fast enemies move about the dungeon on their own
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnemyMovement {
+    Normal,
+    Slow,
+    Still,
+}
+
+impl EnemyMovement {
+    // multiplies enemy walking speed
+    pub fn factor(&self) -> f32 {
+        match self {
+            EnemyMovement::Normal => 1.,
+            EnemyMovement::Slow => 0.4,
+            EnemyMovement::Still => 0.,
+        }
+    }
+
+    fn next(self, step: i32) -> Self {
+        let all = [EnemyMovement::Normal, EnemyMovement::Slow, EnemyMovement::Still];
+        all[cycle(all.iter().position(|movement| *movement == self).unwrap_or(0), step, all.len())]
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            EnemyMovement::Normal => "movement-normal",
+            EnemyMovement::Slow => "movement-slow",
+            EnemyMovement::Still => "movement-still",
+        }
+    }
+}
+
+// what respawning at the last checkpoint costs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeathPenalty {
+    None,
+    Light,      // one skill point
+    Harsh,      // half of them, rounded up
+}
+
+impl DeathPenalty {
+    pub fn skill_points_lost(&self, skill_points: u32) -> u32 {
+        match self {
+            DeathPenalty::None => 0,
+            DeathPenalty::Light => skill_points.min(1),
+            DeathPenalty::Harsh => skill_points.div_ceil(2),
+        }
+    }
+
+    fn next(self, step: i32) -> Self {
+        let all = [DeathPenalty::None, DeathPenalty::Light, DeathPenalty::Harsh];
+        all[cycle(all.iter().position(|penalty| *penalty == self).unwrap_or(1), step, all.len())]
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            DeathPenalty::None => "penalty-none",
+            DeathPenalty::Light => "penalty-light",
+            DeathPenalty::Harsh => "penalty-harsh",
+        }
+    }
+}
+
+// the colors that tell things apart; the colorblind one is built from the Okabe-Ito set,
+// so no two of them differ only in red against green
+pub struct Palette {
+    pub health: Color,      // health bar fill
+    pub boss_health: Color,
+    pub player: Color,      // the player on the maps
+    pub enemy: Color,       // enemies on the minimap
+}
+
+const STANDARD_PALETTE: Palette = Palette {
+    health: Color::srgb(0.17, 0.75, 0.19),  // the green of the old bar image
+    boss_health: Color::srgb(0.75, 0.1, 0.1),
+    player: Color::srgb(0.2, 1., 0.2),
+    enemy: Color::srgb(0.9, 0.1, 0.1),
+};
+
+const COLORBLIND_PALETTE: Palette = Palette {
+    health: Color::srgb(0., 0.45, 0.7),
+    boss_health: Color::srgb(0.84, 0.37, 0.),
+    player: Color::srgb(0., 0.62, 0.45),
+    enemy: Color::srgb(0.84, 0.37, 0.),
+};
+
+// the player's options, read from SETTINGS_FILE at startup and written back whenever one changes
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub language: Language,
+    pub display_mode: DisplayMode,
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    pub master_volume: f32,     // all volumes 0..1
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub text_speed: TextSpeed,
+    pub ui_scale: f32,
+    pub colorblind: bool,
+    pub text_scale: f32,        // on top of ui_scale, for text only
+    pub high_contrast_log: bool,
+    pub reduced_motion: bool,   // no camera shake, lunges, flashing or drifting numbers
+    pub enemy_movement: EnemyMovement,
+    pub death_penalty: DeathPenalty,
+    pub shrine_autosave: bool,  // resting at a shrine also saves the game
+    pub shrine_spacing: usize,  // a shrine every this many rooms, 0 for none; used when the dungeon is next built
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            language: Language::English,
+            display_mode: DisplayMode::Windowed,
+            resolution: RESOLUTIONS[0],
+            vsync: true,
+            master_volume: 1.,
+            music_volume: 0.8,
+            sfx_volume: 0.8,
+            text_speed: TextSpeed::Normal,
+            ui_scale: 1.,
+            colorblind: false,
+            text_scale: 1.,
+            high_contrast_log: false,
+            reduced_motion: false,
+            enemy_movement: EnemyMovement::Normal,
+            death_penalty: DeathPenalty::Light,
+            shrine_autosave: true,
+            shrine_spacing: 3,
+        }
+    }
+}
+
+impl Settings {
+    // the player's settings if the file is there and parses; the defaults are written out otherwise
+    pub fn load() -> Self {
+        let text = match fs::read_to_string(SETTINGS_FILE) {
+            Ok(text) => text,
+            Err(_) => {
+                let settings = Self::default();
+                settings.save();
+                return settings;
+            }
+        };
+        Self::parse(&text)
+    }
+
+    // options missing from the file keep their defaults, so older files still load
+    fn parse(text: &str) -> Self {
+        match ron::from_str::<Settings>(text) {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("Could not parse {}: {}, using the default settings", SETTINGS_FILE, e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let text = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Could not serialize settings: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = fs::create_dir_all(CONFIG_DIR).and_then(|_| fs::write(SETTINGS_FILE, text)) {
+            warn!("Could not write {}: {}", SETTINGS_FILE, e);
+        }
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        match self.display_mode {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+
+    pub fn palette(&self) -> &'static Palette {
+        if self.colorblind {
+            &COLORBLIND_PALETTE
+        } else {
+            &STANDARD_PALETTE
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::Fifo
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+}
+
+fn cycle(index: usize, step: i32, len: usize) -> usize {
+    (index as i32 + step).rem_euclid(len as i32) as usize
+}
+
+// the index of the step closest to `value`, so a hand-edited or rounded value still lands on one
+fn nearest(steps: &[f32], value: f32) -> usize {
+    (0..steps.len())
+        .min_by(|a, b| (steps[*a] - value).abs().total_cmp(&(steps[*b] - value).abs()))
+        .unwrap_or(0)
+}
+
+// a line of the settings screen
+#[derive(Clone, Copy, PartialEq)]
+enum SettingsRow {
+    Language,
+    DisplayMode,
+    Resolution,
+    VSync,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    TextSpeed,
+    UiScale,
+    TextScale,
+    Colorblind,
+    HighContrastLog,
+    ReducedMotion,
+    EnemyMovement,
+    DeathPenalty,
+    ShrineAutosave,
+    ShrineSpacing,
+    Controls,
+    Back,
+}
+
+impl SettingsRow {
+    const ALL: [SettingsRow; 19] = [
+        SettingsRow::Language,
+        SettingsRow::DisplayMode,
+        SettingsRow::Resolution,
+        SettingsRow::VSync,
+        SettingsRow::MasterVolume,
+        SettingsRow::MusicVolume,
+        SettingsRow::SfxVolume,
+        SettingsRow::TextSpeed,
+        SettingsRow::UiScale,
+        SettingsRow::TextScale,
+        SettingsRow::Colorblind,
+        SettingsRow::HighContrastLog,
+        SettingsRow::ReducedMotion,
+        SettingsRow::EnemyMovement,
+        SettingsRow::DeathPenalty,
+        SettingsRow::ShrineAutosave,
+        SettingsRow::ShrineSpacing,
+        SettingsRow::Controls,
+        SettingsRow::Back,
+    ];
+
+    fn describe(&self, settings: &Settings, locale: &Locale) -> String {
+        let percent = |value: f32| ("percent", ((value * 100.).round() as i64).into());
+        let switch = |on: bool| ("on", if on { "on" } else { "off" }.into());
+        match self {
+            SettingsRow::Language => locale.format("settings-language", &[("language", settings.language.name().into())]),
+            SettingsRow::DisplayMode => locale.format("settings-window", &[("mode", locale.text(settings.display_mode.key()).into())]),
+            SettingsRow::Resolution => locale.format("settings-resolution", &[("width", settings.resolution.0.into()), ("height", settings.resolution.1.into())]),
+            SettingsRow::VSync => locale.format("settings-vsync", &[("vsync", if settings.vsync { "on" } else { "off" }.into())]),
+            SettingsRow::MasterVolume => locale.format("settings-master-volume", &[percent(settings.master_volume)]),
+            SettingsRow::MusicVolume => locale.format("settings-music-volume", &[percent(settings.music_volume)]),
+            SettingsRow::SfxVolume => locale.format("settings-sfx-volume", &[percent(settings.sfx_volume)]),
+            SettingsRow::TextSpeed => locale.format("settings-text-speed", &[("speed", locale.text(settings.text_speed.key()).into())]),
+            SettingsRow::UiScale => locale.format("settings-ui-scale", &[percent(settings.ui_scale)]),
+            SettingsRow::TextScale => locale.format("settings-text-scale", &[percent(settings.text_scale)]),
+            SettingsRow::Colorblind => locale.format("settings-colorblind", &[switch(settings.colorblind)]),
+            SettingsRow::HighContrastLog => locale.format("settings-high-contrast-log", &[switch(settings.high_contrast_log)]),
+            SettingsRow::ReducedMotion => locale.format("settings-reduced-motion", &[switch(settings.reduced_motion)]),
+            SettingsRow::EnemyMovement => locale.format("settings-enemy-movement", &[("movement", locale.text(settings.enemy_movement.key()).into())]),
+            SettingsRow::DeathPenalty => locale.format("settings-death-penalty", &[("penalty", locale.text(settings.death_penalty.key()).into())]),
+            SettingsRow::ShrineAutosave => locale.format("settings-shrine-autosave", &[switch(settings.shrine_autosave)]),
+            SettingsRow::ShrineSpacing => locale.format("settings-shrine-spacing", &[("rooms", settings.shrine_spacing.into())]),
+            SettingsRow::Controls => locale.text("settings-controls"),
+            SettingsRow::Back => locale.text("settings-back"),
+        }
+    }
+
+    // steps the setting on this line back (-1) or forward (1); false for lines that aren't a setting
+    fn change(&self, settings: &mut Settings, step: i32) -> bool {
+        let volume = |volume: f32| (((volume / VOLUME_STEP).round() + step as f32) * VOLUME_STEP).clamp(0., 1.);
+        match self {
+            SettingsRow::Language => {
+                let i = Language::ALL.iter().position(|language| *language == settings.language).unwrap_or(0);
+                settings.language = Language::ALL[cycle(i, step, Language::ALL.len())];
+            }
+            SettingsRow::DisplayMode => settings.display_mode = settings.display_mode.next(step),
+            SettingsRow::Resolution => {
+                let i = RESOLUTIONS.iter().position(|resolution| *resolution == settings.resolution).unwrap_or(0);
+                settings.resolution = RESOLUTIONS[cycle(i, step, RESOLUTIONS.len())];
+            }
+            SettingsRow::VSync => settings.vsync = !settings.vsync,
+            SettingsRow::MasterVolume => settings.master_volume = volume(settings.master_volume),
+            SettingsRow::MusicVolume => settings.music_volume = volume(settings.music_volume),
+            SettingsRow::SfxVolume => settings.sfx_volume = volume(settings.sfx_volume),
+            SettingsRow::TextSpeed => settings.text_speed = settings.text_speed.next(step),
+            SettingsRow::UiScale => {
+                let i = nearest(&UI_SCALES, settings.ui_scale);
+                settings.ui_scale = UI_SCALES[cycle(i, step, UI_SCALES.len())];
+            }
+            SettingsRow::TextScale => {
+                let i = nearest(&TEXT_SCALES, settings.text_scale);
+                settings.text_scale = TEXT_SCALES[cycle(i, step, TEXT_SCALES.len())];
+            }
+            SettingsRow::Colorblind => settings.colorblind = !settings.colorblind,
+            SettingsRow::HighContrastLog => settings.high_contrast_log = !settings.high_contrast_log,
+            SettingsRow::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            SettingsRow::EnemyMovement => settings.enemy_movement = settings.enemy_movement.next(step),
+            SettingsRow::DeathPenalty => settings.death_penalty = settings.death_penalty.next(step),
+            SettingsRow::ShrineAutosave => settings.shrine_autosave = !settings.shrine_autosave,
+            SettingsRow::ShrineSpacing => {
+                let i = SHRINE_SPACINGS.iter().position(|spacing| *spacing == settings.shrine_spacing).unwrap_or(1);
+                settings.shrine_spacing = SHRINE_SPACINGS[cycle(i, step, SHRINE_SPACINGS.len())];
+            }
+            SettingsRow::Controls | SettingsRow::Back => return false,
+        }
+        true
+    }
+}
+
+#[derive(Component)]    // root ui node of the settings screen
+struct SettingsUI;
+
+#[derive(Component)]
+struct SettingsButton {
+    row: SettingsRow,
+    index: usize,
+    enabled: bool,
+}
+
+#[derive(Component)]
+struct SettingsLabel;
+
+// the font sizes a piece of text was spawned with, so text_scale always starts from them
+#[derive(Component)]
+struct BaseFontSize(Vec<f32>);
+
+// the line Up/Down or the mouse last moved to
+#[derive(Resource, Default)]
+struct SettingsFocus(usize);
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsFocus>();
+        app.add_systems(Update, apply_settings.run_if(resource_changed::<Settings>));
+        app.add_systems(Update, fit_ui_scale);
+        app.add_systems(PostUpdate, scale_text.before(UiSystem::Layout));
+        app.add_systems(PreUpdate, change_settings.after(ReadInput).run_if(in_state(PauseState::Settings)));
+        app.add_systems(Update, update_settings_screen.run_if(in_state(PauseState::Settings)));
+        app.add_systems(OnEnter(PauseState::Settings), spawn_settings_screen);
+        app.add_systems(OnExit(PauseState::Settings), despawn_settings_screen);
+    }
+}
+
+// window mode, size and vsync take effect right away
+fn apply_settings(
+    settings: Res<Settings>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window.get_single_mut() else {
+        return;
+    };
+    window.mode = settings.window_mode();
+    window.resolution.set(settings.resolution.0 as f32, settings.resolution.1 as f32);
+    window.present_mode = settings.present_mode();
+}
+
+// the ui is laid out for a WIN_H tall window, so it grows with the window and then by the player's scale
+fn fit_ui_scale(
+    settings: Res<Settings>,
+    mut resized_events: EventReader<WindowResized>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    if resized_events.read().count() == 0 && !settings.is_changed() {
+        return;
+    }
+    if let Ok(window) = window.get_single() {
+        ui_scale.0 = settings.ui_scale * window.height() / WIN_H;
+    }
+}
+
+// new text is scaled as it shows up, everything is rescaled when the setting changes
+fn scale_text(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut texts: Query<(Entity, &mut Text, Option<&BaseFontSize>)>,
+) {
+    for (entity, mut text, base) in texts.iter_mut() {
+        let sizes = match base {
+            Some(_) if !settings.is_changed() => continue,
+            Some(base) => base.0.clone(),
+            None => {
+                let sizes: Vec<f32> = text.sections.iter().map(|section| section.style.font_size).collect();
+                commands.entity(entity).insert(BaseFontSize(sizes.clone()));
+                sizes
+            }
+        };
+        for (section, size) in text.sections.iter_mut().zip(sizes) {
+            section.style.font_size = size * settings.text_scale;
+        }
+    }
+}
+
+fn spawn_settings_screen(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut focus: ResMut<SettingsFocus>,
+) {
+    focus.0 = 0;
+    commands.spawn((
+        SettingsUI,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+            focus_policy: bevy::ui::FocusPolicy::Block,
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.),
+                padding: UiRect::all(Val::Px(20.)),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+            ..default()
+        })
+        .with_children(|panel| {
+            panel.spawn((
+                Localized::new("settings-title"),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 34.,
+                        color: Color::srgb(1., 1., 0.),
+                        ..default()
+                    },
+                ),
+            ));
+            for (index, row) in SettingsRow::ALL.iter().enumerate() {
+                // the controls screen is its own GameState, which would end a battle
+                let enabled = *row != SettingsRow::Controls || *state.get() == GameState::InGame;
+                panel.spawn((
+                    SettingsButton { row: *row, index, enabled },
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(440.),
+                            padding: UiRect::axes(Val::Px(12.), Val::Px(2.)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: BackgroundColor(BUTTON_COLOR),
+                        ..default()
+                    },
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        SettingsLabel,
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 20.,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ),
+                    ));
+                });
+            }
+            panel.spawn((
+                Localized::new("settings-help"),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.,
+                        color: Color::srgb(0.6, 0.6, 0.6),
+                        ..default()
+                    },
+                ),
+            ));
+        });
+    });
+}
+
+fn despawn_settings_screen(mut commands: Commands, query: Query<Entity, With<SettingsUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Up/Down or hovering picks a line, Left/Right or a click changes it; every change is saved straight away
+fn change_settings(
+    mut actions: ResMut<ActionState>,
+    mut focus: ResMut<SettingsFocus>,
+    buttons: Query<(&SettingsButton, Ref<Interaction>)>,
+    mut settings: ResMut<Settings>,
+    mut next_pause: ResMut<NextState<PauseState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let picked = pick_button(&mut focus.0, SettingsRow::ALL.len(), &actions, buttons.iter().map(|(button, interaction)| (button.index, interaction))).is_some();
+    let Some(button) = buttons.iter().map(|(button, _)| button).find(|button| button.index == focus.0) else {
+        actions.clear();
+        return;
+    };
+
+    let step = if actions.just_pressed(Action::Left) {
+        -1
+    } else if actions.just_pressed(Action::Right) || picked {
+        1
+    } else {
+        0
+    };
+    if actions.just_pressed(Action::Back) {
+        next_pause.set(PauseState::Paused);
+    } else if step != 0 && button.enabled {
+        let mut changed = settings.clone();
+        if button.row.change(&mut changed, step) {
+            changed.save();
+            *settings = changed;
+        } else if picked {
+            match button.row {
+                SettingsRow::Controls => {
+                    next_pause.set(PauseState::Running);
+                    next_state.set(GameState::Controls);
+                }
+                SettingsRow::Back => next_pause.set(PauseState::Paused),
+                _ => {}
+            }
+        }
+    }
+    actions.clear();
+}
+
+fn update_settings_screen(
+    focus: Res<SettingsFocus>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut buttons: Query<(&SettingsButton, &Children, &mut BackgroundColor)>,
+    mut labels: Query<&mut Text, With<SettingsLabel>>,
+) {
+    for (button, children, mut background) in buttons.iter_mut() {
+        *background = button_color(button.index == focus.0);
+        for child in children.iter() {
+            if let Ok(mut label) = labels.get_mut(*child) {
+                let text = button.row.describe(&settings, &locale);
+                if label.sections[0].value != text {
+                    label.sections[0].value = text;
+                }
+                label.sections[0].style.color = label_color(button.enabled);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycling_wraps_both_ways() {
+        assert_eq!(cycle(0, -1, 3), 2);
+        assert_eq!(cycle(2, 1, 3), 0);
+        assert_eq!(cycle(1, 1, 3), 2);
+        assert_eq!(DisplayMode::Windowed.next(-1), DisplayMode::Fullscreen);
+        assert_eq!(DeathPenalty::Harsh.next(1), DeathPenalty::None);
+    }
+
+    #[test]
+    fn volumes_stay_on_the_steps_and_in_range() {
+        let mut settings = Settings::default();
+        for _ in 0..3 {
+            SettingsRow::MusicVolume.change(&mut settings, 1);
+        }
+        assert_eq!(settings.music_volume, 1.);
+        for _ in 0..7 {
+            SettingsRow::MusicVolume.change(&mut settings, -1);
+        }
+        assert_eq!(settings.music_volume, 3. * VOLUME_STEP);
+        for _ in 0..20 {
+            SettingsRow::MusicVolume.change(&mut settings, -1);
+        }
+        assert_eq!(settings.music_volume, 0.);
+
+        // a value off the steps snaps back onto them
+        settings.sfx_volume = 0.4999;
+        SettingsRow::SfxVolume.change(&mut settings, 1);
+        assert_eq!(settings.sfx_volume, 6. * VOLUME_STEP);
+    }
+
+    #[test]
+    fn scales_step_from_the_nearest_choice() {
+        let mut settings = Settings { ui_scale: 1.26, text_scale: 1.0000001, ..default() };
+        SettingsRow::UiScale.change(&mut settings, 1);
+        assert_eq!(settings.ui_scale, 1.5);
+        SettingsRow::TextScale.change(&mut settings, 1);
+        assert_eq!(settings.text_scale, 1.25);
+        SettingsRow::TextScale.change(&mut settings, -1);
+        SettingsRow::TextScale.change(&mut settings, -1);
+        assert_eq!(settings.text_scale, 1.75);
+    }
+
+    #[test]
+    fn only_settings_lines_change_anything() {
+        let mut settings = Settings::default();
+        assert!(SettingsRow::ReducedMotion.change(&mut settings, 1));
+        assert!(settings.reduced_motion);
+        assert!(SettingsRow::Resolution.change(&mut settings, -1));
+        assert_eq!(settings.resolution, RESOLUTIONS[RESOLUTIONS.len() - 1]);
+
+        let before = settings.clone();
+        assert!(!SettingsRow::Controls.change(&mut settings, 1));
+        assert!(!SettingsRow::Back.change(&mut settings, 1));
+        assert_eq!(settings, before);
+    }
+
+    #[test]
+    fn missing_options_load_as_their_defaults() {
+        let settings = Settings::parse("(vsync: false, death_penalty: Harsh)");
+        assert!(!settings.vsync);
+        assert_eq!(settings.death_penalty, DeathPenalty::Harsh);
+        assert_eq!(settings, Settings { vsync: false, death_penalty: DeathPenalty::Harsh, ..default() });
+
+        assert_eq!(Settings::parse("not settings at all"), Settings::default());
+    }
+
+    #[test]
+    fn death_penalties_never_take_more_than_there_is() {
+        assert_eq!(DeathPenalty::None.skill_points_lost(5), 0);
+        assert_eq!(DeathPenalty::Light.skill_points_lost(5), 1);
+        assert_eq!(DeathPenalty::Light.skill_points_lost(0), 0);
+        assert_eq!(DeathPenalty::Harsh.skill_points_lost(5), 3);
+        assert_eq!(DeathPenalty::Harsh.skill_points_lost(4), 2);
+        assert_eq!(DeathPenalty::Harsh.skill_points_lost(0), 0);
+    }
+
+    #[test]
+    fn colorblind_mode_swaps_the_palette() {
+        let standard = Settings::default();
+        let colorblind = Settings { colorblind: true, ..default() };
+        assert_eq!(standard.palette().health, STANDARD_PALETTE.health);
+        assert_eq!(colorblind.palette().health, COLORBLIND_PALETTE.health);
+        // the player and enemies must not be told apart by red against green alone
+        assert_ne!(colorblind.palette().player, colorblind.palette().enemy);
+        assert_ne!(colorblind.palette().health, standard.palette().health);
+    }
+
+    #[test]
+    fn text_scales_from_the_size_it_was_spawned_with() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(Settings { text_scale: 1.5, ..default() });
+        app.add_systems(Update, scale_text);
+        let style = |font_size| TextStyle { font_size, ..default() };
+        let text = app.world_mut().spawn(Text::from_sections([TextSection::new("a", style(20.)), TextSection::new("b", style(10.))])).id();
+        let sizes = |app: &App| app.world().get::<Text>(text).unwrap().sections.iter().map(|section| section.style.font_size).collect::<Vec<_>>();
+
+        app.update();
+        assert_eq!(sizes(&app), vec![30., 15.]);
+        // nothing changed, nothing scales again
+        app.update();
+        assert_eq!(sizes(&app), vec![30., 15.]);
+
+        app.world_mut().resource_mut::<Settings>().text_scale = 1.;
+        app.update();
+        assert_eq!(sizes(&app), vec![20., 10.]);
+    }
+}
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+use crate::player::{Player, PlayerStats};
+use crate::controls::{Action, ActionState, Bindings};
+use crate::defeat::Checkpoint;
+use crate::events::SaveGameEvent;
+use crate::locale::Locale;
+use crate::settings::Settings;
+
+const SHRINE_RANGE: f32 = 144.;     // how close the player has to stand, one tile
+const SHRINE_SIZE: Vec2 = Vec2::new(60., 96.);
+const SHRINE_COLOR: Color = Color::srgb(0.45, 0.7, 0.85);
+const LIT_COLOR: Color = Color::srgb(0.8, 1., 1.);   // the shrine the player would respawn at
+
+// heals the player and becomes their respawn point; the dungeon builder puts one in every few battle rooms
+#[derive(Component)]
+pub struct Shrine {
+    pub room: usize,
+}
+
+#[derive(Component)]    // line at the bottom of the screen while the player stands at a shrine
+struct ShrinePrompt;
+
+pub struct ShrinePlugin;
+
+impl Plugin for ShrinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_shrine_prompt);
+        app.add_systems(Update, use_shrine.run_if(in_state(GameState::InGame)));
+        app.add_systems(Update, (light_shrines, show_shrine_prompt).after(use_shrine));
+    }
+}
+
+pub fn spawn_shrine(commands: &mut Commands, position: Vec3, room: usize) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: SHRINE_COLOR,
+                    custom_size: Some(SHRINE_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            Shrine { room },
+        ))
+        .id()
+}
+
+fn setup_shrine_prompt(mut commands: Commands) {
+    commands.spawn((
+        ShrinePrompt,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 24.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(30.),
+            width: Val::Percent(100.),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_text_justify(JustifyText::Center)
+        .with_background_color(Color::srgba(0., 0., 0., 0.6)),
+    ));
+}
+
+fn near(shrine: &Transform, player: &Transform) -> bool {
+    shrine.translation.truncate().distance(player.translation.truncate()) < SHRINE_RANGE
+}
+
+// resting heals fully and moves the respawn point here, then saves if the player wants that
+fn use_shrine(
+    actions: Res<ActionState>,
+    settings: Res<Settings>,
+    mut save_events: EventWriter<SaveGameEvent>,
+    mut player: Query<(&Transform, &mut PlayerStats, &mut Checkpoint), With<Player>>,
+    shrines: Query<&Transform, (With<Shrine>, Without<Player>)>,
+) {
+    if !actions.just_pressed(Action::Interact) {
+        return;
+    }
+    let Ok((pt, mut player_stats, mut checkpoint)) = player.get_single_mut() else {
+        return;
+    };
+    let Some(shrine) = shrines.iter().find(|shrine| near(shrine, pt)) else {
+        return;
+    };
+    player_stats.hp = player_stats.max_hp;
+    checkpoint.0 = shrine.translation.truncate();
+    if settings.shrine_autosave {
+        save_events.send(SaveGameEvent);
+    }
+}
+
+fn light_shrines(
+    player: Query<&Checkpoint, With<Player>>,
+    mut shrines: Query<(&Transform, &mut Sprite), With<Shrine>>,
+) {
+    let Ok(checkpoint) = player.get_single() else {
+        return;
+    };
+    for (transform, mut sprite) in shrines.iter_mut() {
+        sprite.color = if transform.translation.truncate() == checkpoint.0 { LIT_COLOR } else { SHRINE_COLOR };
+    }
+}
+
+// how to use the shrine, or that it is already the respawn point
+fn show_shrine_prompt(
+    state: Res<State<GameState>>,
+    bindings: Res<Bindings>,
+    locale: Res<Locale>,
+    player: Query<(&Transform, &Checkpoint), With<Player>>,
+    shrines: Query<&Transform, (With<Shrine>, Without<Player>)>,
+    mut prompt: Query<(&mut Text, &mut Visibility), With<ShrinePrompt>>,
+) {
+    let Ok((mut text, mut visibility)) = prompt.get_single_mut() else {
+        return;
+    };
+    let shrine = player
+        .get_single()
+        .ok()
+        .and_then(|(pt, checkpoint)| shrines.iter().find(|shrine| near(shrine, pt)).map(|shrine| (shrine, checkpoint)));
+    let Some((shrine, checkpoint)) = shrine.filter(|_| *state.get() == GameState::InGame) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    let line = if shrine.translation.truncate() == checkpoint.0 {
+        locale.text("shrine-rested")
+    } else {
+        let key = bindings
+            .overworld
+            .iter()
+            .find(|binding| binding.action == Action::Interact)
+            .and_then(|binding| binding.keys.first().cloned())
+            .unwrap_or_default();
+        locale.format("shrine-prompt", &[("key", key.into())])
+    };
+    if text.sections[0].value != line {
+        text.sections[0].value = line;
+    }
+    *visibility = Visibility::Visible;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHRINE_SPOT: Vec3 = Vec3::new(500., 200., 1.);
+
+    fn test_app(autosave: bool, player_at: Vec3) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ActionState>();
+        app.insert_resource(Settings { shrine_autosave: autosave, ..default() });
+        app.add_event::<SaveGameEvent>();
+        app.add_systems(Update, use_shrine);
+        let mut player_stats = PlayerStats::new();
+        player_stats.hp = 1;
+        app.world_mut().spawn((Player, player_stats, Checkpoint::default(), Transform::from_translation(player_at)));
+        app.world_mut().spawn((Shrine { room: 3 }, Transform::from_translation(SHRINE_SPOT)));
+        app
+    }
+
+    fn interact(app: &mut App) {
+        app.world_mut().resource_mut::<ActionState>().press(Action::Interact, true);
+        app.update();
+    }
+
+    fn player(app: &mut App) -> (PlayerStats, Checkpoint) {
+        let world = app.world_mut();
+        let (player_stats, checkpoint) = world.query::<(&PlayerStats, &Checkpoint)>().single(world);
+        (player_stats.clone(), *checkpoint)
+    }
+
+    #[test]
+    fn resting_heals_sets_the_checkpoint_and_saves() {
+        let mut app = test_app(true, SHRINE_SPOT + Vec3::new(60., 0., 0.));
+        interact(&mut app);
+        let (player_stats, checkpoint) = player(&mut app);
+        assert_eq!(player_stats.hp, player_stats.max_hp);
+        assert_eq!(checkpoint.0, SHRINE_SPOT.truncate());
+        assert_eq!(app.world().resource::<Events<SaveGameEvent>>().len(), 1);
+    }
+
+    #[test]
+    fn autosave_can_be_turned_off() {
+        let mut app = test_app(false, SHRINE_SPOT);
+        interact(&mut app);
+        let (_, checkpoint) = player(&mut app);
+        assert_eq!(checkpoint.0, SHRINE_SPOT.truncate());
+        assert!(app.world().resource::<Events<SaveGameEvent>>().is_empty());
+    }
+
+    #[test]
+    fn shrines_only_answer_up_close() {
+        let mut app = test_app(true, SHRINE_SPOT + Vec3::new(SHRINE_RANGE, 0., 0.));
+        interact(&mut app);
+        let (player_stats, checkpoint) = player(&mut app);
+        assert_eq!(player_stats.hp, 1);
+        assert_eq!(checkpoint.0, Vec2::ZERO);
+        assert!(app.world().resource::<Events<SaveGameEvent>>().is_empty());
+
+        // standing next to it without pressing anything does nothing either
+        let mut app = test_app(true, SHRINE_SPOT);
+        app.update();
+        assert_eq!(player(&mut app).0.hp, 1);
+    }
+}
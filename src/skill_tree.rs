@@ -5,8 +5,10 @@ use bevy::{
 
 use crate::GameState;
 use crate::player::{PlayerStats, BonusStats, Player, init_player};
-use crate::{WIN_W, WIN_H};
-use crate::player::{LEVEL_W, LEVEL_H};
+use crate::dungeon::LevelBounds;
+use crate::element::spell_for_node;
+use crate::controls::{Action, ActionState};
+use crate::locale::Localized;
 
 #[derive(Component)]
 struct SkillTreeUIBackground;
@@ -15,13 +17,20 @@ struct SkillTreeUISkeleton;
 #[derive(Component)]
 struct SkillTreeUIDetails;
 #[derive(Component)]
-struct SkillTreeUINode {
-    unlocked: bool,
-    index: u32,
+pub struct SkillTreeUINode {
+    pub unlocked: bool,
+    pub index: u32,
 }
 #[derive(Component)]
 struct SkillTreeUIComponent;
 
+// the node the mouse, keys or pad are pointing at, and whether it was picked this frame
+#[derive(Resource, Default)]
+struct NodeFocus {
+    index: u32,
+    pressed: bool,
+}
+
 #[derive(Component)]
 struct StatText {
     stat_type: StatType,
@@ -53,7 +62,8 @@ impl Plugin for SkillTreePlugin{
         app.add_systems(PostStartup, hide_skill_tree_ui);
         app.add_systems(Update, toggle_skill_tree_ui);
         app.add_systems(Update, update_skill_tree_ui);
-        app.add_systems(Update, unlock_skill_tree_nodes.run_if(in_state(GameState::SkillTreeMenu)));
+        app.init_resource::<NodeFocus>();
+        app.add_systems(Update, (point_at_nodes, move_node_focus, unlock_skill_tree_nodes, show_node_focus).chain().run_if(in_state(GameState::SkillTreeMenu)));
         app.add_systems(Update, spend_ability_point.run_if(in_state(GameState::SkillTreeMenu)));
         app.add_systems(OnEnter(GameState::SkillTreeMenu), show_skill_tree_ui);
         app.add_systems(OnExit(GameState::SkillTreeMenu), hide_skill_tree_ui);
@@ -650,8 +660,9 @@ fn load_skill_tree_ui(
         // exit text  ------------------------------------------------------------
         commands.spawn((
             SkillTreeUIComponent,
+            Localized::new("skill-tree-exit"),
             TextBundle::from_section(
-                "Press 'Q' to exit",
+                "",
                 TextStyle {
                     font_size: 20.0,
                     color: bevy::prelude::Color::Srgba(WHITE),
@@ -669,8 +680,9 @@ fn load_skill_tree_ui(
         // temporary descriptive text  ------------------------------------------------------------
         commands.spawn((
             SkillTreeUIComponent,
+            Localized::new("skill-tree-help"),
             TextBundle::from_section(
-                "Press 'F, G, H, or J' to upgrade strength, magic, agility, or health\nup to a base total of 7\nClick on a node to unlock bonus stats",
+                "",
                 TextStyle {
                     font_size: 20.0,
                     color: bevy::prelude::Color::Srgba(WHITE),
@@ -687,6 +699,9 @@ fn load_skill_tree_ui(
     }
 }
 
+// the player the skill tree is centred on, kept apart from the tree's own Transforms
+type SkillTreeViewer = (With<Player>, Without<SkillTreeUIBackground>, Without<SkillTreeUISkeleton>, Without<SkillTreeUINode>, Without<SkillTreeUIDetails>);
+
 fn show_skill_tree_ui(
     mut commands: Commands,
     query: Query<Entity, With<SkillTreeUIComponent>>,
@@ -694,7 +709,8 @@ fn show_skill_tree_ui(
     mut details: Query<&mut Transform, (With<SkillTreeUIDetails>, Without<SkillTreeUIBackground>, Without<SkillTreeUISkeleton>)>,
     mut background: Query<&mut Transform, (With<SkillTreeUIBackground>, Without<SkillTreeUISkeleton>, Without<SkillTreeUIDetails>)>,
     mut nodes: Query<&mut Transform, (With<SkillTreeUINode>, Without<SkillTreeUIBackground>, Without<SkillTreeUISkeleton>, Without<SkillTreeUIDetails>)>,
-    player: Query<&Transform, (With<Player>, Without<SkillTreeUIBackground>, Without<SkillTreeUISkeleton>, Without<SkillTreeUINode>, Without<SkillTreeUIDetails>)>,)
+    player: Query<&Transform, SkillTreeViewer>,
+    bounds: Res<LevelBounds>,)
     // an &Transform with <...> would not have <SkillTreeUI...> applied by user logic, but the Without<T> is included to not cause a panic and crash the game
 {
     // makes the skill tree UI visible
@@ -704,11 +720,10 @@ fn show_skill_tree_ui(
 
     // centers the skill tree on the center of the screen (based on player location)
     let pt = player.single();
-    let x_bound = LEVEL_W / 2. - WIN_W / 2.;
-    let y_bound = LEVEL_H / 2. - WIN_H / 2.;
+    let screen = bounds.screen_center(pt.translation);
 
-    let x_player = pt.translation.x.clamp(-x_bound, x_bound);   // same logic as camera/player movement
-    let y_player = pt.translation.y.clamp(-y_bound, y_bound);   // same logic as camera/player movement
+    let x_player = screen.x;
+    let y_player = screen.y;
     let z_player = pt.translation.z;
 
     let mut bt = background.single_mut();
@@ -800,9 +815,9 @@ fn hide_skill_tree_ui(
 fn toggle_skill_tree_ui(
     state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
 ) {
-        if input.just_pressed(KeyCode::KeyQ) {
+        if actions.just_pressed(Action::SkillTree) {
             match state.get() {
                 GameState::Welcome => next_state.set(GameState::Welcome),
                 GameState::InGame => next_state.set(GameState::SkillTreeMenu),
@@ -810,6 +825,9 @@ fn toggle_skill_tree_ui(
                 GameState::BattleMode => next_state.set(GameState::BattleMode),
                 GameState::EndCredits => next_state.set(GameState::EndCredits),
                 GameState::DefeatScreen => next_state.set(GameState::DefeatScreen),
+                GameState::MapView => next_state.set(GameState::MapView),
+                GameState::Journal => next_state.set(GameState::Journal),
+                GameState::Controls => next_state.set(GameState::Controls),
             }
         }
 }
@@ -822,7 +840,7 @@ fn update_skill_tree_ui(
     // update the player stats before updating the stats text
     if let Ok(bonus) = bonus_query.get_single_mut(){
         if let Ok(mut player_stats) = player_query.get_single_mut() {
-            player_stats.update_stats(bonus);
+            player_stats.update_stats(&bonus);
         }
     }
     // update the stats text
@@ -874,20 +892,20 @@ fn update_skill_tree_ui(
 }
 
 fn spend_ability_point(
-    input: Res<ButtonInput<KeyCode>>, // Directly read input for key presses
+    actions: Res<ActionState>, // the spend actions, rebindable on the controls screen
     mut player_query: Query<&mut PlayerStats, With<Player>>,
 ) {
     if let Ok(mut player_stats) = player_query.get_single_mut() {
         // Check if there are ability points available to spend
         if player_stats.ability_points > 0 {
             // Check for key presses and upgrade the appropriate stat
-            if input.just_pressed(KeyCode::KeyF) { //press f to upgrade respect *attack
+            if actions.just_pressed(Action::SpendStrength) { // upgrade respect *attack
                 player_stats.strength += 1;
-            } else if input.just_pressed(KeyCode::KeyG) { //g to upgrade magic
+            } else if actions.just_pressed(Action::SpendMagic) { // upgrade magic
                 player_stats.magic += 1;
-            } else if input.just_pressed(KeyCode::KeyH) { //h to upgrade speed
+            } else if actions.just_pressed(Action::SpendAgility) { // upgrade speed
                 player_stats.agility += 1;
-            } else if input.just_pressed(KeyCode::KeyJ) { //j to upgrade max_hp all temporary for now
+            } else if actions.just_pressed(Action::SpendHealth) { // upgrade max_hp all temporary for now
                 player_stats.health += 1;
                 player_stats.heal(5);
             } else {
@@ -910,47 +928,117 @@ fn spend_skill_points(
         }
 }
 
-fn unlock_skill_tree_nodes(
+// hovering a node focuses it and clicking picks it
+fn point_at_nodes(
+    mut focus: ResMut<NodeFocus>,
+    mut cursor_events: EventReader<CursorMoved>,
     buttons: Res<ButtonInput<MouseButton>>,
     window: Query<&Window>,
-    mut sprites: Query<(&Transform, &Handle<Image>, &mut TextureAtlas, &mut SkillTreeUINode), (With<Sprite>, With<SkillTreeUINode>)>,
+    sprites: Query<(&Transform, &Handle<Image>, &SkillTreeUINode)>,
     assets: Res<Assets<Image>>,
     cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    focus.pressed = false;
+    // a mouse sitting still leaves the keyboard or pad focus alone
+    let moved = cursor_events.read().count() > 0;
+    if !moved && !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = window.get_single().ok().and_then(|window| window.cursor_position()) else {
+        return;
+    };
+    let (camera, position) = cameras.single();
+    let Some(p) = camera.viewport_to_world_2d(position, cursor) else {
+        return;
+    };
+    for (transform, image_handle, node) in sprites.iter() {
+        // get the (rectangular) bounds of the sprite
+        let Some(image) = assets.get(image_handle) else {
+            continue;
+        };
+        let scaled = image.size_f32() * transform.scale.truncate();
+        let bounds = Rect::from_center_size(transform.translation.truncate(), scaled);
+        if bounds.contains(p) {
+            focus.index = node.index;
+            focus.pressed = buttons.just_pressed(MouseButton::Left);
+        }
+    }
+}
+
+// directions hop to the closest node that way, Confirm picks the focused node
+fn move_node_focus(
+    mut focus: ResMut<NodeFocus>,
+    actions: Res<ActionState>,
+    sprites: Query<(&Transform, &SkillTreeUINode)>,
+) {
+    if actions.just_pressed(Action::Confirm) {
+        focus.pressed = true;
+    }
+    let direction = if actions.just_pressed(Action::Up) {
+        Vec2::Y
+    } else if actions.just_pressed(Action::Down) {
+        Vec2::NEG_Y
+    } else if actions.just_pressed(Action::Left) {
+        Vec2::NEG_X
+    } else if actions.just_pressed(Action::Right) {
+        Vec2::X
+    } else {
+        return;
+    };
+    let Some(from) = sprites.iter().find(|(_, node)| node.index == focus.index).map(|(t, _)| t.translation.truncate()) else {
+        return;
+    };
+    // straight ahead beats off to the side
+    let mut best: Option<(f32, u32)> = None;
+    for (transform, node) in sprites.iter() {
+        let offset = transform.translation.truncate() - from;
+        let along = offset.dot(direction);
+        if along <= 0. {
+            continue;
+        }
+        let score = along + 2. * offset.perp_dot(direction).abs();
+        if best.is_none_or(|(best_score, _)| score < best_score) {
+            best = Some((score, node.index));
+        }
+    }
+    if let Some((_, index)) = best {
+        focus.index = index;
+    }
+}
+
+// tints the focused node so keyboard and pad players can see where they are
+fn show_node_focus(
+    focus: Res<NodeFocus>,
+    mut sprites: Query<(&mut Sprite, &SkillTreeUINode)>,
+) {
+    for (mut sprite, node) in sprites.iter_mut() {
+        sprite.color = if node.index == focus.index {
+            Color::srgb(1., 0.9, 0.5)
+        } else {
+            Color::WHITE
+        };
+    }
+}
+
+fn unlock_skill_tree_nodes(
+    focus: Res<NodeFocus>,
+    mut sprites: Query<(&mut TextureAtlas, &mut SkillTreeUINode)>,
     mut player_query: Query<&mut PlayerStats, With<Player>>,
     mut bonus_query:  Query<&mut BonusStats>,
 ) {
     // array that tracks node unlock values
     let mut node_array: [bool; 18] = [false; 18];
     let mut n = 0;
-    for (_transform, _image_handle, _texture_atlas, node) in &mut sprites {
+    for (_texture_atlas, node) in &mut sprites {
         node_array[n] = node.unlocked;
         n += 1;
     }
 
-    let (camera, position) = cameras.single();
-
     // get all matching entities
-    for (transform, image_handle, texture_atlas, node) in &mut sprites {
-        // get the (rectangular) bounds of the sprite
-        let image_size = assets.get(image_handle).unwrap().size_f32();
-        let scaled = image_size * transform.scale.truncate();
-        let bounds = Rect::from_center_size(
-            transform.translation.truncate(),
-            scaled
-        );
-
-        // get the cursor location in world coordinates
-        if let Some(p) = window.single().cursor_position()
-            .and_then(|cursor| camera.viewport_to_world(position, cursor))
-            .map(|ray| ray.origin.truncate())
-        {
-            // if the cursor location is within the (rectangular) bounds of one of the nodes and the mouse is clicked...
-            if p.x > bounds.min.x 
-            && p.x < bounds.max.x 
-            && p.y > bounds.min.y 
-            && p.y < bounds.max.y 
-            && buttons.just_pressed(MouseButton::Left)
-            {
+    for (texture_atlas, node) in &mut sprites {
+        // the node that was clicked or confirmed this frame
+        if let Some(picked) = focus.pressed.then_some(focus.index) {
+            if node.index == picked {
                 if let Ok(mut player_stats) = player_query.get_single_mut() {
                     if let Ok(mut bonus_stats) = bonus_query.get_single_mut() {
                         if node.unlocked != true {
@@ -961,19 +1049,18 @@ fn unlock_skill_tree_nodes(
                                 // unlock the node by changing its unlocked value and sprite using texture atlas index
                                 unlock_node(texture_atlas, node, node_array);
                                 // edit relevant values
-                                bonus_stats.max_hp += 10;
-                                player_stats.heal(10);
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 1);
                             }
                             else if i == 1 && node_array[(i-1) as usize] == true && curr_sp >= 1 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.atk += 3;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 1);
                             }
                             else if i == 2 && node_array[(i-1) as usize] == true && curr_sp >= 1{
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.matk += 3;
-                                player_stats.update_stats(bonus_stats);
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
+                                player_stats.update_stats(&bonus_stats);
                                 spend_skill_points(player_stats, 1);
                             }
 
@@ -981,34 +1068,33 @@ fn unlock_skill_tree_nodes(
                             // top
                             else if i == 3 && node_array[(i-1) as usize] == true && curr_sp >= 2 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.def += 6;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 2);
                             }
                             else if i == 4 && node_array[(i-1) as usize] == true && curr_sp >= 2 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.atk += 6;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 2);
                             }
                             else if i == 5 && node_array[(i-1) as usize] == true && curr_sp >= 2 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.max_hp += 15;
-                                player_stats.heal(15);
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 2);
                             }
                             // bottom
                             else if i == 6 && node_array[2] == true && curr_sp >= 2 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.mdef += 9;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 2);
                             }
                             else if i == 7 && node_array[(i-1) as usize] == true && curr_sp >= 2 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.spd += 1;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 2);
                             }
                             else if i == 8 && node_array[(i-1) as usize] == true && curr_sp >= 2 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.matk += 6;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 2);
                             }
 
@@ -1016,50 +1102,49 @@ fn unlock_skill_tree_nodes(
                             // top
                             else if i == 9 && node_array[5] == true && curr_sp >= 3 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.atk += 9;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 3);
                             }
                             else if i == 10 && node_array[(i-1) as usize] == true && curr_sp >= 3 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.def += 12;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 3);
                             }
                             else if i == 11 && node_array[(i-1) as usize] == true && curr_sp >= 3 {
                                 unlock_node(texture_atlas, node, node_array);
-                                player_stats.strength += 1;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 3);
                             }
                             // middle
                             else if i == 12 && (node_array[5] == true || node_array[8] == true) && curr_sp >= 3 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.spd += 1;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 3);
                             }
                             else if i == 13 && node_array[(i-1) as usize] == true && curr_sp >= 3 {
                                 unlock_node(texture_atlas, node, node_array);
-                                player_stats.ability_points += 1;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 3);
                             }
                             else if i == 14 && node_array[(i-1) as usize] == true && curr_sp >= 3 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.max_hp += 25;
-                                player_stats.heal(25);
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 3);
                             }
                             // bottom
                             else if i == 15 && node_array[8] == true && curr_sp >= 3 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.matk += 9;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 3);
                             }
                             else if i == 16 && node_array[(i-1) as usize] == true && curr_sp >= 3 {
                                 unlock_node(texture_atlas, node, node_array);
-                                bonus_stats.mdef += 9;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 3);
                             }
                             else if i == 17 && node_array[(i-1) as usize] == true && curr_sp >= 3 {
                                 unlock_node(texture_atlas, node, node_array);
-                                player_stats.magic += 1;
+                                apply_node(i, &mut player_stats, &mut bonus_stats);
                                 spend_skill_points(player_stats, 3);
                             }
                         }
@@ -1078,4 +1163,52 @@ fn unlock_node(
     texture_atlas.index = 1;
     node.unlocked = true;
     node_array[node.index as usize] = true;
-}
\ No newline at end of file
+}
+
+// skill points needed to unlock a node, by column of the tree
+pub fn node_cost(index: u32) -> u32 {
+    match index {
+        0..=2 => 1,
+        3..=8 => 2,
+        _ => 3,
+    }
+}
+
+// stat changes granted by a node, also used by the balance simulator
+pub fn apply_node(index: u32, player_stats: &mut PlayerStats, bonus_stats: &mut BonusStats) {
+    match index {
+        0 => {
+            bonus_stats.max_hp += 10;
+            player_stats.heal(10);
+        }
+        1 => bonus_stats.atk += 3,
+        2 => bonus_stats.matk += 3,
+        3 => bonus_stats.def += 6,
+        4 => bonus_stats.atk += 6,
+        5 => {
+            bonus_stats.max_hp += 15;
+            player_stats.heal(15);
+        }
+        6 => bonus_stats.mdef += 9,
+        7 => bonus_stats.spd += 1,
+        8 => bonus_stats.matk += 6,
+        9 => bonus_stats.atk += 9,
+        10 => bonus_stats.def += 12,
+        11 => player_stats.strength += 1,
+        12 => bonus_stats.spd += 1,
+        13 => player_stats.ability_points += 1,
+        14 => {
+            bonus_stats.max_hp += 25;
+            player_stats.heal(25);
+        }
+        15 => bonus_stats.matk += 9,
+        16 => bonus_stats.mdef += 9,
+        17 => player_stats.magic += 1,
+        _ => {}
+    }
+    if let Some(element) = spell_for_node(index) {
+        if !player_stats.spells.contains(&element) {
+            player_stats.spells.push(element);
+        }
+    }
+}
@@ -4,18 +4,24 @@ use bevy::prelude::*;
 use crate::GameState;
 //use crate::TextState;
 use crate::MenuState;
+use crate::BattleState;
+use crate::controls::{Action, ActionState};
 
 use crate::player::Player;
 use crate::player::init_player;
-use crate::WIN_W;
-use crate::WIN_H;
-use crate::player::LEVEL_W;
-use crate::player::LEVEL_H;
 
 use crate::player::PlayerStats; // }
 use crate::enemy::EnemyStats;   // }for player and enemy hp displays
 use crate::enemy::Enemy;        // }
 use crate::enemy::find_closest_enemy;
+use crate::battle_log::{BattleLog, LOG_LINES};
+use crate::battle::{PlayerAction, battle_input, damage_preview, action_available, heal};
+use crate::element::{Element, affinity};
+use crate::replay::BattleReplay;
+use crate::events::PlayerActionEvent;
+use crate::locale::Locale;
+use crate::menu::{BUTTON_COLOR, button_color, label_color, pick_button};
+use crate::settings::Settings;
 
 #[derive(Component)]    //All UI's in battle screen have this component
 struct Textbox;
@@ -35,44 +41,128 @@ struct TextboxBackground;
 #[derive(Component)]
 struct BattleLogTag;
 
+#[derive(Component)]    //the column the battle menu buttons are spawned into
+struct MenuButtons;
+
+#[derive(Component)]    //Used to identify the tooltip under the battle menu
+struct MenuTooltip;
+
+#[derive(Component)]
+struct MenuLabel;
+
+// an entry of the battle menu
+#[derive(Clone, Copy, PartialEq)]
+enum MenuOption {
+    Fight,      // opens the attack menu
+    Battle(PlayerAction),
+    Back,       // back to the main menu
+}
+
 #[derive(Component)]
-pub struct BattleDialogue{
-    pub dialogue1: String,
-    pub dialogue2: String,
-    pub dialogue3: String,
+struct MenuButton {
+    option: MenuOption,
+    index: usize,       // position in the menu, top to bottom
+    enabled: bool,
+    tooltip: String,
+}
+
+// the button Up/Down or the mouse last moved to, picked with Confirm or a click
+#[derive(Resource, Default)]
+struct MenuFocus(usize);
+
+impl MenuOption {
+    fn label(&self, locale: &Locale) -> String {
+        let key = match self {
+            MenuOption::Fight => "menu-fight",
+            MenuOption::Battle(PlayerAction::Attack) => "menu-attack",
+            MenuOption::Battle(PlayerAction::Magic) => "menu-magic",
+            MenuOption::Battle(PlayerAction::Spell(element)) => {
+                return locale.format("menu-cast", &[("element", locale.text(element.key()).into())]);
+            }
+            MenuOption::Battle(PlayerAction::Heal) => "menu-heal",
+            MenuOption::Battle(PlayerAction::Run) => "menu-run",
+            MenuOption::Battle(PlayerAction::Wait) => "menu-wait",
+            MenuOption::Battle(PlayerAction::Guard) => "menu-guard",
+            MenuOption::Back => "menu-back",
+        };
+        locale.text(key)
+    }
 }
 
-impl BattleDialogue{
-    pub fn new() -> Self {
-        Self {
-            dialogue1: String::from(""),
-            dialogue2: String::from(""),
-            dialogue3: String::from(">Battle Start"),
+// the entries of each menu, the attack menu lists every spell learned so far
+fn menu_options(menu_state: &MenuState, spells: &[Element]) -> Vec<MenuOption> {
+    match menu_state {
+        MenuState::MainMenu => vec![
+            MenuOption::Fight,
+            MenuOption::Battle(PlayerAction::Heal),
+            MenuOption::Battle(PlayerAction::Guard),
+            MenuOption::Battle(PlayerAction::Wait),
+            MenuOption::Battle(PlayerAction::Run),
+        ],
+        MenuState::AttackMenu => {
+            let mut options = vec![MenuOption::Battle(PlayerAction::Attack), MenuOption::Battle(PlayerAction::Magic)];
+            options.extend(spells.iter().map(|element| MenuOption::Battle(PlayerAction::Spell(*element))));
+            options.push(MenuOption::Back);
+            options
         }
+        MenuState::Text => vec![],
     }
+}
 
-    pub fn change(&mut self, text: String){
-        self.dialogue1 = self.dialogue2.clone();
-        self.dialogue2 = self.dialogue3.clone();
-        self.dialogue3 = text.clone();
+// whether an option can be picked against this enemy, and what its tooltip says
+fn assess(option: MenuOption, player_stats: &PlayerStats, enemy_stats: &EnemyStats, locale: &Locale) -> (bool, String) {
+    let action = match option {
+        MenuOption::Fight => return (true, locale.text("tooltip-fight")),
+        MenuOption::Back => return (true, locale.text("tooltip-back")),
+        MenuOption::Battle(action) => action,
+    };
+    if let Some((low, high, chance)) = damage_preview(action, player_stats, enemy_stats) {
+        let damage = if low == high {
+            locale.format("tooltip-damage", &[("low", low.into())])
+        } else {
+            locale.format("tooltip-damage-range", &[("low", low.into()), ("high", high.into())])
+        };
+        let affinity = match action {
+            PlayerAction::Spell(element) if affinity(enemy_stats.etype, element) > 1. => "weak",
+            PlayerAction::Spell(element) if affinity(enemy_stats.etype, element) < 1. => "resisted",
+            _ => "neutral",
+        };
+        return (true, locale.format("tooltip-hit", &[("damage", damage.into()), ("chance", chance.into()), ("affinity", affinity.into())]));
     }
-    
+    let enabled = action_available(action, player_stats, enemy_stats);
+    let tooltip = match action {
+        PlayerAction::Heal if enabled => {
+            let amount = heal(4, player_stats.magic).min(player_stats.max_hp - player_stats.hp);
+            return (enabled, locale.format("tooltip-heal", &[("amount", amount.into())]));
+        }
+        PlayerAction::Heal => "tooltip-full-hp",
+        PlayerAction::Run if enabled => "tooltip-run",
+        PlayerAction::Run => "tooltip-run-boss",
+        PlayerAction::Guard => "tooltip-guard",
+        _ => "tooltip-wait",
+    };
+    (enabled, locale.text(tooltip))
 }
 
 pub struct TextboxPlugin;
 
 impl Plugin for TextboxPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>();
         app.add_systems(Startup, setup_textbox.after(init_player));
        // app.add_systems(Startup, steup_player_health.after(init_player));
         app.add_systems(PostStartup, hide_textbox);
         app.add_systems(OnEnter(GameState::BattleMode), show_textbox);
         app.add_systems(OnExit(GameState::BattleMode), hide_textbox);        
         //app.add_systems(Update, toggle_textbox);
-        app.add_systems(Update, menu_interaction);
-        app.add_systems(Update, update_playerhp.after(menu_interaction));
-        app.add_systems(Update, update_enemyhp.after(menu_interaction));
-        app.add_systems(Update, update_battledialogue.after(menu_interaction));
+        // the action text stays up until the battle is back to choosing an action
+        app.add_systems(OnEnter(BattleState::ResolvePlayer), show_action_text);
+        app.add_systems(OnEnter(BattleState::ChooseAction), back_to_main_menu);
+        app.add_systems(Update, (build_menu, show_menu_prompt, assess_menu, style_menu).chain().run_if(in_state(GameState::BattleMode)));
+        app.add_systems(Update, pick_option.after(assess_menu).before(battle_input).run_if(in_state(BattleState::ChooseAction)));
+        app.add_systems(Update, update_playerhp);
+        app.add_systems(Update, update_enemyhp);
+        app.add_systems(Update, update_battle_log);
 
     }
 }
@@ -114,27 +204,56 @@ fn setup_textbox(
         TextboxBackground
     ));*/
 
-    // Battle Options display
+    // Battle menu: what is going on, the buttons for the current menu and the focused button's tooltip
     commands.spawn((
         Textbox,
-        Battleoptions,
-        TextBundle {
-            text: Text::from_section(
-                "What will you do?\n1. Attack\n2. Magic\n3. Heal\n4. Run",
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(30.0),
+                left: Val::Px(80.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            Battleoptions,
+            TextBundle::from_section(
+                "",
                 TextStyle {
                     font_size: 30.0,
                     color: Color::WHITE,
                     ..Default::default()
                 },
-            ).with_justify(JustifyText::Left),
-            ..Default::default()
-        }.with_style(Style {
-            position_type: PositionType::Absolute,
-            bottom: Val::Px(50.0),
-            left: Val::Px(80.0),
-            ..default()
-        })
-    ));
+            ),
+        ));
+        parent.spawn((
+            MenuButtons,
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+        parent.spawn((
+            MenuTooltip,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::srgb(0.75, 0.75, 0.75),
+                    ..Default::default()
+                },
+            ),
+        ));
+    });
     // Player HP Display
     commands.spawn((
         Textbox,
@@ -177,64 +296,19 @@ fn setup_textbox(
             ..default()
         })
     ));
-    //battle dialogue display
+    //battle log display, one section per visible line plus a scroll hint on top
+    let log_style = TextStyle {
+        font_size: 25.0,
+        color: Color::WHITE,
+        ..Default::default()
+    };
     commands.spawn((
-        BattleDialogue::new(),
-    ));
-    let mut bd= BattleDialogue::new();
-    
-    commands.spawn((    //top of dialogue, bd[0]
-        Textbox,
-        BattleLogTag,
-        TextBundle {
-            text: Text::from_section(
-                bd.dialogue1.clone(),        
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::WHITE,
-                    ..Default::default()
-                },
-            ),
-            ..Default::default()
-        }.with_style(Style {
-            position_type: PositionType::Absolute,
-            bottom: Val::Px(50.0),
-            right: Val::Px(40.0),
-            ..default()
-        }),
-    ));
-    commands.spawn((    //middle of dialogue, bd[1]
-        Textbox,
-        BattleLogTag,
-        TextBundle {
-            text: Text::from_section(
-                bd.dialogue2.clone(),       
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::WHITE,
-                    ..Default::default()
-                },
-            ),
-            ..Default::default()
-        }.with_style(Style {
-            position_type: PositionType::Absolute,
-            bottom: Val::Px(30.0),
-            right: Val::Px(40.0),
-            ..default()
-        }),
-    ));
-    commands.spawn((    //bottom of dialogue, bd[2]
         Textbox,
         BattleLogTag,
         TextBundle {
-            text: Text::from_section(
-                bd.dialogue3.clone(),        
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::WHITE,
-                    ..Default::default()
-                },
-            ),
+            text: Text::from_sections(
+                (0..=LOG_LINES).map(|_| TextSection::new("", log_style.clone()))
+            ).with_justify(JustifyText::Right),
             ..Default::default()
         }.with_style(Style {
             position_type: PositionType::Absolute,
@@ -245,56 +319,204 @@ fn setup_textbox(
     ));
 }
 
-fn menu_interaction(
+// shows what the player did while it plays out
+fn show_action_text(
     mut query: Query<&mut Text, With<Battleoptions>>,
-    input: Res<ButtonInput<KeyCode>>,  
-    menu_state: Res<State<MenuState>>,  
-    mut next_menu_state: ResMut<NextState<MenuState>>, 
-    //mut next_text_state: ResMut<NextState<TextState>>,  
+    mut action_events: EventReader<PlayerActionEvent>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+    locale: Res<Locale>,
 ) {
+    let Some(PlayerActionEvent(action)) = action_events.read().last().copied() else {
+        return;
+    };
     for mut text in query.iter_mut() {
-        match menu_state.get() { 
-            MenuState::MainMenu => {    //main attack options menu
-                if input.just_pressed(KeyCode::Digit1) {
-                    text.sections[0].value = "Attacked!".to_string();   
-                    next_menu_state.set(MenuState::Text);
-                    //next_menu_state.set(MenuState::AttackMenu);
-                    //text.sections[0].value = "Choose your attack:\n1. Attack1\n2. Attack2\n3. Attack3".to_string();
-                } else if input.just_pressed(KeyCode::Digit2) {
-                    text.sections[0].value = "Magic Attacked!".to_string();                    
-                    next_menu_state.set(MenuState::Text);
-                } else if input.just_pressed(KeyCode::Digit3) {
-                    text.sections[0].value = "1hp restored!".to_string();   //<-------- in future change to variable # of hp resotred
-                    next_menu_state.set(MenuState::Text);
-                } else if input.just_pressed(KeyCode::Digit3) {
-                    text.sections[0].value = "You ran away!".to_string();
-                    next_menu_state.set(MenuState::Text);
-                }
-            }
-            MenuState::AttackMenu => {  //sub menu containing other attacks?
-                if input.just_pressed(KeyCode::Digit1) {
-                    text.sections[0].value = "You chose Attack1!".to_string();
-                    next_menu_state.set(MenuState::Text);
-                } else if input.just_pressed(KeyCode::Digit2) {
-                    text.sections[0].value = "You chose Attack2!".to_string();
-                    next_menu_state.set(MenuState::Text);
-                } else if input.just_pressed(KeyCode::Digit3) {
-                    text.sections[0].value = "You chose Attack3!".to_string();
-                    next_menu_state.set(MenuState::Text);
+        text.sections[0].value = match action {
+            PlayerAction::Attack => locale.text("action-text-attack"),
+            PlayerAction::Magic => locale.text("action-text-magic"),
+            PlayerAction::Spell(element) => locale.format("action-text-spell", &[("element", locale.text(element.key()).into())]),
+            PlayerAction::Heal => locale.text("action-text-heal"),
+            PlayerAction::Run => locale.text("action-text-run"),
+            PlayerAction::Wait => locale.text("action-text-wait"),
+            PlayerAction::Guard => locale.text("action-text-guard"),
+        };
+    }
+    next_menu_state.set(MenuState::Text);
+}
+
+// every turn starts over at the main menu
+fn back_to_main_menu(mut next_menu_state: ResMut<NextState<MenuState>>) {
+    next_menu_state.set(MenuState::MainMenu);
+}
+
+// respawns the buttons whenever the battle moves to another menu, there are none while an action plays out
+fn build_menu(
+    mut commands: Commands,
+    menu_state: Res<State<MenuState>>,
+    battle_state: Res<State<BattleState>>,
+    mut focus: ResMut<MenuFocus>,
+    player_stat_query: Query<&PlayerStats, With<Player>>,
+    panel: Query<Entity, With<MenuButtons>>,
+) {
+    if !menu_state.is_changed() && !battle_state.is_changed() {
+        return;
+    }
+    let Ok(panel) = panel.get_single() else {
+        return;
+    };
+    commands.entity(panel).despawn_descendants();
+    focus.0 = 0;
+    if *battle_state.get() != BattleState::ChooseAction {
+        return;
+    }
+
+    let spells = player_stat_query.get_single().map(|player_stats| player_stats.spells.clone()).unwrap_or_default();
+    commands.entity(panel).with_children(|parent| {
+        for (index, option) in menu_options(menu_state.get(), &spells).into_iter().enumerate() {
+            parent.spawn((
+                MenuButton { option, index, enabled: true, tooltip: String::new() },
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(240.0),
+                        padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(BUTTON_COLOR),
+                    ..default()
+                },
+            ))
+            .with_children(|button| {
+                button.spawn((
+                    MenuLabel,
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 26.0,
+                            color: Color::WHITE,
+                            ..Default::default()
+                        },
+                    ),
+                ));
+            });
+        }
+    });
+}
+
+// the line above the buttons while the player picks, empty before anything has happened
+fn show_menu_prompt(
+    menu_state: Res<State<MenuState>>,
+    battle_state: Res<State<BattleState>>,
+    locale: Res<Locale>,
+    mut header: Query<&mut Text, With<Battleoptions>>,
+) {
+    if !menu_state.is_changed() && !battle_state.is_changed() && !locale.is_changed() {
+        return;
+    }
+    let prompt = match (battle_state.get(), menu_state.get()) {
+        (BattleState::Intro, _) => String::new(),
+        (BattleState::ChooseAction, MenuState::MainMenu) => locale.text("battle-prompt"),
+        (BattleState::ChooseAction, MenuState::AttackMenu) => locale.text("battle-prompt-attack"),
+        // what the player did stays up while it plays out
+        _ => return,
+    };
+    for mut text in header.iter_mut() {
+        text.sections[0].value = prompt.clone();
+    }
+}
+
+// works out which buttons can be picked and what their tooltips say against the enemy being fought
+fn assess_menu(
+    mut buttons: Query<&mut MenuButton>,
+    player_stat_query: Query<&PlayerStats, With<Player>>,
+    enemy_stat_query: Query<&EnemyStats, With<Enemy>>,
+    commands: Commands,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    player_query: Query<&Transform, With<Player>>,
+    locale: Res<Locale>,
+) {
+    let Some(closest_enemy) = find_closest_enemy(&commands, &enemy_query, &player_query) else {
+        return;
+    };
+    let (Ok(player_stats), Ok(enemy_stats)) = (player_stat_query.get_single(), enemy_stat_query.get(closest_enemy)) else {
+        return;
+    };
+    for mut button in buttons.iter_mut() {
+        let (enabled, tooltip) = assess(button.option, player_stats, enemy_stats, &locale);
+        if button.enabled != enabled || button.tooltip != tooltip {
+            button.enabled = enabled;
+            button.tooltip = tooltip;
+        }
+    }
+}
+
+// labels the buttons, highlights the focused one, greys out the ones that can't be picked and shows the focused one's tooltip
+fn style_menu(
+    focus: Res<MenuFocus>,
+    locale: Res<Locale>,
+    mut buttons: Query<(&MenuButton, &Children, &mut BackgroundColor)>,
+    mut labels: Query<&mut Text, With<MenuLabel>>,
+    mut tooltip: Query<&mut Text, (With<MenuTooltip>, Without<MenuLabel>)>,
+) {
+    let mut focused_tooltip = String::new();
+    for (button, children, mut background) in buttons.iter_mut() {
+        let focused = button.index == focus.0;
+        if focused {
+            focused_tooltip = button.tooltip.clone();
+        }
+        *background = button_color(focused);
+        for child in children.iter() {
+            if let Ok(mut label) = labels.get_mut(*child) {
+                let text = button.option.label(&locale);
+                if label.sections[0].value != text {
+                    label.sections[0].value = text;
                 }
-            }
-            MenuState::Text => {    //puts main attack menu back up after action text was displayed
-                //for _key in input.get_just_pressed() {
-                    //probably need to put in a delay here so the above action texts gets displayed?
-                    text.sections[0].value = "What will you do?\n1. Attack\n2. Magic\n3. Heal\n4. Run".to_string();
-                    next_menu_state.set(MenuState::MainMenu);
-                //}
+                label.sections[0].style.color = label_color(button.enabled);
             }
         }
     }
-
+    for mut text in tooltip.iter_mut() {
+        if text.sections[0].value != focused_tooltip {
+            text.sections[0].value = focused_tooltip.clone();
+        }
+    }
 }
 
+// keys, pad and mouse all end up here: Up/Down or hovering moves the focus, Confirm or a click picks
+fn pick_option(
+    actions: Res<ActionState>,
+    mut focus: ResMut<MenuFocus>,
+    buttons: Query<(&MenuButton, Ref<Interaction>)>,
+    menu_state: Res<State<MenuState>>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+    replay: Res<BattleReplay>,
+    mut action_events: EventWriter<PlayerActionEvent>,
+) {
+    let count = buttons.iter().count();
+    if count == 0 {
+        return;
+    }
+    let picked = pick_button(&mut focus.0, count, &actions, buttons.iter().map(|(button, interaction)| (button.index, interaction)))
+        .and_then(|index| buttons.iter().map(|(button, _)| button).find(|button| button.index == index));
+    if actions.just_pressed(Action::Back) && *menu_state.get() == MenuState::AttackMenu {
+        next_menu_state.set(MenuState::MainMenu);
+        return;
+    }
+
+    let Some(button) = picked else {
+        return;
+    };
+    if !button.enabled {
+        return;
+    }
+    match button.option {
+        MenuOption::Fight => next_menu_state.set(MenuState::AttackMenu),
+        MenuOption::Back => next_menu_state.set(MenuState::MainMenu),
+        // a recorded battle is driving the menu
+        MenuOption::Battle(_) if replay.active() => {}
+        MenuOption::Battle(action) => {
+            action_events.send(PlayerActionEvent(action));
+        }
+    }
+}
 
 fn update_playerhp(
     mut playerhpquery: Query<&mut Text, With<Playerhp>>,        //to change hp textbox
@@ -322,42 +544,41 @@ fn update_enemyhp(
     }
 }
 
-fn update_battledialogue(
-    mut battle_log_query: Query<&mut Text, With<BattleLogTag>>,          //to access the dialogue box
-    battle_dialogue_query: Query<&mut BattleDialogue>,                   //to get the actual dialogue strings
+// shows the LOG_LINES entries of the current battle ending `scroll` lines above the newest one
+fn update_battle_log(
+    mut battle_log_query: Query<(&mut Text, &mut BackgroundColor), With<BattleLogTag>>,  //to access the log panel
+    battle_log: Res<BattleLog>,                                         //to get the actual entries
+    locale: Res<Locale>,
+    settings: Res<Settings>,
 ){
-    if let Ok(battle_dialogue) = battle_dialogue_query.get_single(){
-        let mut counter = 1;
-        for mut text in &mut battle_log_query.iter_mut(){
-            if(counter == 1){
-                //text.sections[0].value = battle_dialogue.dialogue1.clone();
-                text.sections[0].value = battle_dialogue.dialogue1.clone();
-                text.sections[0].style.color = Color::srgb(1.0, 1.0, 1.0);
-            }
-            else if(counter == 2){
-                text.sections[0].value = battle_dialogue.dialogue2.clone();
-                text.sections[0].style.color = Color::srgb(1.0, 1.0, 0.7);
-            }
-            else if(counter == 3){
-                text.sections[0].value = battle_dialogue.dialogue3.clone();
-                text.sections[0].style.color = Color::srgb(1.0, 1.0, 0.0);
-            }
-            counter += 1;   //should only interate 3 times since there are only 3 entites tagged wiith BattleLogTag
-        }
+    if !battle_log.is_changed() && !locale.is_changed() && !settings.is_changed() {
+        return;
     }
-}
+    let high_contrast = settings.high_contrast_log;
+    let entries = battle_log.current();
+    let end = entries.len() - battle_log.scroll.min(entries.len());
+    let start = end.saturating_sub(LOG_LINES);
 
-fn reset_battle_dialogue(
-    mut battle_log_query: Query<&mut Text, With<BattleLogTag>>,          //to access the dialogue box
-){
-    for mut text in &mut battle_log_query.iter_mut(){
-        //text.sections[0].value = battle_dialogue.dialogue1.clone();
-        text.sections[0].value = "".to_string();
-        text.sections[0].style.color = Color::srgb(1.0, 1.0, 1.0);
-        text.sections[0].value = "".to_string();
-        text.sections[0].style.color = Color::srgb(1.0, 1.0, 0.7);
-        text.sections[0].value = ">Battle Start".to_string();
-        text.sections[0].style.color = Color::srgb(1.0, 1.0, 0.0);
+    for (mut text, mut background) in battle_log_query.iter_mut(){
+        // high contrast puts the log on a dark panel in pure white and yellow
+        *background = BackgroundColor(if high_contrast { Color::srgba(0., 0., 0., 0.85) } else { Color::NONE });
+        text.sections[0].value = if start > 0 { locale.text("log-scroll-hint") + "\n" } else { "".to_string() };
+        text.sections[0].style.color = if high_contrast { Color::srgb(0.85, 0.85, 0.85) } else { Color::srgb(0.6, 0.6, 0.6) };
+        for line in 0..LOG_LINES {
+            let section = &mut text.sections[line + 1];
+            section.value = match entries.get(start + line) {
+                Some(entry) if start + line < end => entry.describe(&locale) + "\n",
+                _ => "".to_string(),
+            };
+            // newest line in yellow, the one before it pale yellow, older ones white
+            section.style.color = if start + line + 1 == entries.len() {
+                Color::srgb(1.0, 1.0, 0.0)
+            } else if start + line + 2 == entries.len() && !high_contrast {
+                Color::srgb(1.0, 1.0, 0.7)
+            } else {
+                Color::srgb(1.0, 1.0, 1.0)
+            };
+        }
     }
 }
 
@@ -374,11 +595,10 @@ fn show_textbox(
 
     /*let pt = player.single();
     let mut bt = background.single_mut();
-    let x_bound = LEVEL_W / 2. - WIN_W / 2.;
-    let y_bound = LEVEL_H / 2. - WIN_H / 2.;
+    let screen = bounds.screen_center(pt.translation);
 
-    bt.translation.x = pt.translation.x.clamp(-x_bound, x_bound);   // same logic as camera/player movement
-    bt.translation.y = pt.translation.y.clamp(-y_bound, y_bound);   // same logic as camera/player movement
+    bt.translation.x = screen.x;
+    bt.translation.y = screen.y;
     bt.translation.z = pt.translation.z + 1.; */ 
 }
 
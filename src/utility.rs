@@ -0,0 +1,244 @@
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::brain::{BattleSnapshot, EnemyAction};
+use crate::battle_log::{Actor, BattleAction};
+
+// designers tune enemy behaviour here, read once at startup into the UtilityConfig resource
+pub const UTILITY_CONFIG: &str = "assets/utility_ai.ron";
+
+const NEVER_USED: f32 = 99.;    // TurnsSinceUsed for a move not made yet this battle
+
+// Something an enemy looks at when weighing a move
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Consideration {
+    OwnHp,              // the enemy's hp as a fraction of its max
+    TargetHp,           // the player's hp as a fraction of their max
+    StatEdge,           // enemy physatk minus mgkatk
+    TargetDefenseGap,   // player mdef minus def, positive when physical hits are the better bet
+    TurnsSinceUsed,     // enemy turns since it last made this move, from the battle log
+    OwnFocus,           // focus the enemy has built up by guarding
+    TargetFocus,        // focus the player has built up, about to be spent on an attack
+    TargetGuarding,     // 1 while the player's guard is up, 0 otherwise
+}
+
+// Turns a consideration's raw value into a score
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Curve {
+    Linear { slope: f32, min: f32, max: f32 },
+    // the value of the first step the input is at or under, otherwise `above`
+    Steps { steps: Vec<(f32, f32)>, above: f32 },
+    Logistic { midpoint: f32, steepness: f32, scale: f32 },
+}
+
+impl Curve {
+    pub fn evaluate(&self, x: f32) -> f32 {
+        match self {
+            Curve::Linear { slope, min, max } => (slope * x).clamp(*min, *max),
+            Curve::Steps { steps, above } => steps
+                .iter()
+                .find(|(upto, _)| x <= *upto)
+                .map(|(_, value)| *value)
+                .unwrap_or(*above),
+            Curve::Logistic { midpoint, steepness, scale } => scale / (1. + (-steepness * (x - midpoint)).exp()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Factor {
+    pub consideration: Consideration,
+    pub curve: Curve,
+    pub weight: f32,
+    // a vetoing factor that scores zero or less rules the move out entirely
+    #[serde(default)]
+    pub veto: bool,
+}
+
+impl Factor {
+    fn new(consideration: Consideration, curve: Curve, weight: f32) -> Self {
+        Self { consideration, curve, weight, veto: false }
+    }
+}
+
+// The factors scored for each move; a move's utility is the weighted sum of its factors
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct UtilityConfig {
+    pub attack: Vec<Factor>,
+    pub magic: Vec<Factor>,
+    pub heal: Vec<Factor>,
+    #[serde(default)]
+    pub guard: Vec<Factor>,
+    #[serde(default)]
+    pub wait: Vec<Factor>,
+}
+
+// the weights ai_attack used to have hard coded; its 0.9 and 0.2 cut-offs were strict, the others were not
+impl Default for UtilityConfig {
+    fn default() -> Self {
+        let finisher = Curve::Steps { steps: vec![(0.2f32.next_down(), 10.)], above: 0. };
+        Self {
+            attack: vec![
+                Factor::new(Consideration::StatEdge, Curve::Linear { slope: 0.5, min: 0., max: 10. }, 1.),
+                Factor::new(Consideration::TargetDefenseGap, Curve::Linear { slope: 0.5, min: 0., max: 10. }, 1.),
+                Factor::new(Consideration::TargetHp, finisher.clone(), 1.),
+            ],
+            magic: vec![
+                Factor::new(Consideration::StatEdge, Curve::Linear { slope: -0.5, min: 0., max: 10. }, 1.),
+                Factor::new(Consideration::TargetDefenseGap, Curve::Linear { slope: -0.5, min: 0., max: 10. }, 1.),
+                Factor::new(Consideration::TargetHp, finisher, 1.),
+            ],
+            heal: vec![Factor {
+                consideration: Consideration::OwnHp,
+                curve: Curve::Steps { steps: vec![(0.3, 6.), (0.6, 2.), (0.9f32.next_down(), 1.)], above: 0. },
+                weight: 1.,
+                veto: true,
+            }],
+            // brace when the player is about to unload their focus, unless already at full focus
+            guard: vec![
+                Factor::new(Consideration::TargetFocus, Curve::Linear { slope: 2., min: 0., max: 6. }, 1.),
+                Factor {
+                    consideration: Consideration::OwnFocus,
+                    curve: Curve::Steps { steps: vec![(2., 1.)], above: 0. },
+                    weight: 0.,
+                    veto: true,
+                },
+            ],
+            // let the player's guard drop before hitting into it
+            wait: vec![Factor::new(Consideration::TargetGuarding, Curve::Linear { slope: 6., min: 0., max: 6. }, 1.)],
+        }
+    }
+}
+
+impl UtilityConfig {
+    // the designer's config if it is there and parses, the built-in weights otherwise
+    pub fn load() -> Self {
+        let text = match fs::read_to_string(UTILITY_CONFIG) {
+            Ok(text) => text,
+            Err(_) => return Self::default(),
+        };
+        match ron::from_str(&text) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Could not parse {}: {}, using the built-in weights", UTILITY_CONFIG, e);
+                Self::default()
+            }
+        }
+    }
+
+    // utility of each move, indexed by EnemyAction; vetoed moves score -infinity
+    pub fn score(&self, snapshot: &BattleSnapshot) -> [f32; 5] {
+        let mut scores = [0.; 5];
+        let mut explanation = Vec::new();
+        for (i, action) in EnemyAction::ALL.iter().enumerate() {
+            let factors = match action {
+                EnemyAction::Attack => &self.attack,
+                EnemyAction::Magic => &self.magic,
+                EnemyAction::Heal => &self.heal,
+                EnemyAction::Guard => &self.guard,
+                EnemyAction::Wait => &self.wait,
+            };
+            let mut terms = Vec::new();
+            for factor in factors.iter() {
+                let input = consideration_value(factor.consideration, *action, snapshot);
+                let value = factor.curve.evaluate(input);
+                terms.push(format!("{:?} {:.2} -> {:.2}x{}", factor.consideration, input, value, factor.weight));
+                if factor.veto && value <= 0. {
+                    scores[i] = f32::NEG_INFINITY;
+                    terms.push("vetoed".to_string());
+                    break;
+                }
+                scores[i] += factor.weight * value;
+            }
+            explanation.push(format!("{:?} {:.2} [{}]", action, scores[i], terms.join(", ")));
+        }
+        debug!("utility: {}", explanation.join("; "));
+        scores
+    }
+}
+
+fn consideration_value(consideration: Consideration, action: EnemyAction, snapshot: &BattleSnapshot) -> f32 {
+    let player = snapshot.player_stats;
+    let enemy = snapshot.enemy_stats;
+    match consideration {
+        Consideration::OwnHp => enemy.hp as f32 / enemy.max_hp.max(1) as f32,
+        Consideration::TargetHp => player.hp as f32 / player.max_hp.max(1) as f32,
+        Consideration::StatEdge => enemy.physatk as f32 - enemy.mgkatk as f32,
+        Consideration::TargetDefenseGap => player.mdef as f32 - player.def as f32,
+        Consideration::TurnsSinceUsed => {
+            let logged = match action {
+                EnemyAction::Attack => BattleAction::Attack,
+                EnemyAction::Magic => BattleAction::Magic,
+                EnemyAction::Heal => BattleAction::Heal,
+                EnemyAction::Guard => BattleAction::Guard,
+                EnemyAction::Wait => BattleAction::Wait,
+            };
+            snapshot.battle_log.current()
+                .iter()
+                .filter(|entry| entry.actor == Actor::Enemy)
+                .rev()
+                .position(|entry| entry.action == logged)
+                .map(|turns| turns as f32)
+                .unwrap_or(NEVER_USED)
+        }
+        Consideration::OwnFocus => enemy.focus as f32,
+        Consideration::TargetFocus => player.focus as f32,
+        Consideration::TargetGuarding => if player.guarding { 1. } else { 0. },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::PlayerStats;
+    use crate::enemy::EnemyStats;
+    use crate::battle_log::BattleLog;
+
+    // scores for an enemy at `own_hp` of its health against a healthy player
+    fn scores_at(own_hp: f32) -> [f32; 5] {
+        let player = PlayerStats::new();
+        let mut enemy = EnemyStats::new(2);
+        enemy.max_hp = 100;
+        enemy.hp = (own_hp * 100.).round() as u32;
+        let mut battle_log = BattleLog::default();
+        battle_log.start_battle();
+        let snapshot = BattleSnapshot { player_stats: &player, enemy_stats: &enemy, battle_log: &battle_log };
+        UtilityConfig::default().score(&snapshot)
+    }
+
+    fn score(scores: &[f32; 5], action: EnemyAction) -> f32 {
+        scores[action as usize]
+    }
+
+    #[test]
+    fn no_healing_at_ninety_percent() {
+        let scores = scores_at(0.9);
+        assert_eq!(score(&scores, EnemyAction::Heal), f32::NEG_INFINITY);
+        assert!(score(&scores, EnemyAction::Attack) > score(&scores, EnemyAction::Heal));
+        assert_eq!(score(&scores_at(0.89), EnemyAction::Heal), 1.);
+    }
+
+    #[test]
+    fn healing_weighs_more_at_sixty_percent() {
+        let scores = scores_at(0.6);
+        assert_eq!(score(&scores, EnemyAction::Heal), 2.);
+        assert!(score(&scores, EnemyAction::Heal) > score(&scores, EnemyAction::Attack));
+        assert!(score(&scores, EnemyAction::Heal) > score(&scores, EnemyAction::Magic));
+    }
+
+    #[test]
+    fn healing_wins_at_thirty_percent() {
+        let scores = scores_at(0.3);
+        assert_eq!(score(&scores, EnemyAction::Heal), 6.);
+        assert!(EnemyAction::ALL.iter().all(|action| score(&scores, *action) <= score(&scores, EnemyAction::Heal)));
+    }
+
+    #[test]
+    fn finisher_needs_the_player_under_a_fifth() {
+        let finisher = &UtilityConfig::default().attack[2].curve;
+        assert_eq!(finisher.evaluate(0.2), 0.);
+        assert_eq!(finisher.evaluate(0.19), 10.);
+    }
+}
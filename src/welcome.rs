@@ -1,7 +1,7 @@
 use bevy::prelude::*;
-use bevy::input::common_conditions::input_just_pressed;
 
 use crate::GameState;
+use crate::controls::{Action, action_just_pressed};
 
 
 #[derive(Component)]
@@ -12,7 +12,8 @@ pub struct WelcomePlugin;
 impl Plugin for WelcomePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, show_welcome);
-        app.add_systems(Update, hide_welcome.run_if(input_just_pressed(KeyCode::Space)));
+        app.add_systems(OnEnter(GameState::Welcome), show_title);
+        app.add_systems(Update, hide_welcome.run_if(action_just_pressed(Action::Confirm)));
     }
 }
 
@@ -30,6 +31,9 @@ fn show_welcome(
         GameState::SkillTreeMenu => next_state.set(GameState::Welcome),
         GameState::EndCredits => next_state.set(GameState::EndCredits),
         GameState::DefeatScreen => next_state.set(GameState::DefeatScreen),
+        GameState::MapView => next_state.set(GameState::Welcome),
+        GameState::Journal => next_state.set(GameState::Welcome),
+        GameState::Controls => next_state.set(GameState::Welcome),
     }
     let welcome_texture_handle: Handle<Image> = asset_server.load("welcomeScreen.png");
     
@@ -51,6 +55,13 @@ fn show_welcome(
     }
 }
 
+// back at the title screen, e.g. after quitting to it from the pause menu
+fn show_title(mut query: Query<&mut Visibility, With<WelcomeSreen>>) {
+    for mut visibility in query.iter_mut() {
+        *visibility = Visibility::Visible;
+    }
+}
+
 fn hide_welcome(
     state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
@@ -67,5 +78,8 @@ fn hide_welcome(
         GameState::SkillTreeMenu => next_state.set(GameState::SkillTreeMenu),
         GameState::EndCredits => next_state.set(GameState::EndCredits),
         GameState::DefeatScreen => next_state.set(GameState::DefeatScreen),
+        GameState::MapView => next_state.set(GameState::MapView),
+        GameState::Journal => next_state.set(GameState::Journal),
+        GameState::Controls => next_state.set(GameState::Controls),
     }
 }